@@ -0,0 +1,493 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use alloy::primitives::Address;
+use kamu_node_api_client::{AccountID, DatasetAccessRole, DatasetID, DatasetRoleOperation, DidPhk};
+use molecule_ocl::entities::{OclId, OclOwnershipProjection, OclOwnershipProjectionMap};
+use serde::Serialize;
+
+use crate::access_policy::{
+    AccessPolicies, AccessPolicy, DatasetKind, MultisigAccessPolicy, PrincipalKind, highest_role,
+    lowest_role,
+};
+use crate::state::{ChainState, MultisigState, OclHoldersState, OffChainMoleculeProjectProjection};
+
+#[derive(Debug, Default)]
+pub struct GetOwnersResponse {
+    pub current_owners: HashMap<Address, OwnerAccess>,
+    pub former_owners: HashSet<Address>,
+}
+
+impl GetOwnersResponse {
+    /// Keeps the least limited access if the account is already an owner
+    fn add_current_owner(&mut self, address: Address, owner_access: OwnerAccess) {
+        self.current_owners
+            .entry(address)
+            .and_modify(|current_owner_access| {
+                if highest_role(current_owner_access.max_role, owner_access.max_role)
+                    != current_owner_access.max_role
+                {
+                    *current_owner_access = owner_access;
+                }
+            })
+            .or_insert(owner_access);
+    }
+}
+
+/// How an account owns an OCL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OwnerAccess {
+    /// Either [`PrincipalKind::Owner`] or [`PrincipalKind::MultisigSigner`]
+    pub kind: PrincipalKind,
+    /// Upper bound of the roles granted by the access policy
+    /// (see [`MultisigAccessPolicy`])
+    pub max_role: DatasetAccessRole,
+}
+
+impl OwnerAccess {
+    const OWNER: Self = Self {
+        kind: PrincipalKind::Owner,
+        max_role: DatasetAccessRole::Maintainer,
+    };
+}
+
+/// Accounts involved in an OCL access update
+#[derive(Debug, Default)]
+pub struct OclAccounts {
+    /// Get the owner roles of the access policy
+    pub current_owners: HashMap<Address, OwnerAccess>,
+    /// Lose the owner roles
+    pub revoke_access_accounts: HashSet<Address>,
+    /// Get the holder roles of the access policy
+    pub current_holders: HashSet<Address>,
+    /// Lose the holder roles
+    pub revoke_holder_access_accounts: HashSet<Address>,
+}
+
+impl OclAccounts {
+    /// Returns the role the account should have for a dataset of the kind:
+    /// the highest one among its principal kinds
+    pub fn desired_role(
+        &self,
+        address: Address,
+        dataset_kind: &DatasetKind,
+        access_policy: &AccessPolicy,
+    ) -> Option<DatasetAccessRole> {
+        let roles = access_policy.roles(dataset_kind);
+
+        let maybe_owner_role = self.current_owners.get(&address).and_then(|owner_access| {
+            roles
+                .role(owner_access.kind)
+                .map(|role| lowest_role(role, owner_access.max_role))
+        });
+        let maybe_holder_role = roles
+            .holder
+            .filter(|_| self.current_holders.contains(&address));
+
+        match (maybe_owner_role, maybe_holder_role) {
+            (Some(owner_role), Some(holder_role)) => Some(highest_role(owner_role, holder_role)),
+            (maybe_owner_role, maybe_holder_role) => maybe_owner_role.or(maybe_holder_role),
+        }
+    }
+
+    /// Returns the operation that brings the role of the account for a dataset
+    /// of the kind in line with the access policy: either the desired role,
+    /// or revoking the access that the account has lost
+    pub fn role_operation(
+        &self,
+        address: Address,
+        dataset_kind: &DatasetKind,
+        access_policy: &AccessPolicy,
+    ) -> Option<DatasetRoleOperation> {
+        if let Some(role) = self.desired_role(address, dataset_kind, access_policy) {
+            return Some(DatasetRoleOperation::Set(role));
+        }
+
+        let roles = access_policy.roles(dataset_kind);
+        let lost_owner_role = self.revoke_access_accounts.contains(&address)
+            && (roles.owner.is_some() || roles.multisig_signer.is_some());
+        let lost_holder_role =
+            self.revoke_holder_access_accounts.contains(&address) && roles.holder.is_some();
+
+        (lost_owner_role || lost_holder_role).then_some(DatasetRoleOperation::Unset)
+    }
+
+    pub fn all_addresses(&self) -> impl Iterator<Item = Address> {
+        self.current_owners
+            .keys()
+            .chain(&self.revoke_access_accounts)
+            .chain(&self.current_holders)
+            .chain(&self.revoke_holder_access_accounts)
+            .copied()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ProjectDatasetIds<'a> {
+    /// Along with the kind that defines the access to them
+    pub dataset_ids: Vec<(&'a DatasetID, DatasetKind)>,
+    pub removed_file_dataset_ids: Vec<&'a DatasetID>,
+}
+
+impl<'a> ProjectDatasetIds<'a> {
+    pub fn all_dataset_ids(&self) -> impl Iterator<Item = &'a DatasetID> + '_ {
+        self.dataset_ids
+            .iter()
+            .map(|(dataset_id, _)| *dataset_id)
+            .chain(self.removed_file_dataset_ids.iter().copied())
+    }
+}
+
+pub fn ocl_owner_addresses(
+    on_chain_ocl_ownership: &OclOwnershipProjection,
+) -> impl Iterator<Item = Address> + '_ {
+    on_chain_ocl_ownership
+        .current
+        .iter()
+        .chain(on_chain_ocl_ownership.previous.iter())
+        .copied()
+}
+
+/// OCL as seen on one of the chains
+pub struct OnChainOcl<'a> {
+    pub chain_id: u64,
+    pub ownership: &'a OclOwnershipProjection,
+    pub owners_resolver: OwnersResolver<'a>,
+    pub maybe_holders: Option<&'a OclHoldersState>,
+    pub access_policy: &'a AccessPolicy,
+}
+
+pub fn get_on_chain_ocls<'a>(
+    chains: &'a BTreeMap<u64, ChainState>,
+    access_policies: &'a AccessPolicies,
+    ocl_id: OclId,
+) -> Vec<OnChainOcl<'a>> {
+    chains
+        .iter()
+        .filter_map(|(chain_id, chain_state)| {
+            let ownership = chain_state
+                .on_chain_ocl_ownership_projection_map
+                .get(&ocl_id)?;
+
+            Some(OnChainOcl {
+                chain_id: *chain_id,
+                ownership,
+                owners_resolver: OwnersResolver {
+                    multisig: &chain_state.multisig,
+                    ocl_ownership_projection_map: &chain_state
+                        .on_chain_ocl_ownership_projection_map,
+                    multisig_access_policy: access_policies.multisig.get(*chain_id, ocl_id),
+                },
+                maybe_holders: chain_state.ocl_holders.get(&ocl_id),
+                access_policy: &access_policies.access,
+            })
+        })
+        .collect()
+}
+
+/// Resolves an OCL owner address to the accounts behind it along with their
+/// access: multisig owners (limited by the multisig access policy of the OCL),
+/// or owners of the OCL for a token-bound account.
+///
+/// Expects all involved addresses to be resolved beforehand
+/// (see `App::resolve_multisigs()`)
+pub struct OwnersResolver<'a> {
+    multisig: &'a HashMap<Address, Option<MultisigState>>,
+    ocl_ownership_projection_map: &'a OclOwnershipProjectionMap,
+    multisig_access_policy: &'a MultisigAccessPolicy,
+}
+
+impl OwnersResolver<'_> {
+    pub fn get_owners(&self, address: Address) -> GetOwnersResponse {
+        self.get_owners_impl(address, &mut HashSet::new())
+    }
+
+    fn get_owners_impl(
+        &self,
+        address: Address,
+        token_bound_accounts_path: &mut HashSet<Address>,
+    ) -> GetOwnersResponse {
+        if let Some((ocl_id, ownership_projection)) = self
+            .ocl_ownership_projection_map
+            .find_by_identity_account(address)
+        {
+            return self.get_token_bound_account_owners(
+                address,
+                *ocl_id,
+                ownership_projection,
+                token_bound_accounts_path,
+            );
+        }
+
+        match self.multisig.get(&address) {
+            Some(Some(multisig_state)) => self.get_multisig_owners(address, multisig_state),
+            Some(None) => GetOwnersResponse {
+                current_owners: HashMap::from([(address, OwnerAccess::OWNER)]),
+                former_owners: HashSet::new(),
+            },
+            None => {
+                tracing::warn!(%address, "Address was not resolved, considering it a regular wallet");
+
+                GetOwnersResponse {
+                    current_owners: HashMap::from([(address, OwnerAccess::OWNER)]),
+                    former_owners: HashSet::new(),
+                }
+            }
+        }
+    }
+
+    fn get_multisig_owners(
+        &self,
+        address: Address,
+        multisig_state: &MultisigState,
+    ) -> GetOwnersResponse {
+        let mut response = GetOwnersResponse {
+            former_owners: multisig_state.former_owners.clone(),
+            ..Default::default()
+        };
+
+        // NOTE: Signers without a role lose their access, e.g. when the threshold is raised.
+        match self
+            .multisig_access_policy
+            .signer_role(multisig_state.threshold)
+        {
+            Some(max_role) => {
+                for owner in &multisig_state.current_owners {
+                    response.add_current_owner(
+                        *owner,
+                        OwnerAccess {
+                            kind: PrincipalKind::MultisigSigner,
+                            max_role,
+                        },
+                    );
+                }
+            }
+            None => {
+                response
+                    .former_owners
+                    .extend(&multisig_state.current_owners);
+            }
+        }
+
+        // NOTE: The role of the multisig itself only depends on the configuration,
+        //       so a role that is no longer configured is revoked by reconciliation.
+        if let Some(max_role) = self.multisig_access_policy.multisig_role {
+            response.add_current_owner(
+                address,
+                OwnerAccess {
+                    kind: PrincipalKind::Owner,
+                    max_role,
+                },
+            );
+        }
+
+        response
+    }
+
+    /// Token-bound account is controlled by the owner of the OCL it belongs to
+    fn get_token_bound_account_owners(
+        &self,
+        address: Address,
+        ocl_id: OclId,
+        ownership_projection: &OclOwnershipProjection,
+        token_bound_accounts_path: &mut HashSet<Address>,
+    ) -> GetOwnersResponse {
+        if !token_bound_accounts_path.insert(address) {
+            // NOTE: E.g. an OCL that is owned by its own token-bound account.
+            tracing::warn!(%address, %ocl_id, "Token-bound account ownership cycle, no owners");
+
+            return GetOwnersResponse::default();
+        }
+
+        let mut response = ownership_projection
+            .current
+            .map(|current_owner| self.get_owners_impl(current_owner, token_bound_accounts_path))
+            .unwrap_or_default();
+
+        for previous in &ownership_projection.previous {
+            let GetOwnersResponse {
+                current_owners: former_owners_1,
+                former_owners: former_owners_2,
+            } = self.get_owners_impl(*previous, token_bound_accounts_path);
+            response.former_owners.extend(former_owners_1.into_keys());
+            response.former_owners.extend(former_owners_2);
+        }
+
+        token_bound_accounts_path.remove(&address);
+
+        response
+    }
+}
+
+pub fn get_accounts_by_ocl_project(
+    on_chain_ocl_ownership: &OclOwnershipProjection,
+    owners_resolver: &OwnersResolver<'_>,
+    maybe_holders: Option<&OclHoldersState>,
+) -> OclAccounts {
+    let mut accounts = OclAccounts::default();
+
+    if let Some(current_owner) = &on_chain_ocl_ownership.current {
+        let GetOwnersResponse {
+            current_owners: new_owners,
+            former_owners,
+        } = owners_resolver.get_owners(*current_owner);
+        accounts.current_owners.extend(new_owners);
+        accounts.revoke_access_accounts.extend(former_owners);
+    }
+
+    for previous in &on_chain_ocl_ownership.previous {
+        let GetOwnersResponse {
+            current_owners: former_owners_1,
+            former_owners: former_owners_2,
+        } = owners_resolver.get_owners(*previous);
+        accounts
+            .revoke_access_accounts
+            .extend(former_owners_1.into_keys());
+        accounts.revoke_access_accounts.extend(former_owners_2);
+    }
+
+    accounts
+        .revoke_holder_access_accounts
+        .clone_from(&accounts.revoke_access_accounts);
+
+    if let Some(holders) = maybe_holders {
+        accounts
+            .current_holders
+            .extend(holders.balances.keys().copied());
+        accounts
+            .revoke_holder_access_accounts
+            .extend(holders.former_holders.iter().copied());
+    }
+
+    account_access_sanity_checks(&mut accounts);
+
+    accounts
+}
+
+pub fn account_access_sanity_checks(accounts: &mut OclAccounts) {
+    // NOTE: Accounts only lose the roles of the principal kinds they don't belong to anymore,
+    //       e.g. a former holder keeps the owner roles (see `OclAccounts::role_operation()`).
+    for owner in accounts.current_owners.keys() {
+        accounts.revoke_access_accounts.remove(owner);
+    }
+    for holder in &accounts.current_holders {
+        accounts.revoke_holder_access_accounts.remove(holder);
+    }
+}
+
+pub fn get_project_dataset_ids(
+    off_chain_project: &OffChainMoleculeProjectProjection,
+) -> ProjectDatasetIds<'_> {
+    let mut dataset_ids = vec![
+        (
+            &off_chain_project.entry.data_room_dataset_id,
+            DatasetKind::DataRoom,
+        ),
+        (
+            &off_chain_project.entry.announcements_dataset_id,
+            DatasetKind::Announcements,
+        ),
+    ];
+    dataset_ids.extend(off_chain_project.actual_files_map.iter().map(
+        |(dataset_id, entry_with_access_level)| {
+            (
+                dataset_id,
+                DatasetKind::File(entry_with_access_level.molecule_access_level.clone()),
+            )
+        },
+    ));
+
+    ProjectDatasetIds {
+        dataset_ids,
+        removed_file_dataset_ids: off_chain_project.removed_files_map.keys().collect(),
+    }
+}
+
+/// Returns datasets of the project that are readable by everyone
+pub fn get_public_dataset_ids(
+    off_chain_project: &OffChainMoleculeProjectProjection,
+    access_policy: &AccessPolicy,
+) -> Vec<DatasetID> {
+    get_project_dataset_ids(off_chain_project)
+        .dataset_ids
+        .into_iter()
+        .filter(|(_, dataset_kind)| access_policy.is_public(dataset_kind))
+        .map(|(dataset_id, _)| dataset_id.clone())
+        .collect()
+}
+
+/// Role that an account should have for a dataset of an OCL project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DesiredAccess {
+    pub dataset_id: DatasetID,
+    pub account_id: AccountID,
+    pub chain_id: u64,
+    pub address: Address,
+    pub role: DatasetAccessRole,
+    pub ocl_id: OclId,
+    pub reason: AccessReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AccessReason {
+    /// The account owns the OCL: directly, as a multisig owner or through a
+    /// token-bound account
+    OclOwner,
+    /// The account holds the IP-token of the OCL
+    IpTokenHolder,
+}
+
+/// Returns roles that the current owners and holders of the OCL should have
+/// on the chain.
+///
+/// Follows the same rules as the access granting (see [`AccessPolicy`]).
+pub fn get_desired_ocl_access(
+    off_chain_project: &OffChainMoleculeProjectProjection,
+    on_chain_ocl: &OnChainOcl<'_>,
+) -> eyre::Result<Vec<DesiredAccess>> {
+    let accounts = get_accounts_by_ocl_project(
+        on_chain_ocl.ownership,
+        &on_chain_ocl.owners_resolver,
+        on_chain_ocl.maybe_holders,
+    );
+    let ProjectDatasetIds { dataset_ids, .. } = get_project_dataset_ids(off_chain_project);
+
+    let chain_id = on_chain_ocl.chain_id;
+    let ocl_id = off_chain_project.entry.ocl_id;
+    let mut desired_access = Vec::new();
+
+    let addresses = accounts
+        .current_owners
+        .keys()
+        .chain(&accounts.current_holders)
+        .copied()
+        .collect::<HashSet<_>>();
+
+    for address in addresses {
+        let account_id = DidPhk::new_from_chain_id(chain_id, address)?.to_string();
+        let reason = if accounts.current_owners.contains_key(&address) {
+            AccessReason::OclOwner
+        } else {
+            AccessReason::IpTokenHolder
+        };
+
+        for (dataset_id, dataset_kind) in &dataset_ids {
+            let Some(role) =
+                accounts.desired_role(address, dataset_kind, on_chain_ocl.access_policy)
+            else {
+                continue;
+            };
+
+            desired_access.push(DesiredAccess {
+                dataset_id: (*dataset_id).clone(),
+                account_id: account_id.clone(),
+                chain_id,
+                address,
+                role,
+                ocl_id,
+                reason,
+            });
+        }
+    }
+
+    Ok(desired_access)
+}
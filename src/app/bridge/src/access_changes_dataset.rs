@@ -0,0 +1,134 @@
+use alloy::primitives::B256;
+use chrono::{DateTime, Utc};
+use kamu_node_api_client::{AccountID, DatasetAccessRole, DatasetID, DatasetRoleOperation};
+use molecule_ocl::entities::OclId;
+use serde::Serialize;
+
+use crate::access_query::{ProjectDataset, find_project_dataset};
+use crate::audit_log::{AccessAuditLog, AccessAuditRecord, AccessChangeCause, AccessChangeResult};
+use crate::state::AppState;
+
+/// Row of the access changes dataset in Kamu Node: one per applied operation
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct AccessChangeRow {
+    /// ID of the audit log record: operations applied in one batch share it
+    pub change_id: u64,
+    pub event_time: DateTime<Utc>,
+    /// `None` if the dataset doesn't belong to any known project
+    pub ocl_id: Option<OclId>,
+    pub symbol: Option<String>,
+    pub account_id: AccountID,
+    pub dataset_id: DatasetID,
+    pub operation: AccessChangeOperation,
+    /// `None` for the `unset` operation
+    pub role: Option<DatasetAccessRole>,
+    pub reason: String,
+    /// The latest on-chain event that caused the change
+    pub source_chain_id: Option<u64>,
+    pub source_block_number: Option<u64>,
+    pub source_tx_hash: Option<B256>,
+    /// Offset of the data room up to which the project file changes were loaded
+    pub source_data_room_offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessChangeOperation {
+    Set,
+    Unset,
+}
+
+/// Reads up to `limit` audit log records that were not pushed into the dataset
+/// yet.
+///
+/// The cursor is reset if the log is behind it: e.g. the log is kept in memory
+/// only, so it starts from scratch after a restart, while the cursor is
+/// restored from the state snapshot.
+pub async fn read_unpushed_audit_log_records(
+    audit_log: &dyn AccessAuditLog,
+    next_pushed_record_id: &mut u64,
+    limit: usize,
+) -> eyre::Result<Vec<AccessAuditRecord>> {
+    let next_record_id = audit_log.next_id().await?;
+    if *next_pushed_record_id > next_record_id {
+        tracing::warn!(
+            next_pushed_record_id = *next_pushed_record_id,
+            next_record_id,
+            "Audit log is behind the pushed records, pushing it from the start",
+        );
+        *next_pushed_record_id = 0;
+    }
+
+    audit_log.read(*next_pushed_record_id, limit).await
+}
+
+/// Flattens the record into dataset rows. Failed changes and changes that were
+/// not applied in dry-run mode produce no rows.
+///
+/// OCLs of the datasets are resolved using the current state, so that rows of
+/// reconciliation are attributed to projects as well.
+pub fn build_access_change_rows(
+    app_state: &AppState,
+    record: &AccessAuditRecord,
+) -> Vec<AccessChangeRow> {
+    if let AccessChangeResult::Failed { .. } | AccessChangeResult::DryRun = record.result {
+        return Vec::new();
+    }
+
+    let maybe_cause_ocl_id = match &record.cause {
+        AccessChangeCause::OclChanged { ocl_id, .. }
+        | AccessChangeCause::OclFullUpdate { ocl_id }
+        | AccessChangeCause::OclIgnored { ocl_id } => Some(*ocl_id),
+        AccessChangeCause::Reconciliation | AccessChangeCause::AccessPolicyChanged => None,
+    };
+    let (maybe_on_chain_trigger, maybe_off_chain_trigger) = match &record.cause {
+        AccessChangeCause::OclChanged {
+            on_chain,
+            off_chain,
+            ..
+        } => (on_chain.last(), off_chain.as_ref()),
+        AccessChangeCause::OclFullUpdate { .. }
+        | AccessChangeCause::Reconciliation
+        | AccessChangeCause::OclIgnored { .. }
+        | AccessChangeCause::AccessPolicyChanged => (None, None),
+    };
+
+    record
+        .operations
+        .iter()
+        .map(|operation| {
+            let maybe_ocl_id = maybe_cause_ocl_id.or_else(|| {
+                find_project_dataset(app_state, &operation.dataset_id)
+                    .map(|ProjectDataset { ocl_id, .. }| ocl_id)
+            });
+            let maybe_symbol = maybe_ocl_id.and_then(|ocl_id| {
+                app_state
+                    .off_chain_ocl_project_map
+                    .get(&ocl_id)
+                    .map(|project| project.entry.symbol.clone())
+            });
+
+            let (operation_kind, maybe_role) = match operation.operation {
+                DatasetRoleOperation::Set(role) => (AccessChangeOperation::Set, Some(role)),
+                DatasetRoleOperation::Unset => (AccessChangeOperation::Unset, None),
+            };
+
+            AccessChangeRow {
+                change_id: record.id,
+                event_time: record.recorded_at,
+                ocl_id: maybe_ocl_id,
+                symbol: maybe_symbol,
+                account_id: operation.account_id.clone(),
+                dataset_id: operation.dataset_id.clone(),
+                operation: operation_kind,
+                role: maybe_role,
+                reason: record.reason.clone(),
+                source_chain_id: maybe_on_chain_trigger.map(|trigger| trigger.chain_id),
+                source_block_number: maybe_on_chain_trigger.map(|trigger| trigger.block_number),
+                source_tx_hash: maybe_on_chain_trigger.and_then(|trigger| trigger.tx_hash),
+                source_data_room_offset: maybe_off_chain_trigger
+                    .map(|trigger| trigger.data_room_offset),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use kamu_node_api_client::{DatasetAccessRole, MoleculeAccessLevel};
+use molecule_ocl::entities::OclId;
+use serde::{Deserialize, Serialize};
+
+/// Policies the access to project datasets is derived with
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct AccessPolicies {
+    pub access: AccessPolicy,
+    pub multisig: MultisigAccessPolicies,
+}
+
+/// Access policies shared with the HTTP API, replaced when the config is
+/// reloaded
+#[derive(Debug, Default)]
+pub struct SharedAccessPolicies(RwLock<Arc<AccessPolicies>>);
+
+impl SharedAccessPolicies {
+    pub fn new(access_policies: AccessPolicies) -> Self {
+        Self(RwLock::new(Arc::new(access_policies)))
+    }
+
+    /// Returns the current policies. They are not affected by later
+    /// replacements.
+    pub fn get(&self) -> Arc<AccessPolicies> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Returns the replaced policies
+    pub fn replace(&self, access_policies: AccessPolicies) -> Arc<AccessPolicies> {
+        std::mem::replace(&mut *self.0.write().unwrap(), Arc::new(access_policies))
+    }
+}
+
+/// Kind of accounts that get access to project datasets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrincipalKind {
+    /// Owner of the OCL: a wallet, the multisig itself or the owner behind a
+    /// token-bound account
+    Owner,
+    /// Signer of a multisig owning the OCL
+    MultisigSigner,
+    /// Holder of the IP-token of the OCL
+    Holder,
+    /// Everyone: the dataset is made public
+    Public,
+}
+
+/// Kind of project datasets that defines the access to them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatasetKind {
+    DataRoom,
+    Announcements,
+    /// Versioned file of the data room
+    File(MoleculeAccessLevel),
+}
+
+/// Roles that each kind of principals gets for each kind of project datasets.
+/// Sections that are not set keep the default roles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccessPolicy {
+    pub data_room: PrincipalRoles,
+    pub announcements: PrincipalRoles,
+    pub files: FileAccessPolicy,
+}
+
+/// Roles for versioned files per `molecule_access_level`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileAccessPolicy {
+    pub public: PrincipalRoles,
+    pub admin: PrincipalRoles,
+    pub admin_2: PrincipalRoles,
+    pub holder: PrincipalRoles,
+    /// Roles for files with a `molecule_access_level` the bridge doesn't
+    /// support yet
+    pub unknown_fallback: UnknownAccessLevelFallback,
+}
+
+/// How files with an unknown `molecule_access_level` are treated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownAccessLevelFallback {
+    /// Nobody gets access
+    Deny,
+    /// Same as `Public` files
+    Public,
+    /// Same as `Admin` files
+    #[default]
+    Admin,
+    /// Same as `Admin2` files
+    #[serde(rename = "admin_2")]
+    Admin2,
+    /// Same as `Holder` files
+    Holder,
+}
+
+/// Roles per principal kind. `None` means no access.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrincipalRoles {
+    #[serde(default)]
+    pub owner: Option<DatasetAccessRole>,
+    #[serde(default)]
+    pub multisig_signer: Option<DatasetAccessRole>,
+    #[serde(default)]
+    pub holder: Option<DatasetAccessRole>,
+    /// Only `Reader` is supported: the dataset is made public
+    #[serde(default)]
+    pub public: Option<DatasetAccessRole>,
+}
+
+impl AccessPolicy {
+    pub fn roles(&self, dataset_kind: &DatasetKind) -> &PrincipalRoles {
+        use MoleculeAccessLevel as Access;
+        use UnknownAccessLevelFallback as Fallback;
+
+        match dataset_kind {
+            DatasetKind::DataRoom => &self.data_room,
+            DatasetKind::Announcements => &self.announcements,
+            DatasetKind::File(Access::Public) => &self.files.public,
+            DatasetKind::File(Access::Admin) => &self.files.admin,
+            DatasetKind::File(Access::Admin2) => &self.files.admin_2,
+            DatasetKind::File(Access::Holder) => &self.files.holder,
+            DatasetKind::File(Access::Unknown(_)) => match self.files.unknown_fallback {
+                Fallback::Deny => &PrincipalRoles::NONE,
+                Fallback::Public => &self.files.public,
+                Fallback::Admin => &self.files.admin,
+                Fallback::Admin2 => &self.files.admin_2,
+                Fallback::Holder => &self.files.holder,
+            },
+        }
+    }
+
+    /// Whether datasets of the kind are readable by everyone
+    pub fn is_public(&self, dataset_kind: &DatasetKind) -> bool {
+        self.roles(dataset_kind).public.is_some()
+    }
+
+    pub fn validate(&self) -> eyre::Result<()> {
+        let sections = [
+            ("data_room", &self.data_room),
+            ("announcements", &self.announcements),
+            ("files.public", &self.files.public),
+            ("files.admin", &self.files.admin),
+            ("files.admin_2", &self.files.admin_2),
+            ("files.holder", &self.files.holder),
+        ];
+
+        for (section, roles) in sections {
+            if let Some(role) = roles.public
+                && role != DatasetAccessRole::Reader
+            {
+                eyre::bail!(
+                    "Access policy: `{section}.public` can only be `Reader` (the dataset is made \
+                     public), got `{role:?}`"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for AccessPolicy {
+    /// Owners maintain all project datasets, holders read `Holder` and
+    /// `Public` files, `Public` files are readable by everyone
+    fn default() -> Self {
+        const OWNER_ROLES: PrincipalRoles = PrincipalRoles {
+            owner: Some(DatasetAccessRole::Maintainer),
+            multisig_signer: Some(DatasetAccessRole::Maintainer),
+            ..PrincipalRoles::NONE
+        };
+        const HOLDER_ROLES: PrincipalRoles = PrincipalRoles {
+            holder: Some(DatasetAccessRole::Reader),
+            ..OWNER_ROLES
+        };
+
+        Self {
+            data_room: OWNER_ROLES,
+            announcements: OWNER_ROLES,
+            files: FileAccessPolicy {
+                public: PrincipalRoles {
+                    public: Some(DatasetAccessRole::Reader),
+                    ..HOLDER_ROLES
+                },
+                admin: OWNER_ROLES,
+                admin_2: OWNER_ROLES,
+                holder: HOLDER_ROLES,
+                unknown_fallback: UnknownAccessLevelFallback::default(),
+            },
+        }
+    }
+}
+
+impl Default for FileAccessPolicy {
+    fn default() -> Self {
+        AccessPolicy::default().files
+    }
+}
+
+impl PrincipalRoles {
+    /// No access for anyone
+    pub const NONE: Self = Self {
+        owner: None,
+        multisig_signer: None,
+        holder: None,
+        public: None,
+    };
+
+    pub fn role(&self, principal_kind: PrincipalKind) -> Option<DatasetAccessRole> {
+        match principal_kind {
+            PrincipalKind::Owner => self.owner,
+            PrincipalKind::MultisigSigner => self.multisig_signer,
+            PrincipalKind::Holder => self.holder,
+            PrincipalKind::Public => self.public,
+        }
+    }
+}
+
+/// Upper bounds of the roles that signers of a multisig owning an OCL (and
+/// the multisig itself) get from the access policy. `None` means no access.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MultisigAccessPolicy {
+    /// Role of the multisig itself
+    #[serde(default)]
+    pub multisig_role: Option<DatasetAccessRole>,
+    /// Role of the signers of a multisig with a threshold of 1, i.e. when any
+    /// of them can act on behalf of the multisig alone
+    #[serde(default = "MultisigAccessPolicy::default_signer_role")]
+    pub sole_signer_role: Option<DatasetAccessRole>,
+    /// Role of the signers of a multisig with a threshold above 1
+    #[serde(default = "MultisigAccessPolicy::default_signer_role")]
+    pub signer_role: Option<DatasetAccessRole>,
+}
+
+impl MultisigAccessPolicy {
+    /// Every signer gets the roles of the access policy, the multisig itself
+    /// gets no access
+    pub const DEFAULT: Self = Self {
+        multisig_role: None,
+        sole_signer_role: Some(DatasetAccessRole::Maintainer),
+        signer_role: Some(DatasetAccessRole::Maintainer),
+    };
+
+    fn default_signer_role() -> Option<DatasetAccessRole> {
+        Self::DEFAULT.signer_role
+    }
+
+    pub fn signer_role(&self, threshold: u64) -> Option<DatasetAccessRole> {
+        if threshold <= 1 {
+            self.sole_signer_role
+        } else {
+            self.signer_role
+        }
+    }
+}
+
+impl Default for MultisigAccessPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Multisig access policies per chain (deployment) and per OCL (project)
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct MultisigAccessPolicies {
+    pub per_chain: HashMap<u64, MultisigAccessPolicy>,
+    /// Take precedence over the per-chain policies
+    pub per_ocl: HashMap<OclId, MultisigAccessPolicy>,
+}
+
+impl MultisigAccessPolicies {
+    pub fn get(&self, chain_id: u64, ocl_id: OclId) -> &MultisigAccessPolicy {
+        self.per_ocl
+            .get(&ocl_id)
+            .or_else(|| self.per_chain.get(&chain_id))
+            .unwrap_or(&MultisigAccessPolicy::DEFAULT)
+    }
+}
+
+pub fn highest_role(a: DatasetAccessRole, b: DatasetAccessRole) -> DatasetAccessRole {
+    if role_rank(a) >= role_rank(b) { a } else { b }
+}
+
+pub fn lowest_role(a: DatasetAccessRole, b: DatasetAccessRole) -> DatasetAccessRole {
+    if role_rank(a) <= role_rank(b) { a } else { b }
+}
+
+fn role_rank(role: DatasetAccessRole) -> u8 {
+    match role {
+        DatasetAccessRole::Reader => 0,
+        DatasetAccessRole::Editor => 1,
+        DatasetAccessRole::Maintainer => 2,
+    }
+}
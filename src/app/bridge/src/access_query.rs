@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+
+use alloy::primitives::Address;
+use kamu_node_api_client::{AccountID, DatasetID, MoleculeAccessLevel};
+use molecule_ocl::entities::OclId;
+use serde::{Deserialize, Serialize};
+
+use crate::access::{
+    DesiredAccess, OclAccounts, get_accounts_by_ocl_project, get_desired_ocl_access,
+    get_on_chain_ocls, ocl_owner_addresses,
+};
+use crate::access_policy::AccessPolicies;
+use crate::state::{AppState, MultisigState, OffChainMoleculeProjectProjection};
+
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PageArgs {
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    /// Number of items on all pages
+    pub total_count: usize,
+    pub offset: usize,
+    pub items: Vec<T>,
+}
+
+impl<T> Page<T> {
+    fn new(items: Vec<T>, page_args: &PageArgs) -> Self {
+        let total_count = items.len();
+        let limit = page_args.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+        Self {
+            total_count,
+            offset: page_args.offset,
+            items: items
+                .into_iter()
+                .skip(page_args.offset)
+                .take(limit)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OclView {
+    pub ocl_id: OclId,
+    /// `None` until the project appears in `molecule/projects`
+    pub project: Option<OclProjectView>,
+    /// Chains the OCL is present on
+    pub chains: Vec<OclChainView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OclProjectView {
+    pub symbol: String,
+    pub project_account_id: AccountID,
+    pub data_room_dataset_id: DatasetID,
+    pub announcements_dataset_id: DatasetID,
+    pub files: OclFilesView,
+    pub removed_files: Vec<OclFileView>,
+}
+
+/// Files of the project grouped by their molecule access level
+#[derive(Debug, Default, Serialize)]
+pub struct OclFilesView {
+    pub public: Vec<OclFileView>,
+    pub holder: Vec<OclFileView>,
+    pub admin: Vec<OclFileView>,
+    pub admin_2: Vec<OclFileView>,
+    /// Files with a molecule access level that is not supported yet
+    pub unknown: Vec<OclFileView>,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct OclFileView {
+    pub path: String,
+    pub dataset_id: DatasetID,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OclChainView {
+    pub chain_id: u64,
+    pub current_owner: Option<Address>,
+    pub previous_owners: Vec<Address>,
+    pub identity_account: Option<Address>,
+    /// Multisigs among the current and previous owners
+    pub multisigs: BTreeMap<Address, MultisigState>,
+    /// Accounts that maintain all project files
+    pub owner_accounts: Vec<Address>,
+    /// Accounts that read `Holder` and `Public` files
+    pub holder_accounts: Vec<Address>,
+    /// Former owners whose access is revoked
+    pub former_owner_accounts: Vec<Address>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetAccessView {
+    pub dataset_id: DatasetID,
+    pub ocl_id: OclId,
+    pub dataset: ProjectDatasetView,
+    pub accounts: Page<DesiredAccess>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type")]
+pub enum ProjectDatasetView {
+    DataRoom,
+    Announcements,
+    File {
+        path: String,
+        molecule_access_level: MoleculeAccessLevel,
+    },
+    /// Nobody should have access to a removed file
+    RemovedFile {
+        path: String,
+    },
+}
+
+/// Dataset along with the project it belongs to
+pub struct ProjectDataset<'a> {
+    pub ocl_id: OclId,
+    pub off_chain_ocl_project: &'a OffChainMoleculeProjectProjection,
+    pub dataset: ProjectDatasetView,
+}
+
+/// Returns the on-chain ownership and files of the OCL
+pub fn get_ocl_view(
+    app_state: &AppState,
+    access_policies: &AccessPolicies,
+    ocl_id: OclId,
+) -> Option<OclView> {
+    let project = app_state
+        .off_chain_ocl_project_map
+        .get(&ocl_id)
+        .map(get_ocl_project_view);
+
+    let chains = get_on_chain_ocls(&app_state.chains, access_policies, ocl_id)
+        .into_iter()
+        .map(|on_chain_ocl| {
+            let OclAccounts {
+                current_owners,
+                revoke_access_accounts,
+                current_holders,
+                ..
+            } = get_accounts_by_ocl_project(
+                on_chain_ocl.ownership,
+                &on_chain_ocl.owners_resolver,
+                on_chain_ocl.maybe_holders,
+            );
+
+            let chain_state = &app_state.chains[&on_chain_ocl.chain_id];
+            let multisigs = ocl_owner_addresses(on_chain_ocl.ownership)
+                .filter_map(|owner| {
+                    let multisig_state = chain_state.multisig.get(&owner)?.as_ref()?;
+                    Some((owner, multisig_state.clone()))
+                })
+                .collect();
+
+            OclChainView {
+                chain_id: on_chain_ocl.chain_id,
+                current_owner: on_chain_ocl.ownership.current,
+                previous_owners: on_chain_ocl.ownership.previous.clone(),
+                identity_account: on_chain_ocl.ownership.identity_account,
+                multisigs,
+                owner_accounts: sorted(current_owners.into_keys()),
+                holder_accounts: sorted(current_holders),
+                former_owner_accounts: sorted(revoke_access_accounts),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if project.is_none() && chains.is_empty() {
+        return None;
+    }
+
+    Some(OclView {
+        ocl_id,
+        project,
+        chains,
+    })
+}
+
+/// Returns datasets the address should have access to on any of the chains
+pub fn get_account_access(
+    app_state: &AppState,
+    access_policies: &AccessPolicies,
+    address: Address,
+    page_args: &PageArgs,
+) -> eyre::Result<Page<DesiredAccess>> {
+    let mut account_access = Vec::new();
+
+    for (ocl_id, off_chain_ocl_project) in &app_state.off_chain_ocl_project_map {
+        for on_chain_ocl in get_on_chain_ocls(&app_state.chains, access_policies, *ocl_id) {
+            account_access.extend(
+                get_desired_ocl_access(off_chain_ocl_project, &on_chain_ocl)?
+                    .into_iter()
+                    .filter(|desired_access| desired_access.address == address),
+            );
+        }
+    }
+
+    sort_desired_access(&mut account_access);
+
+    Ok(Page::new(account_access, page_args))
+}
+
+/// Returns accounts that should have access to the dataset.
+/// `None` if the dataset doesn't belong to any project.
+pub fn get_dataset_access(
+    app_state: &AppState,
+    access_policies: &AccessPolicies,
+    dataset_id: &DatasetID,
+    page_args: &PageArgs,
+) -> eyre::Result<Option<DatasetAccessView>> {
+    let Some(ProjectDataset {
+        ocl_id,
+        off_chain_ocl_project,
+        dataset,
+    }) = find_project_dataset(app_state, dataset_id)
+    else {
+        return Ok(None);
+    };
+
+    let mut dataset_access = Vec::new();
+
+    for on_chain_ocl in get_on_chain_ocls(&app_state.chains, access_policies, ocl_id) {
+        dataset_access.extend(
+            get_desired_ocl_access(off_chain_ocl_project, &on_chain_ocl)?
+                .into_iter()
+                .filter(|desired_access| desired_access.dataset_id == *dataset_id),
+        );
+    }
+
+    sort_desired_access(&mut dataset_access);
+
+    Ok(Some(DatasetAccessView {
+        dataset_id: dataset_id.clone(),
+        ocl_id,
+        dataset,
+        accounts: Page::new(dataset_access, page_args),
+    }))
+}
+
+/// Returns the project the dataset belongs to (including removed files)
+pub fn find_project_dataset<'a>(
+    app_state: &'a AppState,
+    dataset_id: &DatasetID,
+) -> Option<ProjectDataset<'a>> {
+    app_state
+        .off_chain_ocl_project_map
+        .iter()
+        .find_map(|(ocl_id, off_chain_ocl_project)| {
+            let dataset = get_project_dataset_view(off_chain_ocl_project, dataset_id)?;
+            Some(ProjectDataset {
+                ocl_id: *ocl_id,
+                off_chain_ocl_project,
+                dataset,
+            })
+        })
+}
+
+fn get_ocl_project_view(off_chain_project: &OffChainMoleculeProjectProjection) -> OclProjectView {
+    use MoleculeAccessLevel as Access;
+
+    let mut files = OclFilesView::default();
+
+    for (dataset_id, entry_with_access_level) in &off_chain_project.actual_files_map {
+        let file = OclFileView {
+            path: entry_with_access_level.entry.path.clone(),
+            dataset_id: dataset_id.clone(),
+        };
+
+        match entry_with_access_level.molecule_access_level {
+            Access::Public => files.public.push(file),
+            Access::Holder => files.holder.push(file),
+            Access::Admin => files.admin.push(file),
+            Access::Admin2 => files.admin_2.push(file),
+            Access::Unknown(_) => files.unknown.push(file),
+        }
+    }
+
+    let mut removed_files = off_chain_project
+        .removed_files_map
+        .iter()
+        .map(|(dataset_id, entry)| OclFileView {
+            path: entry.path.clone(),
+            dataset_id: dataset_id.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    for files in [
+        &mut files.public,
+        &mut files.holder,
+        &mut files.admin,
+        &mut files.admin_2,
+        &mut files.unknown,
+        &mut removed_files,
+    ] {
+        files.sort();
+    }
+
+    OclProjectView {
+        symbol: off_chain_project.entry.symbol.clone(),
+        project_account_id: off_chain_project.entry.project_account_id.clone(),
+        data_room_dataset_id: off_chain_project.entry.data_room_dataset_id.clone(),
+        announcements_dataset_id: off_chain_project.entry.announcements_dataset_id.clone(),
+        files,
+        removed_files,
+    }
+}
+
+fn get_project_dataset_view(
+    off_chain_project: &OffChainMoleculeProjectProjection,
+    dataset_id: &DatasetID,
+) -> Option<ProjectDatasetView> {
+    if off_chain_project.entry.data_room_dataset_id == *dataset_id {
+        return Some(ProjectDatasetView::DataRoom);
+    }
+    if off_chain_project.entry.announcements_dataset_id == *dataset_id {
+        return Some(ProjectDatasetView::Announcements);
+    }
+    if let Some(entry_with_access_level) = off_chain_project.actual_files_map.get(dataset_id) {
+        return Some(ProjectDatasetView::File {
+            path: entry_with_access_level.entry.path.clone(),
+            molecule_access_level: entry_with_access_level.molecule_access_level.clone(),
+        });
+    }
+
+    off_chain_project
+        .removed_files_map
+        .get(dataset_id)
+        .map(|entry| ProjectDatasetView::RemovedFile {
+            path: entry.path.clone(),
+        })
+}
+
+fn sort_desired_access(desired_access: &mut [DesiredAccess]) {
+    desired_access.sort_by(|a, b| {
+        (&a.dataset_id, a.chain_id, a.address).cmp(&(&b.dataset_id, b.chain_id, b.address))
+    });
+}
+
+fn sorted(addresses: impl IntoIterator<Item = Address>) -> Vec<Address> {
+    let mut addresses = addresses.into_iter().collect::<Vec<_>>();
+    addresses.sort_unstable();
+    addresses
+}
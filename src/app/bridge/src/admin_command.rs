@@ -0,0 +1,26 @@
+use alloy::primitives::Address;
+use molecule_ocl::entities::OclId;
+use serde::{Deserialize, Serialize};
+
+/// Operation requested via the admin HTTP API.
+///
+/// Commands are queued and processed by the main loop between update
+/// iterations, so they never run concurrently with indexing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AdminCommand {
+    /// Drops the state and performs the catch-up phase again
+    Resync,
+    /// Applies access for all owners and holders of the OCL, as during the
+    /// catch-up phase
+    RefreshOcl { ocl_id: OclId },
+    /// Fetches owners of the Safe multisig again and applies access for the
+    /// OCLs it owns
+    RefreshMultisig { chain_id: u64, address: Address },
+    /// Rebuilds the on-chain state of the chain up to (and including)
+    /// `to_block`, so that the following blocks are indexed again
+    RewindIndexing { chain_id: u64, to_block: u64 },
+}
+
+pub type AdminCommandSender = tokio::sync::mpsc::UnboundedSender<AdminCommand>;
+pub type AdminCommandReceiver = tokio::sync::mpsc::UnboundedReceiver<AdminCommand>;
@@ -1,126 +1,167 @@
-use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
-use alloy::primitives::{Address, Log};
+use alloy::primitives::Address;
 use alloy::providers::DynProvider;
 use alloy_ext::prelude::*;
-use chrono::{DateTime, Utc};
-use eyre::bail;
+use chrono::Utc;
+use eyre::{ContextCompat as _, bail};
+use futures::{StreamExt as _, TryStreamExt as _};
 use kamu_node_api_client::*;
 use molecule_contracts::prelude::*;
-use molecule_contracts::safe::parse_safe_removed_owner_event;
-use molecule_contracts::{LabNFT, Safe, safe};
-use molecule_ocl::entities::{
-    OclId, OclOwnershipChange, OclOwnershipProjection, OclOwnershipProjectionMap, OclTransferEvent,
-};
-use multisig::services::MultisigResolver;
-use serde::Serialize;
-use serde_json::Value;
+use molecule_contracts::{IERC20, LabNFT};
+use molecule_ocl::entities::{OclId, OclOwnershipChange, OclOwnershipProjectionMap};
+use multisig::entities::{MembershipChange, MultisigOwners};
+use multisig::services::{MultisigProviderRegistry, MultisigResolver};
 use tokio::sync::RwLock;
 use tracing::Instrument as _;
 
-use crate::config::Config;
+use crate::access::{
+    GetOwnersResponse, OclAccounts, OnChainOcl, ProjectDatasetIds, account_access_sanity_checks,
+    get_accounts_by_ocl_project, get_desired_ocl_access, get_on_chain_ocls,
+    get_project_dataset_ids, get_public_dataset_ids, ocl_owner_addresses,
+};
+use crate::access_changes_dataset::{build_access_change_rows, read_unpushed_audit_log_records};
+use crate::access_policy::{AccessPolicies, AccessPolicy, DatasetKind, SharedAccessPolicies};
+use crate::admin_command::{AdminCommand, AdminCommandReceiver, AdminCommandSender};
+use crate::audit_log::{
+    AccessAuditLog, AccessChangeCause, AccessChangeResult, NewAccessAuditRecord, OffChainTrigger,
+    OnChainTrigger, records_to_ndjson,
+};
+use crate::config::{ChainConfig, Config, IndexingHead};
+use crate::config_watcher::{ReloadedConfig, ReloadedConfigReceiver, ReloadedConfigSender};
+use crate::dataset_heads::DatasetHeads;
+use crate::events::{
+    AppStateProjector, BridgeEvent, ChangedVersionedFile, ChangedVersionedFilePerProjectMap,
+    DataRoomFileChange, EventLog, OclHolderChanges, OnChainProjectionChanges, ProjectionChanges,
+};
 use crate::http_server;
-use crate::http_server::{HttpServeFuture, StateRequester};
+use crate::http_server::{AdminApi, HttpServeFuture, KamuWebhookApi};
 use crate::metrics::BridgeMetrics;
+use crate::operation_planner::{
+    AppliedRolesMap, collapse_operations, forget_applied_roles, plan_operations,
+    record_applied_operations,
+};
+use crate::reconciliation::reconcile_dataset_roles;
+use crate::state::{
+    AppState, ChainState, MultisigState, OffChainMoleculeProjectProjection, PendingAccessChange,
+    SharedAppState, VersionedFileEntryWithMoleculeAccessLevel,
+};
+use crate::state_store::AppStateStore;
+
+/// Maximum number of audit log records pushed into the Kamu Node dataset at once
+const AUDIT_LOG_PUSH_BATCH_SIZE: usize = 1000;
+
+const ACCESS_CHANGE_APPLYING_MAX_ATTEMPTS: u32 = 3;
+/// Multiplied by the attempt number
+const ACCESS_CHANGE_APPLYING_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
 
-// TODO: Implement event sourcing: maintain single ordered log of events from two sources
-//       and derive state as a projection over that log.
-//
-//       Sources:
-//       - On-chain: LabNFT transfers, multisig changes (source of truth, indexed by block number)
-//       - Off-chain: OCL project changes (polled periodically, indexed by offset)
-//
-//       Each event should carry a timestamp for cross-source ordering.
+/// Projects loading requests (see [`KamuWebhookApi`]) that arrive within this
+/// delay are served by a single iteration
+const PROJECTS_LOADING_REQUEST_DEBOUNCE_DELAY: std::time::Duration =
+    std::time::Duration::from_secs(5);
 
 pub struct App {
     config: Config,
 
-    rpc_client: DynProvider,
-    multisig_resolver: Arc<dyn MultisigResolver>,
+    chain_sources: Vec<ChainSource>,
     kamu_node_api_client: Arc<dyn KamuNodeApiClient>,
+    /// Access changes are not applied to Kamu Node (see `--dry-run`)
+    dry_run: bool,
+    maybe_state_store: Option<Arc<dyn AppStateStore>>,
+    event_log: Arc<dyn EventLog>,
+    audit_log: Arc<dyn AccessAuditLog>,
 
-    #[expect(dead_code)]
     metrics: BridgeMetrics,
     metrics_registry: prometheus::Registry,
 
+    admin_command_sender: AdminCommandSender,
+    admin_command_receiver: AdminCommandReceiver,
+    reloaded_config_sender: ReloadedConfigSender,
+    reloaded_config_receiver: ReloadedConfigReceiver,
+    projects_loading_requested: Arc<tokio::sync::Notify>,
+    /// Projects are loaded on the next iteration regardless of the interval
+    is_projects_loading_requested: bool,
+
+    access_policies: Arc<SharedAccessPolicies>,
+    /// Replaced by the reloaded one (see [`crate::config_watcher`])
+    ocl_id_filter: OclIdFilter,
     state: Arc<RwLock<AppState>>,
 }
 
-#[derive(Debug, Default, Serialize)]
-pub struct AppState {
-    molecule_projects_dataset_offset: Option<u64>,
-    molecule_projects_last_requested_at: Option<DateTime<Utc>>,
-
-    on_chain_ocl_ownership_projection_map: OclOwnershipProjectionMap,
-    off_chain_ocl_project_map: HashMap<OclId, OffChainMoleculeProjectProjection>,
-    latest_indexed_block_number: u64,
-
-    multisig: HashMap<Address, Option<MultisigState>>,
-
-    access_changes: HashMap<DateTime<Utc>, AccessChanges>,
-}
-
-#[derive(Debug, Serialize)]
-struct AccessChanges {
-    reason: String,
-    operations: Vec<AccountDatasetRelationOperation>,
-}
-
-#[async_trait::async_trait]
-impl StateRequester for RwLock<AppState> {
-    async fn request_as_json(&self) -> Value {
-        let readable_state = self.read().await;
-        serde_json::to_value(&*readable_state).unwrap()
-    }
-}
-
-#[derive(Debug, Serialize)]
-struct MultisigState {
-    current_owners: HashSet<Address>,
-    former_owners: HashSet<Address>,
-}
-
-#[derive(Debug, Serialize)]
-struct OffChainMoleculeProjectProjection {
-    entry: MoleculeProjectEntry,
-    latest_data_room_offset: u64,
-    actual_files_map: HashMap<DatasetID, VersionedFileEntryWithMoleculeAccessLevel>,
-    removed_files_map: HashMap<DatasetID, VersionedFileEntry>,
-}
-
-#[derive(Debug, Serialize)]
-struct VersionedFileEntryWithMoleculeAccessLevel {
-    entry: VersionedFileEntry,
-    molecule_access_level: MoleculeAccessLevel,
-}
-
 impl App {
     pub fn new(
         config: Config,
-        rpc_client: DynProvider,
-        multisig_resolver: Arc<dyn MultisigResolver>,
+        chain_sources: Vec<ChainSource>,
+        access_policies: AccessPolicies,
+        ocl_id_filter: OclIdFilter,
         kamu_node_api_client: Arc<dyn KamuNodeApiClient>,
+        dry_run: bool,
+        maybe_state_store: Option<Arc<dyn AppStateStore>>,
+        event_log: Arc<dyn EventLog>,
+        audit_log: Arc<dyn AccessAuditLog>,
         metrics: BridgeMetrics,
         metrics_registry: prometheus::Registry,
     ) -> Self {
+        let (admin_command_sender, admin_command_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let (reloaded_config_sender, reloaded_config_receiver) =
+            tokio::sync::mpsc::unbounded_channel();
+
         Self {
             config,
-            rpc_client,
-            multisig_resolver,
+            chain_sources,
             kamu_node_api_client,
+            dry_run,
+            maybe_state_store,
+            event_log,
+            audit_log,
             metrics,
             metrics_registry,
+            admin_command_sender,
+            admin_command_receiver,
+            reloaded_config_sender,
+            reloaded_config_receiver,
+            projects_loading_requested: Arc::new(tokio::sync::Notify::new()),
+            is_projects_loading_requested: false,
+            access_policies: Arc::new(SharedAccessPolicies::new(access_policies)),
+            ocl_id_filter,
             state: Default::default(),
         }
     }
 
+    /// Sender for configs reloaded while running (see [`crate::config_watcher`])
+    pub fn reloaded_config_sender(&self) -> ReloadedConfigSender {
+        self.reloaded_config_sender.clone()
+    }
+
     /// Loads the state and returns it without making any modifications to permissions
     pub async fn get_state(mut self) -> eyre::Result<AppState> {
         self.init_state().await
     }
 
+    /// Loads the state and brings the dataset roles in Kamu Node in line with it.
+    /// Returns the reconciliation operations (not applied in dry-run mode).
+    pub async fn reconcile(mut self) -> eyre::Result<Vec<AccountDatasetRelationOperation>> {
+        let mut app_state = if let Some(mut restored_app_state) = self.restore_state().await? {
+            tracing::info!("Reconciling with the stored state");
+
+            // NOTE: The journal is applied by the running bridge, while reconciliation
+            //       brings the roles in line with the state anyway.
+            restored_app_state.pending_access_changes.clear();
+            restored_app_state
+        } else {
+            self.init_state().await?
+        };
+
+        let operations = self.reconciliation(&mut app_state).await?;
+
+        if !app_state.pending_access_changes.is_empty() {
+            bail!("Failed to apply reconciliation operations");
+        }
+
+        Ok(operations)
+    }
+
     /// Initializes the state and enters a continuous indexing loop
     pub async fn run<F>(&mut self, shutdown_requested: F) -> eyre::Result<()>
     where
@@ -147,7 +188,30 @@ impl App {
             self.config.http_address,
             self.config.http_port,
             metrics_registry,
-            self.state.clone(),
+            Arc::new(SharedAppState {
+                state: self.state.clone(),
+                access_policies: self.access_policies.clone(),
+            }),
+            self.event_log.clone(),
+            self.audit_log.clone(),
+            AdminApi {
+                maybe_token: self
+                    .config
+                    .admin_token
+                    .clone()
+                    // NOTE: An empty token would allow anyone to use the admin API.
+                    .filter(|token| !token.expose().is_empty()),
+                command_sender: self.admin_command_sender.clone(),
+            },
+            self.config
+                .kamu_webhook_token
+                .clone()
+                // NOTE: An empty token would allow anyone to trigger loading.
+                .filter(|token| !token.expose().is_empty())
+                .map(|token| KamuWebhookApi {
+                    token,
+                    projects_loading_requested: self.projects_loading_requested.clone(),
+                }),
         )
         .await?;
 
@@ -165,22 +229,115 @@ impl App {
             .instrument(observability::tracing::root_span!("App::init"))
             .await?;
 
-        let iteration_delay =
-            std::time::Duration::from_secs(self.config.indexing_delay_between_iterations_in_secs);
+        let mut next_iteration_at = tokio::time::Instant::now() + self.iteration_delay();
 
         loop {
-            tokio::time::sleep(iteration_delay).await;
+            // NOTE: Admin commands, reloaded configs and projects loading requests
+            //       are processed between iterations, so they never race with them.
+            let wakeup = tokio::select! {
+                () = tokio::time::sleep_until(next_iteration_at) => MainLoopWakeup::Iteration,
+                Some(admin_command) = self.admin_command_receiver.recv() => {
+                    MainLoopWakeup::AdminCommand(admin_command)
+                }
+                Some(reloaded_config) = self.reloaded_config_receiver.recv() => {
+                    MainLoopWakeup::ReloadedConfig(Box::new(reloaded_config))
+                }
+                () = self.projects_loading_requested.notified() => {
+                    MainLoopWakeup::ProjectsLoadingRequested
+                }
+            };
+
+            match wakeup {
+                MainLoopWakeup::Iteration => {}
+                MainLoopWakeup::AdminCommand(admin_command) => {
+                    if let Err(e) = self
+                        .handle_admin_command(admin_command)
+                        .instrument(observability::tracing::root_span!(
+                            "App::handle_admin_command"
+                        ))
+                        .await
+                    {
+                        tracing::error!(error = ?e, error_msg = %e, "Failed to handle admin command");
+                    }
+                    continue;
+                }
+                MainLoopWakeup::ReloadedConfig(reloaded_config) => {
+                    if let Err(e) = self
+                        .apply_reloaded_config(*reloaded_config)
+                        .instrument(observability::tracing::root_span!(
+                            "App::apply_reloaded_config"
+                        ))
+                        .await
+                    {
+                        tracing::error!(error = ?e, error_msg = %e, "Failed to apply reloaded config");
+                    }
+                    // NOTE: The delay may have changed.
+                    next_iteration_at =
+                        next_iteration_at.min(tokio::time::Instant::now() + self.iteration_delay());
+                    continue;
+                }
+                MainLoopWakeup::ProjectsLoadingRequested => {
+                    tracing::info!("Projects loading is requested");
+                    self.is_projects_loading_requested = true;
+                    // NOTE: Requests are coalesced until the iteration starts.
+                    next_iteration_at = next_iteration_at
+                        .min(tokio::time::Instant::now() + PROJECTS_LOADING_REQUEST_DEBOUNCE_DELAY);
+                    continue;
+                }
+            }
 
             self.update()
                 .instrument(observability::tracing::root_span!("App::update"))
                 .await?;
+
+            self.push_audit_log_records()
+                .instrument(observability::tracing::root_span!(
+                    "App::push_audit_log_records"
+                ))
+                .await;
+
+            next_iteration_at = tokio::time::Instant::now() + self.iteration_delay();
         }
     }
 
+    fn iteration_delay(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.indexing_delay_between_iterations_in_secs)
+    }
+
     async fn init(&mut self) -> eyre::Result<()> {
-        let mut initial_app_state = self.init_state().await?;
+        let initial_app_state = if let Some(restored_app_state) = self.restore_state().await? {
+            let latest_indexed_block_numbers = restored_app_state
+                .chains
+                .iter()
+                .map(|(chain_id, chain_state)| (*chain_id, chain_state.latest_indexed_block_number))
+                .collect::<BTreeMap<_, _>>();
+            tracing::info!(
+                ?latest_indexed_block_numbers,
+                molecule_projects_dataset_offset = ?restored_app_state.molecule_projects_dataset_offset,
+                "Resuming from the stored state"
+            );
 
-        self.initial_access_applying(&mut initial_app_state).await?;
+            // NOTE: Permissions were already applied before the snapshot was taken,
+            //       so only the changes since then will be processed in the update loop.
+            restored_app_state
+        } else {
+            let mut initial_app_state =
+                if let Some(replayed_app_state) = self.replay_state().await? {
+                    replayed_app_state
+                } else {
+                    self.init_state().await?
+                };
+
+            // NOTE: Applied roles are not recorded in the event log, so access is applied
+            //       for the whole state, the same way as for a newly built one.
+            self.initial_access_applying(&mut initial_app_state).await?;
+            self.apply_pending_access_changes(&mut initial_app_state)
+                .await;
+
+            self.persist_state(&initial_app_state).await;
+
+            initial_app_state
+        };
 
         {
             let mut writable_state = self.state.write().await;
@@ -190,46 +347,471 @@ impl App {
         Ok(())
     }
 
+    async fn restore_state(&self) -> eyre::Result<Option<AppState>> {
+        let Some(state_store) = &self.maybe_state_store else {
+            return Ok(None);
+        };
+        let Some(restored_app_state) = state_store.load().await? else {
+            return Ok(None);
+        };
+
+        if let Err(e) = self
+            .event_log
+            .resume(restored_app_state.event_log_next_sequence)
+            .await
+        {
+            tracing::warn!(error = ?e, error_msg = %e, "Ignore state snapshot: it doesn't match the event log");
+            return Ok(None);
+        }
+
+        Ok(Some(restored_app_state))
+    }
+
+    /// Rebuilds the state by replaying the event log (if it keeps all entries).
+    /// Chains that were not indexed yet are indexed from scratch.
+    async fn replay_state(&self) -> eyre::Result<Option<AppState>> {
+        let Some(entries) = self.event_log.read_all().await? else {
+            return Ok(None);
+        };
+        if entries.is_empty() {
+            return Ok(None);
+        }
+
+        let mut app_state = AppStateProjector::replay(&entries);
+
+        for chain_source in &self.chain_sources {
+            if !app_state.chains.contains_key(&chain_source.config.chain_id) {
+                self.reindex_chain(
+                    chain_source,
+                    &mut app_state,
+                    chain_source.config.labnft_contract_birth_block - 1,
+                    None,
+                )
+                .await?;
+            }
+        }
+
+        let latest_indexed_block_numbers = app_state
+            .chains
+            .iter()
+            .map(|(chain_id, chain_state)| (*chain_id, chain_state.latest_indexed_block_number))
+            .collect::<BTreeMap<_, _>>();
+        tracing::info!(
+            entries_count = entries.len(),
+            ?latest_indexed_block_numbers,
+            molecule_projects_dataset_offset = ?app_state.molecule_projects_dataset_offset,
+            "State is rebuilt from the event log"
+        );
+
+        Ok(Some(app_state))
+    }
+
+    /// Stores the state snapshot (if persistence is enabled).
+    ///
+    /// Failures are not fatal: in the worst case, the next start will perform
+    /// a full resync.
+    async fn persist_state(&self, app_state: &AppState) {
+        let Some(state_store) = &self.maybe_state_store else {
+            return;
+        };
+
+        if let Err(e) = state_store.save(app_state).await {
+            tracing::error!(error = ?e, error_msg = %e, "Failed to persist the state");
+        }
+    }
+
     #[tracing::instrument(level = "info", skip_all)]
     async fn init_state(&mut self) -> eyre::Result<AppState> {
-        let latest_finalized_block_number = self.rpc_client.latest_finalized_block_number().await?;
+        let mut initial_app_state = AppState::default();
 
-        let labnft_contract_birth_block_minus_one = self.config.labnft_contract_birth_block - 1;
+        self.build_state(&mut initial_app_state).await?;
 
-        let mut initial_app_state = AppState {
-            latest_indexed_block_number: labnft_contract_birth_block_minus_one,
-            ..Default::default()
-        };
+        Ok(initial_app_state)
+    }
+
+    /// Indexes all chains and loads projects from scratch
+    async fn build_state(&self, app_state: &mut AppState) -> eyre::Result<()> {
+        for chain_source in &self.chain_sources {
+            let labnft_contract_birth_block_minus_one =
+                chain_source.config.labnft_contract_birth_block - 1;
 
-        self.indexing(&mut initial_app_state, latest_finalized_block_number)
+            self.reindex_chain(
+                chain_source,
+                app_state,
+                labnft_contract_birth_block_minus_one,
+                None,
+            )
             .await?;
+        }
 
-        self.load_molecule_projects(&mut initial_app_state).await?;
+        self.load_molecule_projects(app_state).await?;
 
-        Ok(initial_app_state)
+        Ok(())
     }
 
-    async fn update(&mut self) -> eyre::Result<()> {
-        tracing::info!("Performing update loop iteration");
+    /// Drops the on-chain state of the chain and indexes it again from
+    /// `latest_indexed_block_number` up to the head (or `maybe_to_block`).
+    ///
+    /// Changes are not reported: the caller is expected to apply access based
+    /// on the whole state.
+    async fn reindex_chain(
+        &self,
+        chain_source: &ChainSource,
+        app_state: &mut AppState,
+        latest_indexed_block_number: u64,
+        maybe_to_block: Option<u64>,
+    ) -> eyre::Result<()> {
+        let IndexingHeadResponse {
+            head_block_number,
+            finalized_block_number,
+        } = chain_source.indexing_head().await?;
+
+        let head_block_number = maybe_to_block.map_or(head_block_number, |to_block| {
+            to_block.min(head_block_number)
+        });
+        let finalized_block_number = finalized_block_number.min(head_block_number);
+
+        self.record_events(
+            app_state,
+            vec![BridgeEvent::ChainStateReset {
+                chain_id: chain_source.config.chain_id,
+                latest_indexed_block_number,
+            }],
+        )
+        .await?;
+
+        self.indexing(
+            chain_source,
+            app_state,
+            finalized_block_number,
+            finalized_block_number,
+        )
+        .await?;
+        // NOTE: Index the unfinalized part separately to keep the rollback range short.
+        if head_block_number > finalized_block_number {
+            self.indexing(
+                chain_source,
+                app_state,
+                head_block_number,
+                finalized_block_number,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
 
-        let latest_finalized_block_number = self.rpc_client.latest_finalized_block_number().await?;
+    #[tracing::instrument(level = "info", skip_all, fields(command = ?admin_command))]
+    async fn handle_admin_command(&self, admin_command: AdminCommand) -> eyre::Result<()> {
+        tracing::info!("Handling admin command");
 
         let mut writable_state = self.state.clone().write_owned().await;
 
-        let next_block_for_indexing = writable_state.latest_indexed_block_number + 1;
-        if latest_finalized_block_number <= next_block_for_indexing {
-            tracing::info!(
-                "Skip update iteration as there are no new blocks to index: {latest_finalized_block_number} <= {next_block_for_indexing}"
+        match admin_command {
+            AdminCommand::Resync => {
+                self.resync(&mut writable_state).await?;
+            }
+            AdminCommand::RefreshOcl { ocl_id } => {
+                self.resolve_ocl_owners(&mut writable_state, &[ocl_id])
+                    .await?;
+                // NOTE: Refresh is meant to repair access, so all operations are sent again.
+                if let Some(off_chain_ocl_project) =
+                    writable_state.off_chain_ocl_project_map.get(&ocl_id)
+                {
+                    let dataset_ids = get_project_dataset_ids(off_chain_ocl_project)
+                        .all_dataset_ids()
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    forget_applied_roles(&mut writable_state.applied_roles, &dataset_ids);
+                }
+                self.full_access_applying(&mut writable_state, ocl_id, "refresh")?;
+            }
+            AdminCommand::RefreshMultisig { chain_id, address } => {
+                self.refresh_multisig(&mut writable_state, chain_id, address)
+                    .await?;
+            }
+            AdminCommand::RewindIndexing { chain_id, to_block } => {
+                self.rewind_indexing(&mut writable_state, chain_id, to_block)
+                    .await?;
+            }
+        }
+
+        self.apply_pending_access_changes(&mut writable_state).await;
+
+        self.persist_state(&writable_state).await;
+
+        Ok(())
+    }
+
+    /// Applies the parameters that can be changed live. Changes of the other
+    /// ones are reported and take effect on restart.
+    async fn apply_reloaded_config(&mut self, reloaded_config: ReloadedConfig) -> eyre::Result<()> {
+        let ReloadedConfig {
+            config,
+            ocl_id_filter,
+        } = reloaded_config;
+
+        let restart_required_parameters = self.config.apply_reloadable(config);
+        if !restart_required_parameters.is_empty() {
+            tracing::warn!(
+                ?restart_required_parameters,
+                "Some of the changed parameters are only applied on restart"
+            );
+        }
+
+        if ocl_id_filter != self.ocl_id_filter {
+            tracing::info!(?ocl_id_filter, "OCL id filter is changed");
+            self.ocl_id_filter = ocl_id_filter;
+        }
+        // NOTE: Built from the merged config, so that they match the running chains.
+        let access_policies = self.config.access_policies(&self.config.chain_configs()?)?;
+        let previous_access_policies = self.access_policies.replace(access_policies);
+
+        let mut writable_state = self.state.clone().write_owned().await;
+
+        // NOTE: Projects are loaded on the next iteration, so that a changed OCL id filter
+        //       is applied without waiting for the loading interval.
+        writable_state.molecule_projects_last_requested_at = None;
+
+        if *previous_access_policies != *self.access_policies.get() {
+            tracing::info!("Access policies are changed, applying them");
+
+            self.apply_access_policies_change(&mut writable_state, &previous_access_policies)
+                .await?;
+        }
+
+        self.persist_state(&writable_state).await;
+
+        tracing::info!("Config is reloaded");
+
+        Ok(())
+    }
+
+    /// Updates the visibility of datasets that became public or stopped being
+    /// public, and brings the roles in line with the current policies.
+    async fn apply_access_policies_change(
+        &self,
+        app_state: &mut AppState,
+        previous_access_policies: &AccessPolicies,
+    ) -> eyre::Result<()> {
+        let access_policies = self.access_policies.get();
+        let mut public_dataset_ids = Vec::new();
+        let mut private_dataset_ids = Vec::new();
+
+        for off_chain_ocl_project in app_state.off_chain_ocl_project_map.values() {
+            let previous_public_dataset_ids =
+                get_public_dataset_ids(off_chain_ocl_project, &previous_access_policies.access)
+                    .into_iter()
+                    .collect::<HashSet<_>>();
+            let current_public_dataset_ids =
+                get_public_dataset_ids(off_chain_ocl_project, &access_policies.access)
+                    .into_iter()
+                    .collect::<HashSet<_>>();
+
+            public_dataset_ids.extend(
+                current_public_dataset_ids
+                    .difference(&previous_public_dataset_ids)
+                    .cloned(),
+            );
+            private_dataset_ids.extend(
+                previous_public_dataset_ids
+                    .difference(&current_public_dataset_ids)
+                    .cloned(),
+            );
+        }
+
+        if !public_dataset_ids.is_empty() || !private_dataset_ids.is_empty() {
+            self.push_pending_access_change(
+                &mut app_state.pending_access_changes,
+                PendingAccessChange {
+                    reason: "Access policy changed".to_string(),
+                    cause: AccessChangeCause::AccessPolicyChanged,
+                    accounts: Vec::new(),
+                    operations: Vec::new(),
+                    public_dataset_ids,
+                    private_dataset_ids,
+                },
             );
-            return Ok(());
         }
 
-        let IndexingResponse {
-            // NOTE: emphasize that this includes not just on-chain changes
-            on_chain_ocl_changes_map: mut ocl_changes_map,
-        } = self
-            .indexing(&mut writable_state, latest_finalized_block_number)
+        // NOTE: Roles are computed from the policies, so reconciliation grants and revokes
+        //       exactly what has changed.
+        self.reconciliation(app_state).await?;
+        self.apply_pending_access_changes(app_state).await;
+
+        Ok(())
+    }
+
+    async fn resync(&self, app_state: &mut AppState) -> eyre::Result<()> {
+        // NOTE: The state is rebuilt separately, so that it is not left half-built on failure.
+        let mut new_app_state = AppState::default();
+
+        self.record_events(&mut new_app_state, vec![BridgeEvent::StateReset])
             .await?;
+        self.build_state(&mut new_app_state).await?;
+        self.initial_access_applying(&mut new_app_state).await?;
+
+        // NOTE: The audit log is kept, so the records that were already pushed are not pushed again.
+        new_app_state.audit_log_next_pushed_record_id = app_state.audit_log_next_pushed_record_id;
+        new_app_state.access_changes_next_pushed_record_id =
+            app_state.access_changes_next_pushed_record_id;
+        // NOTE: Resync is meant to repair access, so the applied roles are not kept
+        //       and all operations are sent again.
+        // NOTE: Changes that are not applied yet precede the ones of the rebuilt state.
+        let mut pending_access_changes = std::mem::take(&mut app_state.pending_access_changes);
+        pending_access_changes.append(&mut new_app_state.pending_access_changes);
+        new_app_state.pending_access_changes = pending_access_changes;
+        *app_state = new_app_state;
+
+        Ok(())
+    }
+
+    async fn refresh_multisig(
+        &self,
+        app_state: &mut AppState,
+        chain_id: u64,
+        address: Address,
+    ) -> eyre::Result<()> {
+        let chain_source = self.chain_source(chain_id)?;
+        let chain_state = app_state
+            .chains
+            .get(&chain_id)
+            .context("Chain state is missing")?;
+
+        let to_block = chain_state.latest_indexed_block_number;
+        let owned_ocl_ids =
+            find_ocls_owned_by(&chain_state.on_chain_ocl_ownership_projection_map, address);
+
+        let maybe_multisig_state = chain_source.resolve_multisig(address, to_block).await?;
+
+        self.record_events(
+            app_state,
+            vec![BridgeEvent::MultisigDiscovered {
+                chain_id,
+                at_block: to_block,
+                address,
+                state: maybe_multisig_state,
+            }],
+        )
+        .await?;
+
+        self.resolve_ocl_owners(app_state, &owned_ocl_ids).await?;
+        for ocl_id in owned_ocl_ids {
+            self.full_access_applying(app_state, ocl_id, "multisig refresh")?;
+        }
+
+        Ok(())
+    }
+
+    async fn rewind_indexing(
+        &self,
+        app_state: &mut AppState,
+        chain_id: u64,
+        to_block: u64,
+    ) -> eyre::Result<()> {
+        let chain_source = self.chain_source(chain_id)?;
+        let chain_state = app_state
+            .chains
+            .get(&chain_id)
+            .context("Chain state is missing")?;
+
+        let labnft_contract_birth_block_minus_one =
+            chain_source.config.labnft_contract_birth_block - 1;
+        if to_block < labnft_contract_birth_block_minus_one {
+            bail!(
+                "Cannot rewind to block {to_block}: it is before the LabNFT contract birth block"
+            );
+        }
+        if to_block >= chain_state.latest_indexed_block_number {
+            bail!(
+                "Cannot rewind to block {to_block}: only blocks up to {} are indexed",
+                chain_state.latest_indexed_block_number
+            );
+        }
+
+        // NOTE: The state at the block is restored by indexing from scratch,
+        //       the following blocks will be indexed by the next update iteration.
+        self.reindex_chain(
+            chain_source,
+            app_state,
+            labnft_contract_birth_block_minus_one,
+            Some(to_block),
+        )
+        .await
+    }
+
+    async fn update(&mut self) -> eyre::Result<()> {
+        tracing::info!("Performing update loop iteration");
+
+        let mut writable_state = self.state.clone().write_owned().await;
+
+        // NOTE: Indexing further is postponed until the journaled changes are applied,
+        //       so that the changes are applied in the order they happened.
+        if !writable_state.pending_access_changes.is_empty() {
+            let all_applied = self.apply_pending_access_changes(&mut writable_state).await;
+
+            self.persist_state(&writable_state).await;
+
+            if !all_applied {
+                tracing::warn!("Skip update iteration: pending access changes are not applied yet");
+                return Ok(());
+            }
+        }
+
+        // NOTE: emphasize that this includes not just on-chain changes
+        let mut ocl_changes_map = HashMap::<OclId, OclChange>::new();
+        let mut has_new_blocks = false;
+
+        for chain_source in &self.chain_sources {
+            let chain_id = chain_source.config.chain_id;
+
+            let IndexingHeadResponse {
+                head_block_number,
+                finalized_block_number,
+            } = chain_source.indexing_head().await?;
+
+            let next_block_for_indexing = writable_state
+                .chains
+                .get(&chain_id)
+                .context("Chain state is missing")?
+                .latest_indexed_block_number
+                + 1;
+            if head_block_number <= next_block_for_indexing {
+                tracing::info!(
+                    chain_id,
+                    "Skip chain as there are no new blocks to index: {head_block_number} <= {next_block_for_indexing}"
+                );
+                continue;
+            }
+            has_new_blocks = true;
+
+            let IndexingResponse {
+                on_chain_ocl_changes_map,
+            } = self
+                .indexing(
+                    chain_source,
+                    &mut writable_state,
+                    head_block_number,
+                    finalized_block_number,
+                )
+                .await?;
+
+            for (ocl_id, on_chain_ocl_change) in on_chain_ocl_changes_map {
+                let ocl_change = ocl_changes_map.entry(ocl_id).or_default();
+                ocl_change
+                    .owner_changes
+                    .extend(on_chain_ocl_change.owner_changes);
+                ocl_change
+                    .holder_changes
+                    .extend(on_chain_ocl_change.holder_changes);
+                ocl_change
+                    .dropped_owners
+                    .extend(on_chain_ocl_change.dropped_owners);
+                ocl_change
+                    .on_chain_triggers
+                    .extend(on_chain_ocl_change.on_chain_triggers);
+            }
+        }
 
         let elapsed_secs: u64 = {
             let last_requested_at = writable_state
@@ -238,8 +820,15 @@ impl App {
             (Utc::now() - last_requested_at).num_seconds().try_into()?
         };
         let interval = self.config.molecule_projects_loading_interval_in_secs;
+        let is_projects_loading_due =
+            self.is_projects_loading_requested || elapsed_secs >= interval;
+
+        if !has_new_blocks && !is_projects_loading_due {
+            tracing::info!("Skip update iteration as there are no new blocks to index");
+            return Ok(());
+        }
 
-        if elapsed_secs >= interval {
+        if is_projects_loading_due {
             let versioned_file_changes_per_projects =
                 self.load_molecule_projects(&mut writable_state).await?;
 
@@ -250,214 +839,203 @@ impl App {
             }
 
             writable_state.molecule_projects_last_requested_at = Some(Utc::now());
+            self.is_projects_loading_requested = false;
         }
 
-        self.interval_access_applying(
-            &mut writable_state,
-            ocl_changes_map,
-            next_block_for_indexing,
-        )
-        .await?;
+        self.interval_access_applying(&mut writable_state, ocl_changes_map)
+            .await?;
+        self.apply_pending_access_changes(&mut writable_state).await;
+
+        if let Some(reconciliation_interval) = self.config.reconciliation_interval_in_secs {
+            let elapsed_secs: u64 = {
+                let last_reconciled_at = writable_state.last_reconciled_at.unwrap_or_default();
+                // NOTE: The restored time may be ahead of the clock.
+                (Utc::now() - last_reconciled_at)
+                    .num_seconds()
+                    .max(0)
+                    .try_into()?
+            };
+
+            if elapsed_secs >= reconciliation_interval {
+                // NOTE: Reconciliation is best-effort: it is retried on the next iteration,
+                //       and the work of this iteration is persisted anyway.
+                match self.reconciliation(&mut writable_state).await {
+                    Ok(_) => writable_state.last_reconciled_at = Some(Utc::now()),
+                    Err(e) => {
+                        tracing::error!(error = ?e, error_msg = %e, "Failed to reconcile access");
+                    }
+                }
+            }
+        }
+
+        self.persist_state(&writable_state).await;
 
         Ok(())
     }
 
-    #[tracing::instrument(level = "info", skip_all, fields(to_block = to_block))]
+    #[tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(
+            chain_id = chain_source.config.chain_id,
+            to_block = to_block,
+            finalized_block_number = finalized_block_number,
+        )
+    )]
     async fn indexing(
-        &mut self,
+        &self,
+        chain_source: &ChainSource,
         app_state: &mut AppState,
         to_block: u64,
+        finalized_block_number: u64,
     ) -> eyre::Result<IndexingResponse> {
-        // TODO parallel jobs index_labnft_contract && changed_ocl_multisig_owners
+        let chain_id = chain_source.config.chain_id;
+        let chain_state = app_state
+            .chains
+            .get(&chain_id)
+            .context("Chain state is missing")?;
 
-        let ocl_transfer_events = self
-            .index_labnft_contract(app_state.latest_indexed_block_number + 1, to_block)
-            .await?;
-        let ocl_ownership_diff_map = app_state
-            .on_chain_ocl_ownership_projection_map
-            .apply_events(ocl_transfer_events);
+        let mut events = Vec::new();
 
-        // TODO breakdown to unblock parallel calls
-        let IndexMultisigSafesResponse {
-            changed_ocl_multisig_owners,
-        } = self
-            .index_multisig_safes(
-                app_state,
-                app_state.latest_indexed_block_number + 1,
+        let from_block =
+            if let Some(fork_block_number) = chain_source.detect_reorg(chain_state).await? {
+                events.push(BridgeEvent::ChainReorganized {
+                    chain_id,
+                    fork_block_number,
+                });
+
+                // NOTE: Orphaned ranges are indexed again
+                chain_state
+                    .first_orphaned_checkpoint_index(fork_block_number)
+                    .map(|index| chain_state.unfinalized_checkpoints[index].from_block)
+                    .unwrap_or(chain_state.latest_indexed_block_number + 1)
+            } else {
+                chain_state.latest_indexed_block_number + 1
+            };
+
+        let known_multisigs = chain_state
+            .multisig
+            .iter()
+            .filter_map(|(address, maybe_multisig_state)| {
+                maybe_multisig_state.as_ref().map(|_| *address)
+            })
+            .collect::<Vec<_>>();
+
+        // NOTE: Logs of different contracts are independent, so they are fetched concurrently.
+        let (labnft_events, multisig_events, holder_token_events) = tokio::try_join!(
+            chain_source.index_labnft_contract(from_block, to_block),
+            chain_source.index_multisigs(known_multisigs, from_block, to_block),
+            chain_source.index_holder_tokens(from_block, to_block),
+        )?;
+
+        let mut on_chain_events = labnft_events;
+        on_chain_events.extend(multisig_events);
+        on_chain_events.extend(holder_token_events);
+
+        // NOTE: Keep the order in which events happened on the chain
+        on_chain_events.sort_by_key(BridgeEvent::block_position);
+        events.extend(on_chain_events.iter().cloned());
+
+        if to_block > finalized_block_number {
+            let to_block_hash = chain_source.rpc_client.block_hashes(to_block).await?.hash;
+
+            events.push(BridgeEvent::HeadBlocksIndexed {
+                chain_id,
                 to_block,
-            )
-            .await?;
+                to_block_hash,
+                finalized_block_number,
+            });
+        } else {
+            events.push(BridgeEvent::BlocksIndexed { chain_id, to_block });
+        }
 
-        app_state.latest_indexed_block_number = to_block;
+        let ProjectionChanges { mut on_chain, .. } = self.record_events(app_state, events).await?;
+        let OnChainProjectionChanges {
+            ocl_ownership_diff_map,
+            changed_ocl_multisig_owners,
+            changed_ocl_token_bound_account_owners,
+            changed_ocl_holders,
+            dropped_ocl_owners,
+        } = on_chain.remove(&chain_id).unwrap_or_default();
 
         // Populate blockchain changes:
 
         // 1. From LabNFT contract
         let mut ocl_changes_map: HashMap<_, _> = ocl_ownership_diff_map
             .into_iter()
-            .map(|(ocl_id, ownership_change)| (ocl_id, OclChange::new(ownership_change)))
+            .map(|(ocl_id, ownership_change)| (ocl_id, OclChange::new(chain_id, ownership_change)))
             .collect();
 
-        // 2. From multisig changes
-        for (ocl_id, owner) in changed_ocl_multisig_owners {
-            let ocl_change = ocl_changes_map.entry(ocl_id).or_default();
+        // 2. From multisig and token-bound account changes
+        for (ocl_id, owner) in changed_ocl_multisig_owners
+            .into_iter()
+            .chain(changed_ocl_token_bound_account_owners)
+        {
+            let ocl_change = ocl_changes_map.entry(ocl_id).or_default();
 
             // If the current owner changes, we will request new data from the multisig state if needed.
-            if ocl_change.owner_changes.is_none() {
-                // If there is no owner change, we need to trigger new permissions [re]grant in an OCL.
-                ocl_change.owner_changes = Some(OclOwnershipChange {
+            // If there is no owner change, we need to trigger new permissions [re]grant in an OCL.
+            ocl_change
+                .owner_changes
+                .entry(chain_id)
+                .or_insert(OclOwnershipChange {
                     former_owner: None,
                     current_owner: owner,
                 });
-            }
         }
 
-        Ok(IndexingResponse {
-            on_chain_ocl_changes_map: ocl_changes_map,
-        })
-    }
-
-    #[tracing::instrument(
-        level = "info",
-        skip_all,
-        fields(
-            from_block = from_block,
-            to_block = to_block,
-            diff = to_block.checked_sub(from_block),
-        )
-    )]
-    async fn index_labnft_contract(
-        &self,
-        from_block: u64,
-        to_block: u64,
-    ) -> eyre::Result<Vec<OclTransferEvent>> {
-        // TODO: static/const
-        let event_signatures = HashSet::from_iter([LabNFT::OclTransfer::SIGNATURE_HASH]);
-
-        let mut events = Vec::new();
-
-        self.rpc_client
-            .get_logs_ext(
-                // TODO: add method for only one address?
-                vec![self.config.labnft_contract_address],
-                event_signatures,
-                from_block,
-                to_block,
-                &mut |logs_chunk| {
-                    for log in logs_chunk.logs {
-                        match log.event_signature_hash() {
-                            LabNFT::OclTransfer::SIGNATURE_HASH => {
-                                let log_event = LabNFT::OclTransfer::decode_log(&log.inner)?;
-                                let event = log_event.data;
+        // 3. From IP-token transfers
+        for (ocl_id, holder_changes) in changed_ocl_holders {
+            ocl_changes_map
+                .entry(ocl_id)
+                .or_default()
+                .holder_changes
+                .insert(chain_id, holder_changes);
+        }
 
-                                events.push(event.into());
-                            }
-                            unknown_event_signature_hash => {
-                                bail!(
-                                    "Unknown event signature hash: {unknown_event_signature_hash}"
-                                )
-                            }
-                        }
-                    }
+        // 4. From chain reorganizations
+        for (ocl_id, dropped_owner) in dropped_ocl_owners {
+            ocl_changes_map
+                .entry(ocl_id)
+                .or_default()
+                .dropped_owners
+                .insert(chain_id, dropped_owner);
+        }
 
-                    Ok(())
-                },
-            )
-            .await?;
+        // Record the transactions that caused the changes (for the audit log)
+        let mut on_chain_triggers_map = build_on_chain_triggers_map(
+            chain_id,
+            &on_chain_events,
+            &app_state
+                .chains
+                .get(&chain_id)
+                .context("Chain state is missing")?
+                .on_chain_ocl_ownership_projection_map,
+        );
+        for (ocl_id, ocl_change) in &mut ocl_changes_map {
+            ocl_change.on_chain_triggers = on_chain_triggers_map.remove(ocl_id).unwrap_or_default();
+        }
 
-        Ok(events)
+        Ok(IndexingResponse {
+            on_chain_ocl_changes_map: ocl_changes_map,
+        })
     }
 
-    #[tracing::instrument(
-        level = "info",
-        skip_all,
-        fields(
-            from_block = from_block,
-            to_block = to_block,
-            diff = to_block.checked_sub(from_block),
-        )
-    )]
-    async fn index_multisig_safes(
+    /// Appends events to the log and projects them onto the state
+    async fn record_events(
         &self,
         app_state: &mut AppState,
-        from_block: u64,
-        to_block: u64,
-    ) -> eyre::Result<IndexMultisigSafesResponse> {
-        let multisigs = app_state.multisig.keys().copied().collect::<Vec<_>>();
-
-        if multisigs.is_empty() {
-            return Ok(IndexMultisigSafesResponse::default());
-        }
-
-        let mut changed_multisigs = HashSet::new();
-
-        self.rpc_client
-            .get_logs_ext(
-                multisigs,
-                // TODO: static/const
-                HashSet::from_iter([
-                    Safe::AddedOwner::SIGNATURE_HASH,
-                    Safe::RemovedOwner::SIGNATURE_HASH,
-                ]),
-                from_block,
-                to_block,
-                &mut |logs_chunk| {
-                    for log in logs_chunk.logs {
-                        let safe_address = log.address();
-
-                        // TODO: move state from here
-                        let Some(maybe_multisig_state) = app_state.multisig.get_mut(&safe_address) else {
-                            unreachable!();
-                        };
-                        let Some(multisig_state) = maybe_multisig_state else {
-                            unreachable!();
-                        };
-
-                        changed_multisigs.insert(safe_address);
-
-                        match log.event_signature_hash() {
-                            Safe::AddedOwner::SIGNATURE_HASH => {
-                                let added_owner = parse_safe_added_owner_event(&log.inner)?;
-                                multisig_state.current_owners.insert(added_owner);
-                            }
-                            Safe::RemovedOwner::SIGNATURE_HASH => {
-                                let removed_owner = parse_safe_removed_owner_event(&log.inner)?;
-                                multisig_state.current_owners.remove(&removed_owner);
-                                multisig_state.former_owners.insert(removed_owner);
-                            }
-                            unknown_event_signature_hash => {
-                                bail!("Unknown Safe event signature hash: {unknown_event_signature_hash}")
-                            }
-                        }
-                    }
-
-                    Ok(())
-                },
-            )
-            .await?;
-
-        // TODO breakdown to unblock parallel calls
-        let changed_ocl_multisig_owners = app_state
-            .on_chain_ocl_ownership_projection_map
-            .iter()
-            .fold(HashMap::new(), |mut acc, (ocl_id, ownership_projection)| {
-                if let Some(owner) = ownership_projection.current
-                    && changed_multisigs.contains(&owner)
-                {
-                    acc.insert(*ocl_id, owner);
-                }
-                acc
-            });
+        events: Vec<BridgeEvent>,
+    ) -> eyre::Result<ProjectionChanges> {
+        let entries = self.event_log.append(events).await?;
 
-        Ok(IndexMultisigSafesResponse {
-            changed_ocl_multisig_owners,
-        })
+        Ok(AppStateProjector::project(app_state, &entries))
     }
 
     // TODO: breakdown to smaller peaces
     #[tracing::instrument(level = "info", skip_all)]
     async fn load_molecule_projects(
-        &mut self,
+        &self,
         app_state: &mut AppState,
     ) -> eyre::Result<ChangedVersionedFilePerProjectMap> {
         // Project updates are based on several principles:
@@ -465,16 +1043,26 @@ impl App {
         //   we just need a larger offset.
         // - In case of checking molecule_access_level changes, we also request information about existing files.
 
+        let ocl_id_filter = self.ocl_id_filter.clone();
+
+        // Projects that are not allowed by the filter anymore lose the granted access.
+        self.drop_ignored_projects(app_state, &ocl_id_filter)
+            .await?;
+
         // I. Preparations.
-        let mut detected_changes_map = HashMap::new();
+
+        // Datasets whose head has not moved since the last loading are not queried.
+        let dataset_heads = self.fetch_dataset_heads(app_state).await;
+        let scanned_dataset_heads = app_state.scanned_dataset_heads.clone();
+        let is_dataset_changed = |dataset_id: &DatasetID| {
+            dataset_heads.is_dataset_changed(&scanned_dataset_heads, dataset_id)
+        };
 
         // First, check for new files in known projects (if any).
-        let existing_projects = app_state
+        let existing_data_room_dataset_ids_with_offsets = app_state
             .off_chain_ocl_project_map
-            .values_mut()
-            .collect::<Vec<_>>();
-        let existing_data_room_dataset_ids_with_offsets = existing_projects
-            .iter()
+            .values()
+            .filter(|project| is_dataset_changed(&project.entry.data_room_dataset_id))
             .map(|project| DataRoomDatasetIdWithOffset {
                 dataset_id: project.entry.data_room_dataset_id.clone(),
                 offset: project.latest_data_room_offset + 1,
@@ -482,16 +1070,50 @@ impl App {
             .collect::<Vec<_>>();
 
         // Second, check for new project entries.
-        let new_projects_entries = self
-            .kamu_node_api_client
-            .get_molecule_project_entries(
-                app_state
-                    .molecule_projects_dataset_offset
-                    .map(|offset| offset + 1)
-                    .unwrap_or(0),
-                self.config.ignore_ocl_ids.as_ref(),
-            )
-            .await?;
+        let mut new_projects_entries = if dataset_heads
+            .is_molecule_projects_changed(&scanned_dataset_heads)
+        {
+            self.kamu_node_api_client
+                .get_molecule_project_entries(
+                    app_state
+                        .molecule_projects_dataset_offset
+                        .map(|offset| offset + 1)
+                        .unwrap_or(0),
+                    &ocl_id_filter,
+                )
+                .await?
+        } else {
+            tracing::debug!("Skip querying project entries: \"projects\" dataset is not changed");
+            Vec::new()
+        };
+
+        // Projects that were filtered out before may have entries below the offset,
+        // so they are loaded from the beginning.
+        if let Some(molecule_projects_dataset_offset) = app_state.molecule_projects_dataset_offset
+            && let Some(newly_allowed_filter) =
+                ocl_id_filter.newly_allowed(&app_state.molecule_projects_ocl_id_filter)
+        {
+            let newly_allowed_projects_entries = self
+                .kamu_node_api_client
+                .get_molecule_project_entries(0, &newly_allowed_filter)
+                .await?;
+
+            tracing::info!(
+                newly_allowed_projects_entries_count = newly_allowed_projects_entries.len(),
+                "Load projects allowed by the changed OCL id filter"
+            );
+
+            // NOTE: Entries above the offset were already returned.
+            new_projects_entries.extend(
+                newly_allowed_projects_entries
+                    .into_iter()
+                    .filter(|project_entry| {
+                        project_entry.offset <= molecule_projects_dataset_offset
+                    }),
+            );
+            new_projects_entries.sort_by_key(|project_entry| project_entry.offset);
+        }
+
         let new_data_room_dataset_ids_with_offsets = new_projects_entries
             .iter()
             .map(|project| DataRoomDatasetIdWithOffset {
@@ -524,9 +1146,12 @@ impl App {
                         acc.extend(entries.added_entities.keys().cloned());
                         acc
                     });
-            let existing_file_entry_dataset_ids = existing_projects
-                .iter()
-                .flat_map(|project| project.actual_files_map.keys().cloned())
+            let existing_file_entry_dataset_ids = app_state
+                .off_chain_ocl_project_map
+                .values()
+                .flat_map(|project| project.actual_files_map.keys())
+                .filter(|dataset_id| is_dataset_changed(dataset_id))
+                .cloned()
                 .collect::<Vec<_>>();
 
             let mut ids = Vec::with_capacity(
@@ -541,10 +1166,11 @@ impl App {
             .get_latest_molecule_access_levels_by_dataset_ids(versioned_file_dataset_ids)
             .await?;
 
+        let mut events = Vec::new();
+
         // II. Process existing projects.
-        for existing_project in existing_projects {
+        for existing_project in app_state.off_chain_ocl_project_map.values() {
             let project_entry = &existing_project.entry;
-            let mut detected_changes = Vec::new();
 
             let _span = tracing::debug_span!(
                 "Process existing project",
@@ -553,52 +1179,32 @@ impl App {
             )
             .entered();
 
-            let Some(versioned_files_entries) = versioned_files_entries_map
+            let maybe_versioned_files_entries = versioned_files_entries_map
                 // NOTE: try to extract a value from the map
-                .remove(&project_entry.data_room_dataset_id)
-            else {
-                continue;
-            };
-
-            let changed_versioned_files = prepare_changes_based_on_changed_versioned_files_entries(
-                project_entry,
-                &versioned_files_entries,
-                &molecule_access_levels_map,
-            );
-            detected_changes.extend(changed_versioned_files);
-
-            let added_file_entries_map = build_added_file_entries_with_molecule_access_level_map(
-                versioned_files_entries.added_entities,
-                &molecule_access_levels_map,
-            );
+                .remove(&project_entry.data_room_dataset_id);
+
+            // Check if molecule_access_level has changed for existing files, ...
+            if !project_entry.is_deleted() {
+                events.extend(prepare_events_based_on_changed_molecule_access_levels(
+                    project_entry,
+                    &existing_project.actual_files_map,
+                    maybe_versioned_files_entries.as_ref(),
+                    &molecule_access_levels_map,
+                    is_dataset_changed,
+                ));
+            }
 
-            // Update actual files ...
-            existing_project.actual_files_map.retain(|dataset_id, _| {
-                versioned_files_entries
-                    .removed_entities
-                    .contains_key(dataset_id)
-            });
-            existing_project
-                .actual_files_map
-                .extend(added_file_entries_map);
-            // ... (and check if molecule_access_level has changed for existing files), ...
-            let changed_versioned_files = prepare_changes_based_on_changed_molecule_access_levels(
-                project_entry,
-                &existing_project.actual_files_map,
-                &molecule_access_levels_map,
-            );
-            detected_changes.extend(changed_versioned_files);
-
-            // ... removed files, ...
-            existing_project
-                .removed_files_map
-                .extend(versioned_files_entries.removed_entities);
-            // ... and offset.
-            existing_project.latest_data_room_offset =
-                versioned_files_entries.latest_data_room_offset;
-
-            if !detected_changes.is_empty() {
-                detected_changes_map.insert(project_entry.ocl_id, detected_changes);
+            // ... and added/removed files.
+            if let Some(versioned_files_entries) = maybe_versioned_files_entries {
+                events.push(BridgeEvent::DataRoomFilesChanged {
+                    ocl_id: project_entry.ocl_id,
+                    latest_data_room_offset: versioned_files_entries.latest_data_room_offset,
+                    added_files: build_added_file_entries_with_molecule_access_level_map(
+                        versioned_files_entries.added_entities,
+                        &molecule_access_levels_map,
+                    ),
+                    removed_files: versioned_files_entries.removed_entities,
+                });
             }
         }
 
@@ -607,8 +1213,6 @@ impl App {
         let mut new_molecule_projects_dataset_offset = app_state.molecule_projects_dataset_offset;
 
         for project_entry in new_projects_entries {
-            let mut detected_changes = Vec::new();
-
             let _span = tracing::debug_span!(
                 "Process new project entry",
                 symbol = project_entry.symbol,
@@ -616,12 +1220,14 @@ impl App {
             )
             .entered();
 
-            new_molecule_projects_dataset_offset = Some(project_entry.offset);
+            // NOTE: Newly allowed projects may be behind the offset.
+            new_molecule_projects_dataset_offset =
+                new_molecule_projects_dataset_offset.max(Some(project_entry.offset));
 
+            // NOTE: Only the identity may be known for an OCL that was not transferred yet.
             if app_state
-                .on_chain_ocl_ownership_projection_map
-                .get(&project_entry.ocl_id)
-                .is_none()
+                .ocl_ownership_projections(&project_entry.ocl_id)
+                .all(|(_, ownership_projection)| ownership_projection.current.is_none())
             {
                 tracing::info!("Skip project: not present in blockchain");
                 continue;
@@ -639,36 +1245,175 @@ impl App {
                 "Continue: project is present in blockchain and has files in data-room"
             );
 
-            let changed_versioned_files = prepare_changes_based_on_changed_versioned_files_entries(
-                &project_entry,
-                &versioned_files_entries,
-                &molecule_access_levels_map,
-            );
-            detected_changes.extend(changed_versioned_files);
+            events.push(BridgeEvent::MoleculeProjectLoaded {
+                project: OffChainMoleculeProjectProjection {
+                    latest_data_room_offset: versioned_files_entries.latest_data_room_offset,
+                    actual_files_map: build_added_file_entries_with_molecule_access_level_map(
+                        versioned_files_entries.added_entities,
+                        &molecule_access_levels_map,
+                    ),
+                    removed_files_map: versioned_files_entries.removed_entities,
+                    entry: project_entry,
+                },
+            });
+        }
 
-            let actual_files_map = build_added_file_entries_with_molecule_access_level_map(
-                versioned_files_entries.added_entities,
-                &molecule_access_levels_map,
-            );
+        events.push(BridgeEvent::MoleculeProjectsScanned {
+            molecule_projects_dataset_offset: new_molecule_projects_dataset_offset,
+            ocl_id_filter,
+        });
+
+        let ProjectionChanges {
+            changed_files_per_project,
+            ..
+        } = self.record_events(app_state, events).await?;
+
+        // NOTE: Heads are fetched before the queries, so changes made in between
+        //       are queried again next time rather than missed.
+        app_state.scanned_dataset_heads = dataset_heads;
+
+        Ok(changed_files_per_project)
+    }
+
+    /// Returns the heads of `molecule/projects` and of the data rooms and files
+    /// of the known projects. Heads that failed to be fetched are unknown,
+    /// so their datasets are queried as changed.
+    async fn fetch_dataset_heads(&self, app_state: &AppState) -> DatasetHeads {
+        let dataset_ids = app_state
+            .off_chain_ocl_project_map
+            .values()
+            .flat_map(|project| {
+                std::iter::once(&project.entry.data_room_dataset_id)
+                    .chain(project.actual_files_map.keys())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let molecule_projects = self
+            .kamu_node_api_client
+            .get_molecule_projects_head()
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = ?e, error_msg = %e, "Failed to fetch the head of \"projects\" dataset");
+                None
+            });
+        let datasets = self
+            .kamu_node_api_client
+            .get_dataset_heads(dataset_ids)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = ?e, error_msg = %e, "Failed to fetch the heads of project datasets");
+                DatasetHeadsMap::new()
+            });
+
+        DatasetHeads {
+            molecule_projects,
+            datasets,
+        }
+    }
+
+    /// Revokes the access granted for projects that are not allowed by the
+    /// filter and drops them from the state
+    async fn drop_ignored_projects(
+        &self,
+        app_state: &mut AppState,
+        ocl_id_filter: &OclIdFilter,
+    ) -> eyre::Result<()> {
+        let ignored_ocl_ids = app_state
+            .off_chain_ocl_project_map
+            .keys()
+            .filter(|ocl_id| !ocl_id_filter.is_allowed(ocl_id))
+            .copied()
+            .collect::<Vec<_>>();
+
+        if ignored_ocl_ids.is_empty() {
+            return Ok(());
+        }
+
+        tracing::info!(
+            ?ignored_ocl_ids,
+            "Drop projects ignored by the OCL id filter"
+        );
+
+        self.resolve_ocl_owners(app_state, &ignored_ocl_ids).await?;
+
+        for ocl_id in &ignored_ocl_ids {
+            self.revoke_ignored_ocl_access(app_state, *ocl_id)?;
+        }
+
+        self.record_events(
+            app_state,
+            ignored_ocl_ids
+                .into_iter()
+                .map(|ocl_id| BridgeEvent::MoleculeProjectIgnored { ocl_id })
+                .collect(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Adds the change that revokes all roles on the project datasets that
+    /// were granted by the bridge and makes them private.
+    ///
+    /// Expects owners to be resolved beforehand (see [`App::resolve_ocl_owners`])
+    fn revoke_ignored_ocl_access(
+        &self,
+        app_state: &mut AppState,
+        ocl_id: OclId,
+    ) -> eyre::Result<()> {
+        let Some(off_chain_ocl_project) = app_state.off_chain_ocl_project_map.get(&ocl_id) else {
+            return Ok(());
+        };
+        let symbol = &off_chain_ocl_project.entry.symbol;
+
+        tracing::info!(%ocl_id, symbol, "Revoke access of ignored OCL");
+
+        // NOTE: Both the roles applied before and the ones that would be granted now
+        //       are revoked, so that nothing is left if the applied roles are incomplete.
+        let mut revoked_roles = BTreeSet::new();
+
+        for dataset_id in get_project_dataset_ids(off_chain_ocl_project).all_dataset_ids() {
+            let Some(applied_dataset_roles) = app_state.applied_roles.get(dataset_id) else {
+                continue;
+            };
 
-            if !detected_changes.is_empty() {
-                detected_changes_map.insert(project_entry.ocl_id, detected_changes);
+            for (account_id, operation) in applied_dataset_roles {
+                if matches!(operation, DatasetRoleOperation::Set(_)) {
+                    revoked_roles.insert((account_id.clone(), dataset_id.clone()));
+                }
             }
+        }
 
-            app_state.off_chain_ocl_project_map.insert(
-                project_entry.ocl_id,
-                OffChainMoleculeProjectProjection {
-                    entry: project_entry,
-                    latest_data_room_offset: versioned_files_entries.latest_data_room_offset,
-                    actual_files_map,
-                    removed_files_map: versioned_files_entries.removed_entities,
-                },
-            );
+        let access_policies = self.access_policies.get();
+        for on_chain_ocl in get_on_chain_ocls(&app_state.chains, &access_policies, ocl_id) {
+            for desired_access in get_desired_ocl_access(off_chain_ocl_project, &on_chain_ocl)? {
+                revoked_roles.insert((desired_access.account_id, desired_access.dataset_id));
+            }
         }
 
-        app_state.molecule_projects_dataset_offset = new_molecule_projects_dataset_offset;
+        let pending_access_change = PendingAccessChange {
+            reason: format!("OCL ({ocl_id}/{symbol}) is ignored"),
+            cause: AccessChangeCause::OclIgnored { ocl_id },
+            accounts: Vec::new(),
+            operations: revoked_roles
+                .into_iter()
+                .map(|(account_id, dataset_id)| {
+                    AccountDatasetRelationOperation::revoke_access(account_id, dataset_id)
+                })
+                .collect(),
+            public_dataset_ids: Vec::new(),
+            private_dataset_ids: get_public_dataset_ids(
+                off_chain_ocl_project,
+                &access_policies.access,
+            ),
+        };
+        self.push_pending_access_change(
+            &mut app_state.pending_access_changes,
+            pending_access_change,
+        );
 
-        Ok(detected_changes_map)
+        Ok(())
     }
 
     #[tracing::instrument(
@@ -682,18 +1427,49 @@ impl App {
         &self,
         app_state: &mut AppState,
         ocl_changes_map: HashMap<OclId, OclChange>,
-        to_block: u64,
     ) -> eyre::Result<()> {
-        for (ocl_id, ocl_change) in ocl_changes_map {
+        // Resolve all involved addresses up-front, so that the state is only read below.
+        let mut addresses_to_resolve = HashMap::<u64, HashSet<Address>>::new();
+        for (ocl_id, ocl_change) in &ocl_changes_map {
+            for (chain_id, owner_changes) in &ocl_change.owner_changes {
+                let addresses = addresses_to_resolve.entry(*chain_id).or_default();
+                addresses.insert(owner_changes.current_owner);
+                addresses.extend(owner_changes.former_owner);
+            }
+            for (chain_id, dropped_owner) in &ocl_change.dropped_owners {
+                addresses_to_resolve
+                    .entry(*chain_id)
+                    .or_default()
+                    .insert(*dropped_owner);
+            }
+            if !ocl_change.changed_files.is_empty() || !ocl_change.holder_changes.is_empty() {
+                for (chain_id, on_chain_ocl_ownership) in
+                    app_state.ocl_ownership_projections(ocl_id)
+                {
+                    addresses_to_resolve
+                        .entry(chain_id)
+                        .or_default()
+                        .extend(ocl_owner_addresses(on_chain_ocl_ownership));
+                }
+            }
+        }
+        for (chain_id, addresses) in addresses_to_resolve {
+            self.resolve_multisigs(app_state, chain_id, addresses)
+                .await?;
+        }
+
+        let access_policies = self.access_policies.get();
+        let mut pending_access_changes = Vec::new();
+
+        for (ocl_id, mut ocl_change) in ocl_changes_map {
             tracing::info!(%ocl_id, "OCL interval update");
 
-            let Some(on_chain_ocl_ownership) =
-                app_state.on_chain_ocl_ownership_projection_map.get(&ocl_id)
-            else {
+            let on_chain_ocls = get_on_chain_ocls(&app_state.chains, &access_policies, ocl_id);
+            if on_chain_ocls.is_empty() {
                 tracing::info!("Skip OCL update: not found on-chain");
-                assert!(ocl_change.owner_changes.is_none());
+                assert!(ocl_change.owner_changes.is_empty());
                 continue;
-            };
+            }
 
             let Some(off_chain_ocl_project) = app_state.off_chain_ocl_project_map.get(&ocl_id)
             else {
@@ -702,328 +1478,1210 @@ impl App {
                 continue;
             };
 
-            let operations = self
-                .interval_access_applying_for_ocl(
+            let DatasetVisibilityChanges {
+                public_dataset_ids,
+                private_dataset_ids,
+            } = get_dataset_visibility_changes(&ocl_change.changed_files, &access_policies.access);
+
+            // NOTE: Accounts of different chains are different DIDs,
+            //       so each chain is processed independently.
+            let mut accounts = HashSet::new();
+            let mut operations = Vec::new();
+            for on_chain_ocl in &on_chain_ocls {
+                operations.extend(self.interval_access_applying_for_ocl(
                     ocl_id,
-                    on_chain_ocl_ownership,
+                    on_chain_ocl,
                     off_chain_ocl_project,
-                    ocl_change,
-                    &mut app_state.multisig,
-                    to_block,
-                )
-                .await?;
+                    ocl_change.owner_changes.remove(&on_chain_ocl.chain_id),
+                    ocl_change.holder_changes.remove(&on_chain_ocl.chain_id),
+                    ocl_change.dropped_owners.remove(&on_chain_ocl.chain_id),
+                    &ocl_change.changed_files,
+                    &mut accounts,
+                )?);
+            }
 
-            // Apply operations
-            if !operations.is_empty() {
-                let symbol = &off_chain_ocl_project.entry.symbol;
+            let symbol = &off_chain_ocl_project.entry.symbol;
+            let off_chain_trigger =
+                (!ocl_change.changed_files.is_empty()).then(|| OffChainTrigger {
+                    data_room_dataset_id: off_chain_ocl_project.entry.data_room_dataset_id.clone(),
+                    data_room_offset: off_chain_ocl_project.latest_data_room_offset,
+                });
 
-                app_state.access_changes.insert(
-                    Utc::now(),
-                    AccessChanges {
-                        reason: format!("OCL ({ocl_id}/{symbol}) interval update"),
-                        operations: operations.clone(),
-                    },
-                );
-            }
+            pending_access_changes.push(PendingAccessChange {
+                reason: format!("OCL ({ocl_id}/{symbol}) interval update"),
+                cause: AccessChangeCause::OclChanged {
+                    ocl_id,
+                    on_chain: ocl_change.on_chain_triggers,
+                    off_chain: off_chain_trigger,
+                },
+                accounts: accounts.into_iter().collect(),
+                operations,
+                public_dataset_ids,
+                private_dataset_ids,
+            });
+        }
 
-            self.kamu_node_api_client
-                .apply_account_dataset_relations(operations)
-                .await?;
+        // NOTE: Changes are applied from the journal, so that none of them is lost on failure.
+        for pending_access_change in pending_access_changes {
+            self.push_pending_access_change(
+                &mut app_state.pending_access_changes,
+                pending_access_change,
+            );
         }
 
         Ok(())
     }
 
     // TODO: breakdown to smaller peaces
-    #[tracing::instrument(level = "info", skip_all, fields(ocl_id = %ocl_id))]
-    async fn interval_access_applying_for_ocl(
+    #[tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(ocl_id = %ocl_id, chain_id = on_chain_ocl.chain_id)
+    )]
+    fn interval_access_applying_for_ocl(
         &self,
         ocl_id: OclId,
-        on_chain_ocl_ownership: &OclOwnershipProjection,
+        on_chain_ocl: &OnChainOcl<'_>,
         off_chain_ocl_project: &OffChainMoleculeProjectProjection,
-        ocl_change: OclChange,
-        multisig: &mut HashMap<Address, Option<MultisigState>>,
-        to_block: u64,
+        maybe_owner_changes: Option<OclOwnershipChange>,
+        maybe_holder_changes: Option<OclHolderChanges>,
+        maybe_dropped_owner: Option<Address>,
+        changed_files: &[ChangedVersionedFile],
+        accounts_to_create: &mut HashSet<DidPhk>,
     ) -> eyre::Result<Vec<AccountDatasetRelationOperation>> {
+        let OnChainOcl {
+            chain_id,
+            ownership: on_chain_ocl_ownership,
+            owners_resolver,
+            maybe_holders,
+            access_policy,
+        } = on_chain_ocl;
+
         // 1. Process new blockchain data.
         let blockchain_based_operations = {
             // Prepare account information
-            let mut current_owners = HashSet::new();
-            let mut revoke_access_accounts = HashSet::new();
+            let mut accounts = OclAccounts::default();
 
-            // TODO: PERF: self.get_owners() in parallel for all possible multisig?
-            if let Some(owner_changes) = ocl_change.owner_changes {
+            if let Some(owner_changes) = maybe_owner_changes {
                 let GetOwnersResponse {
                     current_owners: new_owners,
                     former_owners,
-                } = self
-                    .get_owners(owner_changes.current_owner, multisig, to_block)
-                    .await?;
-                current_owners.extend(new_owners);
-                revoke_access_accounts.extend(former_owners);
+                } = owners_resolver.get_owners(owner_changes.current_owner);
+                accounts.current_owners.extend(new_owners);
+                accounts.revoke_access_accounts.extend(former_owners);
 
                 if let Some(former_owner) = owner_changes.former_owner {
                     let GetOwnersResponse {
                         current_owners: old_owners,
                         former_owners,
-                    } = self.get_owners(former_owner, multisig, to_block).await?;
-                    revoke_access_accounts.extend(old_owners);
-                    revoke_access_accounts.extend(former_owners);
+                    } = owners_resolver.get_owners(former_owner);
+                    accounts
+                        .revoke_access_accounts
+                        .extend(old_owners.into_keys());
+                    accounts.revoke_access_accounts.extend(former_owners);
                 }
             }
 
-            account_access_sanity_checks(&current_owners, &mut revoke_access_accounts);
-
+            if let Some(dropped_owner) = maybe_dropped_owner {
+                let GetOwnersResponse {
+                    current_owners: old_owners,
+                    former_owners,
+                } = owners_resolver.get_owners(dropped_owner);
+                accounts
+                    .revoke_access_accounts
+                    .extend(old_owners.into_keys());
+                accounts.revoke_access_accounts.extend(former_owners);
+            }
+
+            // Former owners keep reading holder files while they hold the IP-token
+            if let Some(holders) = maybe_holders {
+                accounts.current_holders = accounts
+                    .revoke_access_accounts
+                    .iter()
+                    .filter(|address| holders.balances.contains_key(address))
+                    .copied()
+                    .collect();
+            }
+            accounts
+                .revoke_holder_access_accounts
+                .clone_from(&accounts.revoke_access_accounts);
+
+            account_access_sanity_checks(&mut accounts);
+
             // Create accounts
-            let CreateAccountsResponse {
-                current_owners_did_pkhs,
-                revoke_access_accounts_did_pkh,
-            } = self.create_did_pkh_accounts(current_owners, revoke_access_accounts)?;
-
-            let all_accounts_count =
-                current_owners_did_pkhs.len() + revoke_access_accounts_did_pkh.len();
-            let accounts = {
-                let mut v = Vec::with_capacity(all_accounts_count);
-                v.extend(current_owners_did_pkhs.clone());
-                v.extend(revoke_access_accounts_did_pkh.clone());
-                v
-            };
+            let accounts_did_pkhs = self.create_did_pkh_accounts(*chain_id, &accounts)?;
 
-            self.kamu_node_api_client
-                .create_wallet_accounts(accounts)
-                .await?;
+            accounts_to_create.extend(accounts_did_pkhs.values().copied());
 
             let project_dataset_ids = get_project_dataset_ids(off_chain_ocl_project);
 
             build_operations(
                 project_dataset_ids,
-                &current_owners_did_pkhs,
-                &revoke_access_accounts_did_pkh,
+                &accounts,
+                &accounts_did_pkhs,
+                access_policy,
             )
         };
 
-        // 2. Process the project's changes.
-        let operations = if !ocl_change.changed_files.is_empty() {
-            let GetAccountsByOclProjectResponse {
-                current_owners,
-                revoke_access_accounts,
-            } = self
-                .get_accounts_by_ocl_project(on_chain_ocl_ownership, multisig, to_block)
-                .await?;
-            let CreateAccountsResponse {
-                current_owners_did_pkhs,
-                revoke_access_accounts_did_pkh,
-            } = self.create_did_pkh_accounts(current_owners, revoke_access_accounts)?;
+        // 2. Process IP-token holder changes.
+        let holder_based_operations =
+            if let Some(OclHolderChanges { added, removed }) = maybe_holder_changes {
+                let GetOwnersResponse { current_owners, .. } = on_chain_ocl_ownership
+                    .current
+                    .map(|current_owner| owners_resolver.get_owners(current_owner))
+                    .unwrap_or_default();
+
+                let mut accounts = OclAccounts {
+                    current_owners,
+                    current_holders: added,
+                    revoke_holder_access_accounts: removed,
+                    ..Default::default()
+                };
+                account_access_sanity_checks(&mut accounts);
+
+                let accounts_did_pkhs = self.create_did_pkh_accounts(*chain_id, &accounts)?;
+
+                accounts_to_create.extend(accounts_did_pkhs.values().copied());
+
+                // NOTE: Only datasets that holders have access to are affected.
+                let ProjectDatasetIds {
+                    mut dataset_ids, ..
+                } = get_project_dataset_ids(off_chain_ocl_project);
+                dataset_ids
+                    .retain(|(_, dataset_kind)| access_policy.roles(dataset_kind).holder.is_some());
+
+                build_operations(
+                    ProjectDatasetIds {
+                        dataset_ids,
+                        ..Default::default()
+                    },
+                    &accounts,
+                    &accounts_did_pkhs,
+                    access_policy,
+                )
+            } else {
+                Vec::new()
+            };
+
+        // 3. Process the project's changes.
+        let project_based_operations = if !changed_files.is_empty() {
+            let accounts = get_accounts_by_ocl_project(
+                on_chain_ocl_ownership,
+                owners_resolver,
+                *maybe_holders,
+            );
+            let accounts_did_pkhs = self.create_did_pkh_accounts(*chain_id, &accounts)?;
 
             let mut changed_project_dataset_ids = ProjectDatasetIds::default();
+            let mut revoke_operations = Vec::new();
 
-            for changed_file in &ocl_change.changed_files {
-                match changed_file.change {
+            for changed_file in changed_files {
+                match &changed_file.change {
                     DataRoomFileChange::Added(molecule_access_level) => {
-                        partition_dataset_id_by_molecule_access_level(
+                        changed_project_dataset_ids.dataset_ids.push((
                             &changed_file.dataset_id,
-                            molecule_access_level,
-                            &mut changed_project_dataset_ids.owner_file_dataset_ids,
-                            &mut changed_project_dataset_ids.holder_file_dataset_ids,
-                        );
+                            DatasetKind::File(molecule_access_level.clone()),
+                        ));
                     }
                     DataRoomFileChange::Removed => {
                         changed_project_dataset_ids
                             .removed_file_dataset_ids
                             .push(&changed_file.dataset_id);
                     }
-                    DataRoomFileChange::MoleculeAccessLevelChanged { from: _, to } => {
-                        partition_dataset_id_by_molecule_access_level(
-                            &changed_file.dataset_id,
-                            to,
-                            &mut changed_project_dataset_ids.owner_file_dataset_ids,
-                            &mut changed_project_dataset_ids.holder_file_dataset_ids,
-                        );
+                    DataRoomFileChange::MoleculeAccessLevelChanged { from, to } => {
+                        let former_kind = DatasetKind::File(from.clone());
+                        let kind = DatasetKind::File(to.clone());
+
+                        // NOTE: Accounts that only had access because of the former level lose it.
+                        for (address, did_pkh) in &accounts_did_pkhs {
+                            let had_role = accounts
+                                .desired_role(*address, &former_kind, access_policy)
+                                .is_some();
+                            let has_role = accounts
+                                .desired_role(*address, &kind, access_policy)
+                                .is_some();
+
+                            if had_role && !has_role {
+                                revoke_operations.push(
+                                    AccountDatasetRelationOperation::revoke_access(
+                                        did_pkh.to_string(),
+                                        changed_file.dataset_id.clone(),
+                                    ),
+                                );
+                            }
+                        }
+
+                        changed_project_dataset_ids
+                            .dataset_ids
+                            .push((&changed_file.dataset_id, kind));
                     }
                 }
             }
 
-            let project_based_operations = build_operations(
+            let mut operations = build_operations(
                 changed_project_dataset_ids,
-                &current_owners_did_pkhs,
-                &revoke_access_accounts_did_pkh,
-            );
-
-            let mut operations = Vec::with_capacity(
-                blockchain_based_operations.len() + project_based_operations.len(),
+                &accounts,
+                &accounts_did_pkhs,
+                access_policy,
             );
-            operations.extend(blockchain_based_operations);
-            operations.extend(project_based_operations);
+            operations.extend(revoke_operations);
             operations
         } else {
-            blockchain_based_operations
+            Vec::new()
         };
 
+        let mut operations = Vec::with_capacity(
+            blockchain_based_operations.len()
+                + holder_based_operations.len()
+                + project_based_operations.len(),
+        );
+        operations.extend(blockchain_based_operations);
+        operations.extend(holder_based_operations);
+        operations.extend(project_based_operations);
+
         Ok(operations)
     }
 
-    #[tracing::instrument(level = "info", skip_all)]
-    async fn initial_access_applying(&self, app_state: &mut AppState) -> eyre::Result<()> {
-        for (ocl_id, off_chain_ocl_project) in &app_state.off_chain_ocl_project_map {
-            let symbol = &off_chain_ocl_project.entry.symbol;
+    #[tracing::instrument(level = "info", skip_all)]
+    async fn initial_access_applying(&self, app_state: &mut AppState) -> eyre::Result<()> {
+        // Resolve all involved addresses up-front, so that the state is only read below.
+        self.resolve_project_owners(app_state).await?;
+
+        let ocl_ids = app_state
+            .off_chain_ocl_project_map
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+
+        for ocl_id in ocl_ids {
+            self.full_access_applying(app_state, ocl_id, "initial update")?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds access changes for all owners and holders of the OCL to the journal.
+    ///
+    /// Expects owners to be resolved beforehand (see [`App::resolve_ocl_owners`])
+    fn full_access_applying(
+        &self,
+        app_state: &mut AppState,
+        ocl_id: OclId,
+        update_kind: &str,
+    ) -> eyre::Result<()> {
+        let Some(off_chain_ocl_project) = app_state.off_chain_ocl_project_map.get(&ocl_id) else {
+            tracing::info!(%ocl_id, "Skip OCL {update_kind}: no entry in \"projects\" dataset");
+            return Ok(());
+        };
+        let symbol = &off_chain_ocl_project.entry.symbol;
+
+        tracing::info!(%ocl_id, symbol, "OCL {update_kind}");
+
+        let access_policies = self.access_policies.get();
+        let on_chain_ocls = get_on_chain_ocls(&app_state.chains, &access_policies, ocl_id);
+        if on_chain_ocls.is_empty() {
+            tracing::info!("Skip OCL update: not found on-chain");
+            return Ok(());
+        }
+
+        let mut accounts = HashSet::new();
+        let mut operations = Vec::new();
+        for on_chain_ocl in &on_chain_ocls {
+            operations.extend(self.initial_access_applying_for_ocl(
+                ocl_id,
+                on_chain_ocl,
+                off_chain_ocl_project,
+                &mut accounts,
+            )?);
+        }
+
+        let pending_access_change = PendingAccessChange {
+            reason: format!("OCL ({ocl_id}/{symbol}) {update_kind}"),
+            cause: AccessChangeCause::OclFullUpdate { ocl_id },
+            accounts: accounts.into_iter().collect(),
+            operations,
+            public_dataset_ids: get_public_dataset_ids(
+                off_chain_ocl_project,
+                &access_policies.access,
+            ),
+            private_dataset_ids: Vec::new(),
+        };
+        self.push_pending_access_change(
+            &mut app_state.pending_access_changes,
+            pending_access_change,
+        );
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(ocl_id = %ocl_id, chain_id = on_chain_ocl.chain_id)
+    )]
+    fn initial_access_applying_for_ocl(
+        &self,
+        ocl_id: OclId,
+        on_chain_ocl: &OnChainOcl<'_>,
+        off_chain_ocl_project: &OffChainMoleculeProjectProjection,
+        accounts_to_create: &mut HashSet<DidPhk>,
+    ) -> eyre::Result<Vec<AccountDatasetRelationOperation>> {
+        // Prepare account information
+        let accounts = get_accounts_by_ocl_project(
+            on_chain_ocl.ownership,
+            &on_chain_ocl.owners_resolver,
+            on_chain_ocl.maybe_holders,
+        );
+
+        // Create accounts
+        let accounts_did_pkhs = self.create_did_pkh_accounts(on_chain_ocl.chain_id, &accounts)?;
+
+        accounts_to_create.extend(accounts_did_pkhs.values().copied());
+
+        let project_dataset_ids = get_project_dataset_ids(off_chain_ocl_project);
+        let operations = build_operations(
+            project_dataset_ids,
+            &accounts,
+            &accounts_did_pkhs,
+            on_chain_ocl.access_policy,
+        );
+
+        Ok(operations)
+    }
+
+    #[tracing::instrument(level = "info", skip_all, fields(
+        public_datasets_count = visibility_changes.public_dataset_ids.len(),
+        private_datasets_count = visibility_changes.private_dataset_ids.len(),
+    ))]
+    async fn apply_datasets_visibility(
+        &self,
+        visibility_changes: DatasetVisibilityChanges,
+    ) -> eyre::Result<()> {
+        let DatasetVisibilityChanges {
+            public_dataset_ids,
+            private_dataset_ids,
+        } = visibility_changes;
+
+        if !public_dataset_ids.is_empty() {
+            self.kamu_node_api_client
+                .set_datasets_visibility(public_dataset_ids, DatasetVisibility::Public)
+                .await?;
+        }
+        if !private_dataset_ids.is_empty() {
+            self.kamu_node_api_client
+                .set_datasets_visibility(private_dataset_ids, DatasetVisibility::Private)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "info", skip_all)]
+    async fn reconciliation(
+        &self,
+        app_state: &mut AppState,
+    ) -> eyre::Result<Vec<AccountDatasetRelationOperation>> {
+        self.resolve_project_owners(app_state).await?;
+
+        let DesiredDatasetRolesResponse {
+            mut desired_roles,
+            accounts,
+        } = self.desired_dataset_roles(app_state)?;
+
+        let actual_roles = self
+            .kamu_node_api_client
+            .get_dataset_account_roles(desired_roles.keys().cloned().collect())
+            .await?;
+
+        // NOTE: Roles cannot be set for datasets that are missing (e.g. manually deleted).
+        desired_roles.retain(|dataset_id, _| actual_roles.contains_key(dataset_id));
+
+        let operations = reconcile_dataset_roles(&desired_roles, &actual_roles);
+
+        tracing::info!(
+            datasets_count = desired_roles.len(),
+            operations_count = operations.len(),
+            "Reconciliation operations are computed"
+        );
+
+        if operations.is_empty() {
+            return Ok(operations);
+        }
+
+        self.push_pending_access_change(
+            &mut app_state.pending_access_changes,
+            PendingAccessChange {
+                reason: "Reconciliation".to_string(),
+                cause: AccessChangeCause::Reconciliation,
+                accounts,
+                operations: operations.clone(),
+                public_dataset_ids: Vec::new(),
+                private_dataset_ids: Vec::new(),
+            },
+        );
+
+        self.apply_pending_access_changes(app_state).await;
+
+        Ok(operations)
+    }
+
+    /// Adds the change to the end of the journal. Its operations are counted
+    /// as planned once, so that changes applied again after a failure are not
+    /// counted twice.
+    fn push_pending_access_change(
+        &self,
+        pending_access_changes: &mut VecDeque<PendingAccessChange>,
+        pending_access_change: PendingAccessChange,
+    ) {
+        self.metrics
+            .access_operations_planned_num_total
+            .inc_by(pending_access_change.operations.len() as u64);
+
+        pending_access_changes.push_back(pending_access_change);
+    }
+
+    /// Applies the journaled access changes in order. On failure, the
+    /// remaining changes are kept in the journal to be applied later.
+    ///
+    /// Returns `true` if the journal is drained.
+    async fn apply_pending_access_changes(&self, app_state: &mut AppState) -> bool {
+        while !app_state.pending_access_changes.is_empty() {
+            // NOTE: Changes of different projects touch different datasets, so they are
+            //       applied concurrently, while changes of the same datasets keep their order.
+            let independent_changes_count = count_independent_access_changes(
+                &app_state.pending_access_changes,
+                self.config.concurrency_limit.get(),
+            );
+            let planned_access_changes = app_state
+                .pending_access_changes
+                .iter()
+                .take(independent_changes_count)
+                .map(|pending_access_change| {
+                    self.plan_access_change(&app_state.applied_roles, pending_access_change)
+                })
+                .collect::<Vec<_>>();
+
+            let apply_results = futures::future::join_all(planned_access_changes.iter().map(
+                |planned_access_change| {
+                    self.apply_pending_access_change_with_retries(planned_access_change)
+                },
+            ))
+            .await;
+
+            // NOTE: Changes that were applied after a failed one stay in the journal
+            //       to keep the order; applying them again is idempotent.
+            for (planned_access_change, apply_result) in
+                planned_access_changes.iter().zip(apply_results)
+            {
+                if let Err(e) = apply_result {
+                    tracing::error!(
+                        error = ?e,
+                        error_msg = %e,
+                        pending_access_changes_count = app_state.pending_access_changes.len(),
+                        "Failed to apply pending access changes, will be retried later"
+                    );
+                    return false;
+                }
+
+                // NOTE: Nothing is applied in dry-run mode, so no operation is a no-op later.
+                if !self.dry_run {
+                    record_applied_operations(
+                        &mut app_state.applied_roles,
+                        &planned_access_change.operations,
+                    );
+                }
+                app_state.pending_access_changes.pop_front();
+            }
+        }
+
+        true
+    }
+
+    /// Minimizes the operations of the change against the roles applied
+    /// before. Reconciliation operations are based on the actual roles, so
+    /// they are only deduplicated.
+    fn plan_access_change(
+        &self,
+        applied_roles: &AppliedRolesMap,
+        pending_access_change: &PendingAccessChange,
+    ) -> PendingAccessChange {
+        let operations = match pending_access_change.cause {
+            AccessChangeCause::Reconciliation => {
+                collapse_operations(&pending_access_change.operations)
+            }
+            AccessChangeCause::OclChanged { .. }
+            | AccessChangeCause::OclFullUpdate { .. }
+            | AccessChangeCause::OclIgnored { .. }
+            | AccessChangeCause::AccessPolicyChanged => {
+                plan_operations(applied_roles, &pending_access_change.operations)
+            }
+        };
+
+        PendingAccessChange {
+            operations,
+            ..pending_access_change.clone()
+        }
+    }
+
+    /// Applies the change and records it in the audit log along with the
+    /// Kamu Node response
+    #[tracing::instrument(level = "info", skip_all, fields(reason = pending_access_change.reason))]
+    async fn apply_pending_access_change_with_retries(
+        &self,
+        pending_access_change: &PendingAccessChange,
+    ) -> eyre::Result<()> {
+        let mut attempt = 1;
+        let apply_result = loop {
+            match self
+                .apply_pending_access_change(pending_access_change)
+                .await
+            {
+                Err(e) if attempt < ACCESS_CHANGE_APPLYING_MAX_ATTEMPTS => {
+                    tracing::warn!(
+                        error = ?e,
+                        error_msg = %e,
+                        attempt,
+                        "Failed to apply access changes, retrying"
+                    );
+
+                    tokio::time::sleep(ACCESS_CHANGE_APPLYING_RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+                apply_result => break apply_result,
+            }
+        };
+
+        let result = match &apply_result {
+            Ok(None) => return Ok(()),
+            Ok(Some(_)) if self.dry_run => AccessChangeResult::DryRun,
+            Ok(Some(message)) => AccessChangeResult::Applied {
+                message: message.clone(),
+            },
+            Err(e) => AccessChangeResult::Failed {
+                error: e.to_string(),
+            },
+        };
+
+        // NOTE: Failing to record the changes should not stop applying access.
+        if let Err(e) = self
+            .audit_log
+            .append(NewAccessAuditRecord {
+                reason: pending_access_change.reason.clone(),
+                cause: pending_access_change.cause.clone(),
+                operations: pending_access_change.operations.clone(),
+                result,
+            })
+            .await
+        {
+            tracing::error!(error = ?e, error_msg = %e, "Failed to record access changes");
+        }
+
+        apply_result?;
+
+        Ok(())
+    }
+
+    /// Returns the message of the Kamu Node response, if there were operations
+    async fn apply_pending_access_change(
+        &self,
+        pending_access_change: &PendingAccessChange,
+    ) -> eyre::Result<Option<String>> {
+        if !pending_access_change.accounts.is_empty() {
+            self.kamu_node_api_client
+                .create_wallet_accounts(pending_access_change.accounts.clone())
+                .await?;
+        }
+
+        let mut messages = Vec::<String>::new();
+        for operations_chunk in pending_access_change
+            .operations
+            .chunks(self.config.kamu_node_max_operations_per_request.get())
+        {
+            let message = self
+                .kamu_node_api_client
+                .apply_account_dataset_relations(operations_chunk.to_vec())
+                .await?;
+
+            self.metrics
+                .access_operations_sent_num_total
+                .inc_by(operations_chunk.len() as u64);
+
+            if !messages.contains(&message) {
+                messages.push(message);
+            }
+        }
+        let maybe_message = (!messages.is_empty()).then(|| messages.join("; "));
+
+        self.apply_datasets_visibility(DatasetVisibilityChanges {
+            public_dataset_ids: pending_access_change.public_dataset_ids.clone(),
+            private_dataset_ids: pending_access_change.private_dataset_ids.clone(),
+        })
+        .await?;
+
+        Ok(maybe_message)
+    }
+
+    /// Pushes the audit log records that were not pushed yet into the Kamu
+    /// Node datasets (if configured): whole records into the audit log dataset
+    /// and one row per applied operation into the access changes dataset.
+    ///
+    /// Failures are not fatal: the records will be pushed on the next attempt.
+    async fn push_audit_log_records(&self) {
+        // NOTE: Nothing is published in dry-run mode.
+        if self.dry_run {
+            return;
+        }
+
+        let mut writable_state = self.state.write().await;
+        let next_pushed_record_ids = (
+            writable_state.audit_log_next_pushed_record_id,
+            writable_state.access_changes_next_pushed_record_id,
+        );
+
+        if let Some(dataset_alias) = &self.config.audit_log_dataset_alias
+            && let Err(e) = self
+                .push_audit_log_records_impl(dataset_alias, &mut writable_state)
+                .await
+        {
+            tracing::error!(error = ?e, error_msg = %e, "Failed to push audit log records");
+        }
+
+        if let Some(dataset_alias) = &self.config.access_changes_dataset_alias
+            && let Err(e) = self
+                .push_access_changes_impl(dataset_alias, &mut writable_state)
+                .await
+        {
+            tracing::error!(error = ?e, error_msg = %e, "Failed to push access changes");
+        }
+
+        if (
+            writable_state.audit_log_next_pushed_record_id,
+            writable_state.access_changes_next_pushed_record_id,
+        ) != next_pushed_record_ids
+        {
+            self.persist_state(&writable_state).await;
+        }
+    }
+
+    async fn push_audit_log_records_impl(
+        &self,
+        dataset_alias: &str,
+        app_state: &mut AppState,
+    ) -> eyre::Result<()> {
+        loop {
+            let records = read_unpushed_audit_log_records(
+                self.audit_log.as_ref(),
+                &mut app_state.audit_log_next_pushed_record_id,
+                AUDIT_LOG_PUSH_BATCH_SIZE,
+            )
+            .await?;
+            let Some(last_record) = records.last() else {
+                return Ok(());
+            };
+            let next_pushed_record_id = last_record.id + 1;
+
+            tracing::info!(records_count = records.len(), "Pushing audit log records");
+
+            self.kamu_node_api_client
+                .ingest_records(dataset_alias, records_to_ndjson(&records)?)
+                .await?;
+
+            app_state.audit_log_next_pushed_record_id = next_pushed_record_id;
+        }
+    }
+
+    async fn push_access_changes_impl(
+        &self,
+        dataset_alias: &str,
+        app_state: &mut AppState,
+    ) -> eyre::Result<()> {
+        loop {
+            let records = read_unpushed_audit_log_records(
+                self.audit_log.as_ref(),
+                &mut app_state.access_changes_next_pushed_record_id,
+                AUDIT_LOG_PUSH_BATCH_SIZE,
+            )
+            .await?;
+            let Some(last_record) = records.last() else {
+                return Ok(());
+            };
+            let next_pushed_record_id = last_record.id + 1;
+
+            let rows = records
+                .iter()
+                .flat_map(|record| build_access_change_rows(app_state, record))
+                .collect::<Vec<_>>();
+
+            // NOTE: Batches of failed changes produce no rows.
+            if !rows.is_empty() {
+                tracing::info!(
+                    records_count = records.len(),
+                    rows_count = rows.len(),
+                    "Pushing access changes"
+                );
+
+                self.kamu_node_api_client
+                    .ingest_records(dataset_alias, records_to_ndjson(&rows)?)
+                    .await?;
+            }
+
+            app_state.access_changes_next_pushed_record_id = next_pushed_record_id;
+        }
+    }
+
+    /// Computes roles that current owners and holders should have for datasets
+    /// of projects that are present on-chain
+    fn desired_dataset_roles(
+        &self,
+        app_state: &AppState,
+    ) -> eyre::Result<DesiredDatasetRolesResponse> {
+        let access_policies = self.access_policies.get();
+        let mut desired_roles = DatasetAccountRolesMap::new();
+        let mut accounts = HashSet::new();
+
+        for (ocl_id, off_chain_ocl_project) in &app_state.off_chain_ocl_project_map {
+            for on_chain_ocl in get_on_chain_ocls(&app_state.chains, &access_policies, *ocl_id) {
+                // NOTE: Datasets without desired roles (e.g. removed files) are still
+                //       reconciled, so that stray roles are revoked.
+                for dataset_id in get_project_dataset_ids(off_chain_ocl_project).all_dataset_ids() {
+                    desired_roles.entry(dataset_id.clone()).or_default();
+                }
+
+                for desired_access in get_desired_ocl_access(off_chain_ocl_project, &on_chain_ocl)?
+                {
+                    accounts.insert((desired_access.chain_id, desired_access.address));
+
+                    desired_roles
+                        .entry(desired_access.dataset_id)
+                        .or_default()
+                        .insert(desired_access.account_id, desired_access.role);
+                }
+            }
+        }
+
+        let accounts = accounts
+            .into_iter()
+            .map(|(chain_id, address)| self.create_did_phk(chain_id, address))
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        Ok(DesiredDatasetRolesResponse {
+            desired_roles,
+            accounts,
+        })
+    }
+
+    async fn resolve_project_owners(&self, app_state: &mut AppState) -> eyre::Result<()> {
+        let ocl_ids = app_state
+            .off_chain_ocl_project_map
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+
+        self.resolve_ocl_owners(app_state, &ocl_ids).await
+    }
+
+    async fn resolve_ocl_owners(
+        &self,
+        app_state: &mut AppState,
+        ocl_ids: &[OclId],
+    ) -> eyre::Result<()> {
+        let addresses_to_resolve_per_chain = app_state
+            .chains
+            .iter()
+            .map(|(chain_id, chain_state)| {
+                let addresses_to_resolve = ocl_ids
+                    .iter()
+                    .filter_map(|ocl_id| {
+                        chain_state
+                            .on_chain_ocl_ownership_projection_map
+                            .get(ocl_id)
+                    })
+                    .flat_map(ocl_owner_addresses)
+                    .collect::<HashSet<_>>();
+
+                (*chain_id, addresses_to_resolve)
+            })
+            .collect::<Vec<_>>();
+
+        for (chain_id, addresses_to_resolve) in addresses_to_resolve_per_chain {
+            self.resolve_multisigs(app_state, chain_id, addresses_to_resolve)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether addresses that were not seen before are multisigs and
+    /// records the result in the log
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(chain_id = chain_id, addresses_count = addresses.len())
+    )]
+    async fn resolve_multisigs(
+        &self,
+        app_state: &mut AppState,
+        chain_id: u64,
+        addresses: HashSet<Address>,
+    ) -> eyre::Result<()> {
+        let chain_source = self.chain_source(chain_id)?;
+        let chain_state = app_state
+            .chains
+            .get(&chain_id)
+            .context("Chain state is missing")?;
+
+        let to_block = chain_state.latest_indexed_block_number;
+
+        let addresses = resolve_token_bound_accounts(
+            addresses,
+            &chain_state.on_chain_ocl_ownership_projection_map,
+        );
+
+        let unknown_addresses = addresses
+            .into_iter()
+            .filter(|address| !chain_state.multisig.contains_key(address))
+            .collect::<Vec<_>>();
+
+        // NOTE: Addresses are split into as many batches as jobs can run concurrently,
+        //       so that resolvers that support batching (e.g. via multicall) make fewer requests.
+        let concurrency_limit = self.config.concurrency_limit.get();
+        let batch_size = unknown_addresses.len().div_ceil(concurrency_limit).max(1);
+
+        let resolved_batches = futures::stream::iter(unknown_addresses.chunks(batch_size))
+            .map(|addresses_batch| {
+                chain_source.resolve_multisigs(addresses_batch.to_vec(), to_block)
+            })
+            .buffered(concurrency_limit)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let events = resolved_batches
+            .into_iter()
+            .flatten()
+            .map(
+                |(address, maybe_multisig_state)| BridgeEvent::MultisigDiscovered {
+                    chain_id,
+                    at_block: to_block,
+                    address,
+                    state: maybe_multisig_state,
+                },
+            )
+            .collect::<Vec<_>>();
+
+        if !events.is_empty() {
+            self.record_events(app_state, events).await?;
+        }
+
+        Ok(())
+    }
+
+    fn chain_source(&self, chain_id: u64) -> eyre::Result<&ChainSource> {
+        self.chain_sources
+            .iter()
+            .find(|chain_source| chain_source.config.chain_id == chain_id)
+            .with_context(|| format!("Chain ID '{chain_id}' is not configured"))
+    }
+
+    fn create_did_phk(&self, chain_id: u64, address: Address) -> eyre::Result<DidPhk> {
+        DidPhk::new_from_chain_id(chain_id, address)
+    }
+
+    /// Creates DIDs of all accounts involved in an OCL access update
+    fn create_did_pkh_accounts(
+        &self,
+        chain_id: u64,
+        accounts: &OclAccounts,
+    ) -> eyre::Result<HashMap<Address, DidPhk>> {
+        accounts
+            .all_addresses()
+            .map(|address| Ok((address, self.create_did_phk(chain_id, address)?)))
+            .collect()
+    }
+}
+
+/// Chain with a LabNFT deployment along with the clients to read it
+pub struct ChainSource {
+    pub config: ChainConfig,
+    pub rpc_client: DynProvider,
+    /// Supported multisig kinds
+    pub multisig_registry: Arc<MultisigProviderRegistry>,
+}
+
+impl ChainSource {
+    async fn indexing_head(&self) -> eyre::Result<IndexingHeadResponse> {
+        let finalized_block_number = self.rpc_client.latest_finalized_block_number().await?;
+
+        let head_block_number = match self.config.indexing_head {
+            IndexingHead::Finalized => finalized_block_number,
+            indexing_head @ (IndexingHead::Safe | IndexingHead::Latest) => self
+                .rpc_client
+                .latest_block_number_by_tag(indexing_head.block_tag())
+                .await?
+                .saturating_sub(self.config.indexing_confirmation_depth)
+                .max(finalized_block_number),
+        };
+
+        Ok(IndexingHeadResponse {
+            head_block_number,
+            finalized_block_number,
+        })
+    }
+
+    /// Compares the parent hash of the next block with the hash of the latest
+    /// indexed one and, if they differ, returns the fork point
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn detect_reorg(&self, chain_state: &ChainState) -> eyre::Result<Option<u64>> {
+        let Some(latest_checkpoint) = chain_state.unfinalized_checkpoints.last() else {
+            return Ok(None);
+        };
+
+        // NOTE: If the node has not seen the next block yet, there is nothing to index either,
+        //       so the check is repeated on the next iteration.
+        let Some(next_block) = self
+            .rpc_client
+            .maybe_block_hashes(latest_checkpoint.to_block + 1)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if next_block.parent_hash == latest_checkpoint.to_block_hash {
+            return Ok(None);
+        }
+
+        tracing::warn!(
+            block_number = latest_checkpoint.to_block,
+            indexed_block_hash = %latest_checkpoint.to_block_hash,
+            actual_block_hash = %next_block.parent_hash,
+            chain_id = self.config.chain_id,
+            "Chain reorganization detected",
+        );
+
+        // Find the latest checkpoint that is still on the canonical chain
+        for checkpoint in chain_state.unfinalized_checkpoints.iter().rev() {
+            let block = self.rpc_client.block_hashes(checkpoint.to_block).await?;
+
+            if block.hash == checkpoint.to_block_hash {
+                // NOTE: The latest checkpoint may still match if the head has changed in between.
+                let is_orphaned = checkpoint.to_block < latest_checkpoint.to_block;
+                return Ok(is_orphaned.then_some(checkpoint.to_block));
+            }
+        }
+
+        // NOTE: The oldest checkpoint starts right after a finalized block.
+        let oldest_checkpoint = &chain_state.unfinalized_checkpoints[0];
+
+        Ok(Some(oldest_checkpoint.from_block - 1))
+    }
+
+    #[tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(
+            from_block = from_block,
+            to_block = to_block,
+            diff = to_block.checked_sub(from_block),
+        )
+    )]
+    async fn index_labnft_contract(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> eyre::Result<Vec<BridgeEvent>> {
+        // TODO: static/const
+        let event_signatures = HashSet::from_iter([
+            LabNFT::OclTransfer::SIGNATURE_HASH,
+            LabNFT::OclIdentityCreated::SIGNATURE_HASH,
+        ]);
+
+        let mut events = Vec::new();
 
-            tracing::info!(%ocl_id, symbol, "OCL initial update");
+        self.rpc_client
+            .get_logs_ext(
+                // TODO: add method for only one address?
+                vec![self.config.labnft_contract_address],
+                event_signatures,
+                from_block,
+                to_block,
+                &mut |logs_chunk| {
+                    for log in logs_chunk.logs {
+                        let block_number = log.block_number.context("Missing log block number")?;
+                        let log_index = log.log_index.context("Missing log index")?;
+                        let tx_hash = log.transaction_hash;
 
-            let Some(on_chain_ocl_ownership) =
-                app_state.on_chain_ocl_ownership_projection_map.get(ocl_id)
-            else {
-                tracing::info!("Skip OCL update: not found on-chain");
-                continue;
-            };
+                        match log.event_signature_hash() {
+                            LabNFT::OclTransfer::SIGNATURE_HASH => {
+                                let log_event = LabNFT::OclTransfer::decode_log(&log.inner)?;
+                                let event = log_event.data;
 
-            let operations = self
-                .initial_access_applying_for_ocl(
-                    *ocl_id,
-                    on_chain_ocl_ownership,
-                    off_chain_ocl_project,
-                    &mut app_state.multisig,
-                    app_state.latest_indexed_block_number,
-                )
-                .await?;
+                                events.push(BridgeEvent::OclTransferred {
+                                    chain_id: self.config.chain_id,
+                                    block_number,
+                                    log_index,
+                                    tx_hash,
+                                    ocl_id: event.oclId.into(),
+                                    from: event.from,
+                                    to: event.to,
+                                });
+                            }
+                            LabNFT::OclIdentityCreated::SIGNATURE_HASH => {
+                                let log_event = LabNFT::OclIdentityCreated::decode_log(&log.inner)?;
+                                let event = log_event.data;
 
-            // Apply operations
-            if !operations.is_empty() {
-                app_state.access_changes.insert(
-                    Utc::now(),
-                    AccessChanges {
-                        reason: format!("OCL ({ocl_id}/{symbol}) initial update"),
-                        operations: operations.clone(),
-                    },
-                );
-            }
+                                events.push(BridgeEvent::OclIdentityCreated {
+                                    chain_id: self.config.chain_id,
+                                    block_number,
+                                    log_index,
+                                    tx_hash,
+                                    ocl_id: event.oclId.into(),
+                                    account: event.account,
+                                });
+                            }
+                            unknown_event_signature_hash => {
+                                bail!(
+                                    "Unknown event signature hash: {unknown_event_signature_hash}"
+                                )
+                            }
+                        }
+                    }
 
-            self.kamu_node_api_client
-                .apply_account_dataset_relations(operations)
-                .await?;
-        }
+                    Ok(())
+                },
+            )
+            .await?;
 
-        Ok(())
+        Ok(events)
     }
 
-    #[tracing::instrument(level = "info", skip_all, fields(ocl_id = %ocl_id))]
-    async fn initial_access_applying_for_ocl(
+    #[tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(
+            from_block = from_block,
+            to_block = to_block,
+            diff = to_block.checked_sub(from_block),
+        )
+    )]
+    async fn index_multisigs(
         &self,
-        ocl_id: OclId,
-        on_chain_ocl_ownership: &OclOwnershipProjection,
-        off_chain_ocl_project: &OffChainMoleculeProjectProjection,
-        multisig: &mut HashMap<Address, Option<MultisigState>>,
+        multisigs: Vec<Address>,
+        from_block: u64,
         to_block: u64,
-    ) -> eyre::Result<Vec<AccountDatasetRelationOperation>> {
-        // Prepare account information
-        let GetAccountsByOclProjectResponse {
-            current_owners,
-            revoke_access_accounts,
-        } = self
-            .get_accounts_by_ocl_project(on_chain_ocl_ownership, multisig, to_block)
-            .await?;
+    ) -> eyre::Result<Vec<BridgeEvent>> {
+        if multisigs.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        // Create accounts
-        let CreateAccountsResponse {
-            current_owners_did_pkhs,
-            revoke_access_accounts_did_pkh,
-        } = self.create_did_pkh_accounts(current_owners, revoke_access_accounts)?;
-
-        let all_accounts_count =
-            current_owners_did_pkhs.len() + revoke_access_accounts_did_pkh.len();
-        let accounts = {
-            let mut v = Vec::with_capacity(all_accounts_count);
-            v.extend(current_owners_did_pkhs.clone());
-            v.extend(revoke_access_accounts_did_pkh.clone());
-            v
-        };
+        let mut events = Vec::new();
 
-        self.kamu_node_api_client
-            .create_wallet_accounts(accounts)
-            .await?;
+        self.rpc_client
+            .get_logs_ext(
+                multisigs,
+                self.multisig_registry.membership_event_signatures(),
+                from_block,
+                to_block,
+                &mut |logs_chunk| {
+                    for log in logs_chunk.logs {
+                        let multisig = log.address();
+                        let block_number = log.block_number.context("Missing log block number")?;
+                        let log_index = log.log_index.context("Missing log index")?;
+                        let tx_hash = log.transaction_hash;
+
+                        match self.multisig_registry.parse_membership_event(&log.inner)? {
+                            MembershipChange::Added { owner } => {
+                                events.push(BridgeEvent::MultisigOwnerAdded {
+                                    chain_id: self.config.chain_id,
+                                    block_number,
+                                    log_index,
+                                    tx_hash,
+                                    multisig,
+                                    owner,
+                                });
+                            }
+                            MembershipChange::Removed { owner } => {
+                                events.push(BridgeEvent::MultisigOwnerRemoved {
+                                    chain_id: self.config.chain_id,
+                                    block_number,
+                                    log_index,
+                                    tx_hash,
+                                    multisig,
+                                    owner,
+                                });
+                            }
+                            MembershipChange::ThresholdChanged { threshold } => {
+                                events.push(BridgeEvent::MultisigThresholdChanged {
+                                    chain_id: self.config.chain_id,
+                                    block_number,
+                                    log_index,
+                                    tx_hash,
+                                    multisig,
+                                    threshold,
+                                });
+                            }
+                        }
+                    }
 
-        // Apply operations
-        let project_dataset_ids = get_project_dataset_ids(off_chain_ocl_project);
-        let operations = build_operations(
-            project_dataset_ids,
-            &current_owners_did_pkhs,
-            &revoke_access_accounts_did_pkh,
-        );
+                    Ok(())
+                },
+            )
+            .await?;
 
-        Ok(operations)
+        Ok(events)
     }
 
-    #[tracing::instrument(level = "debug", skip_all, fields(address = %address, to_block = %to_block))]
-    async fn get_owners(
+    #[tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(
+            from_block = from_block,
+            to_block = to_block,
+            diff = to_block.checked_sub(from_block),
+        )
+    )]
+    async fn index_holder_tokens(
         &self,
-        address: Address,
-        multisig: &mut HashMap<Address, Option<MultisigState>>,
+        from_block: u64,
         to_block: u64,
-    ) -> eyre::Result<GetOwnersResponse> {
-        let multisig_state_vacant_entry = match multisig.entry(address) {
-            Entry::Occupied(maybe_multisig_occupied_entry) => {
-                // Extract information about an already known address:
-                let res = maybe_multisig_occupied_entry
-                    .get()
-                    .as_ref()
-                    // 1) If a known multisig wallet
-                    .map(|multisig| GetOwnersResponse {
-                        current_owners: multisig.current_owners.clone(),
-                        former_owners: multisig.former_owners.clone(),
-                    })
-                    // 2) If a known regular wallet
-                    .unwrap_or_else(|| GetOwnersResponse {
-                        current_owners: HashSet::from([address]),
-                        former_owners: Default::default(),
-                    });
-                // Early return for readability
-                return Ok(res);
-            }
-            Entry::Vacant(multisig_state_vacant_entry) => multisig_state_vacant_entry,
-        };
-
-        // Check if the address belongs to Safe Wallet
-        let Some(multisig_owners_from_api) =
-            self.multisig_resolver.get_multisig_owners(address).await?
-        else {
-            // Remember that it's not a multisig account ...
-            multisig_state_vacant_entry.insert(None);
-            // ... and early return for readability
-            return Ok(GetOwnersResponse {
-                current_owners: HashSet::from([address]),
-                former_owners: Default::default(),
-            });
-        };
-
-        // From SafeWalletApiService we can only get current owners, but we are also interested in former ones.
-        // Restore state up to the requested block (typically the last finalized block).
-
-        // Safe Wallet before v1.3.0 did not have the SafeSetup event that would allow using logs
-        // only to restore the full ownership history (https://github.com/safe-global/safe-smart-account/issues/233).
-        // Therefore, we use the current owners list from the API and the for former owners from the RemovedOwner event.
+    ) -> eyre::Result<Vec<BridgeEvent>> {
+        let ocl_ids_by_holder_token = self.config.ocl_ids_by_holder_token();
+        if ocl_ids_by_holder_token.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        let mut new_multisig_state = MultisigState {
-            current_owners: multisig_owners_from_api,
-            former_owners: Default::default(),
-        };
+        let mut events = Vec::new();
 
         self.rpc_client
             .get_logs_ext(
-                vec![address],
-                HashSet::from_iter([Safe::RemovedOwner::SIGNATURE_HASH]),
-                0, // From the beginning
+                ocl_ids_by_holder_token.keys().copied().collect(),
+                // TODO: static/const
+                HashSet::from_iter([IERC20::Transfer::SIGNATURE_HASH]),
+                from_block,
                 to_block,
                 &mut |logs_chunk| {
                     for log in logs_chunk.logs {
-                        match log.event_signature_hash() {
-                            Safe::RemovedOwner::SIGNATURE_HASH => {
-                                let removed_owner = parse_safe_removed_owner_event(&log.inner)?;
+                        let ocl_id = *ocl_ids_by_holder_token
+                            .get(&log.address())
+                            .context("Unexpected IP-token address")?;
+                        let block_number = log.block_number.context("Missing log block number")?;
+                        let log_index = log.log_index.context("Missing log index")?;
+                        let tx_hash = log.transaction_hash;
 
-                                if !new_multisig_state.current_owners.contains(&removed_owner) {
-                                    new_multisig_state.former_owners.insert(removed_owner);
-                                }
+                        match log.event_signature_hash() {
+                            IERC20::Transfer::SIGNATURE_HASH => {
+                                let event = IERC20::Transfer::decode_log(&log.inner)?.data;
+
+                                events.push(BridgeEvent::HolderTokenTransferred {
+                                    chain_id: self.config.chain_id,
+                                    block_number,
+                                    log_index,
+                                    tx_hash,
+                                    ocl_id,
+                                    from: event.from,
+                                    to: event.to,
+                                    value: event.value,
+                                });
                             }
                             unknown_event_signature_hash => {
-                                bail!("Unknown Safe event signature hash: {unknown_event_signature_hash}")
+                                bail!("Unknown IP-token event signature hash: {unknown_event_signature_hash}")
                             }
                         }
                     }
@@ -1033,81 +2691,68 @@ impl App {
             )
             .await?;
 
-        let res = GetOwnersResponse {
-            current_owners: new_multisig_state.current_owners.clone(),
-            former_owners: new_multisig_state.former_owners.clone(),
-        };
-
-        // Remember multisig data for subsequent requests.
-        multisig_state_vacant_entry.insert(Some(new_multisig_state));
-
-        Ok(res)
-    }
-
-    fn create_did_phk(&self, address: Address) -> eyre::Result<DidPhk> {
-        DidPhk::new_from_chain_id(self.config.chain_id, address)
+        Ok(events)
     }
 
-    fn create_did_pkh_accounts(
+    #[tracing::instrument(level = "debug", skip_all, fields(address = %address, to_block = %to_block))]
+    async fn resolve_multisig(
         &self,
-        current_owners: HashSet<Address>,
-        revoke_access_accounts: HashSet<Address>,
-    ) -> eyre::Result<CreateAccountsResponse> {
-        let mut current_owners_did_pkhs = Vec::with_capacity(current_owners.len());
-        for current_owner in current_owners {
-            let account = self.create_did_phk(current_owner)?;
-            current_owners_did_pkhs.push(account);
-        }
-
-        let mut revoke_access_accounts_did_pkh = Vec::with_capacity(revoke_access_accounts.len());
-        for holder in revoke_access_accounts {
-            let account = self.create_did_phk(holder)?;
-            revoke_access_accounts_did_pkh.push(account);
-        }
+        address: Address,
+        to_block: u64,
+    ) -> eyre::Result<Option<MultisigState>> {
+        let maybe_multisig_owners = self
+            .multisig_registry
+            .get_multisig_owners(address, to_block)
+            .await?;
 
-        Ok(CreateAccountsResponse {
-            current_owners_did_pkhs,
-            revoke_access_accounts_did_pkh,
-        })
+        // NOTE: `None` is remembered as well: it's not a multisig account
+        Ok(maybe_multisig_owners.map(multisig_state_from_owners))
     }
 
-    async fn get_accounts_by_ocl_project(
+    /// Same as [`ChainSource::resolve_multisig`] for several addresses, in the
+    /// same order
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(addresses_count = addresses.len(), to_block = %to_block)
+    )]
+    async fn resolve_multisigs(
         &self,
-        on_chain_ocl_ownership: &OclOwnershipProjection,
-        multisig: &mut HashMap<Address, Option<MultisigState>>,
+        addresses: Vec<Address>,
         to_block: u64,
-    ) -> eyre::Result<GetAccountsByOclProjectResponse> {
-        let mut current_owners = HashSet::new();
-        let mut revoke_access_accounts = HashSet::new();
-
-        // TODO: PERF: self.get_owners() in parallel for all possible multisig?
-        if let Some(current_owner) = &on_chain_ocl_ownership.current {
-            let GetOwnersResponse {
-                current_owners: new_owners,
-                former_owners,
-            } = self.get_owners(*current_owner, multisig, to_block).await?;
-            current_owners.extend(new_owners);
-            revoke_access_accounts.extend(former_owners);
-        }
-
-        for previous in &on_chain_ocl_ownership.previous {
-            let GetOwnersResponse {
-                current_owners: former_owners_1,
-                former_owners: former_owners_2,
-            } = self.get_owners(*previous, multisig, to_block).await?;
-            revoke_access_accounts.extend(former_owners_1);
-            revoke_access_accounts.extend(former_owners_2);
-        }
-
-        account_access_sanity_checks(&current_owners, &mut revoke_access_accounts);
-
-        Ok(GetAccountsByOclProjectResponse {
-            current_owners,
-            revoke_access_accounts,
-        })
+    ) -> eyre::Result<Vec<(Address, Option<MultisigState>)>> {
+        let mut multisigs_owners = self
+            .multisig_registry
+            .get_multisigs_owners(addresses.clone(), to_block)
+            .await?;
+
+        Ok(addresses
+            .into_iter()
+            .map(|address| {
+                let maybe_multisig_owners = multisigs_owners.remove(&address).flatten();
+                (
+                    address,
+                    maybe_multisig_owners.map(multisig_state_from_owners),
+                )
+            })
+            .collect())
     }
 }
 
+#[derive(Debug)]
+enum MainLoopWakeup {
+    Iteration,
+    AdminCommand(AdminCommand),
+    ReloadedConfig(Box<ReloadedConfig>),
+    ProjectsLoadingRequested,
+}
+
+#[derive(Debug)]
+struct IndexingHeadResponse {
+    head_block_number: u64,
+    finalized_block_number: u64,
+}
+
 #[derive(Debug)]
 struct IndexingResponse {
     on_chain_ocl_changes_map: HashMap<OclId, OclChange>,
@@ -1115,64 +2760,77 @@ struct IndexingResponse {
 
 #[derive(Debug, Default)]
 struct OclChange {
-    owner_changes: Option<OclOwnershipChange>,
+    /// Per chain ID
+    owner_changes: HashMap<u64, OclOwnershipChange>,
     changed_files: Vec<ChangedVersionedFile>,
+    /// Per chain ID
+    holder_changes: HashMap<u64, OclHolderChanges>,
+    /// Per chain ID, owners that exist only in orphaned blocks
+    dropped_owners: HashMap<u64, Address>,
+    /// On-chain events that caused the changes
+    on_chain_triggers: Vec<OnChainTrigger>,
 }
 
 impl OclChange {
-    fn new(owner_changes: OclOwnershipChange) -> Self {
+    fn new(chain_id: u64, owner_changes: OclOwnershipChange) -> Self {
         Self {
-            owner_changes: Some(owner_changes),
+            owner_changes: HashMap::from([(chain_id, owner_changes)]),
             changed_files: Vec::new(),
+            holder_changes: HashMap::new(),
+            dropped_owners: HashMap::new(),
+            on_chain_triggers: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Default)]
-struct IndexMultisigSafesResponse {
-    changed_ocl_multisig_owners:
-        HashMap<OclId, /* updated multisig (changed owners) */ Address>,
+struct DesiredDatasetRolesResponse {
+    desired_roles: DatasetAccountRolesMap,
+    accounts: Vec<DidPhk>,
 }
 
-#[derive(Debug)]
-struct ChangedVersionedFile {
-    dataset_id: DatasetID,
-    change: DataRoomFileChange,
+#[derive(Debug, Default)]
+struct DatasetVisibilityChanges {
+    public_dataset_ids: Vec<DatasetID>,
+    private_dataset_ids: Vec<DatasetID>,
 }
 
-type ChangedVersionedFilePerProjectMap = HashMap<OclId, Vec<ChangedVersionedFile>>;
-
-#[derive(Debug)]
-enum DataRoomFileChange {
-    Added(MoleculeAccessLevel),
-    Removed,
-    MoleculeAccessLevelChanged {
-        #[expect(dead_code)]
-        from: MoleculeAccessLevel,
-        to: MoleculeAccessLevel,
-    },
+// Helper methods
+fn multisig_state_from_owners(multisig_owners: MultisigOwners) -> MultisigState {
+    let MultisigOwners {
+        current_owners,
+        former_owners,
+        threshold,
+    } = multisig_owners;
+
+    MultisigState {
+        current_owners,
+        former_owners,
+        threshold,
+    }
 }
 
-#[derive(Debug, Default)]
-struct GetOwnersResponse {
-    current_owners: HashSet<Address>,
-    former_owners: HashSet<Address>,
-}
+/// Returns the number of the first changes (at least one) that touch
+/// different datasets, up to `max_count`
+fn count_independent_access_changes(
+    pending_access_changes: &VecDeque<PendingAccessChange>,
+    max_count: usize,
+) -> usize {
+    let mut dataset_ids = HashSet::new();
+    let mut count = 0;
+
+    for pending_access_change in pending_access_changes.iter().take(max_count) {
+        let change_dataset_ids = pending_access_change.dataset_ids().collect::<HashSet<_>>();
+        if count > 0 && !dataset_ids.is_disjoint(&change_dataset_ids) {
+            break;
+        }
 
-struct CreateAccountsResponse {
-    current_owners_did_pkhs: Vec<DidPhk>,
-    revoke_access_accounts_did_pkh: Vec<DidPhk>,
-}
+        dataset_ids.extend(change_dataset_ids);
+        count += 1;
+    }
 
-#[derive(Debug, Default)]
-struct ProjectDatasetIds<'a> {
-    core_file_dataset_ids: Vec<&'a DatasetID>,
-    owner_file_dataset_ids: Vec<&'a DatasetID>,
-    holder_file_dataset_ids: Vec<&'a DatasetID>,
-    removed_file_dataset_ids: Vec<&'a DatasetID>,
+    count
 }
 
-// Helper methods
 fn build_added_file_entries_with_molecule_access_level_map(
     added_entities: ChangedVersionedFiles,
     molecule_access_levels_map: &MoleculeAccessLevelEntryMap,
@@ -1193,71 +2851,42 @@ fn build_added_file_entries_with_molecule_access_level_map(
                 dataset_id,
                 VersionedFileEntryWithMoleculeAccessLevel {
                     entry: file_entry,
-                    molecule_access_level: *access,
+                    molecule_access_level: access.clone(),
                 },
             ))
         })
         .collect()
 }
 
-struct GetAccountsByOclProjectResponse {
-    current_owners: HashSet<Address>,
-    revoke_access_accounts: HashSet<Address>,
-}
-
-fn prepare_changes_based_on_changed_versioned_files_entries(
-    project_entry: &MoleculeProjectEntry,
-    versioned_files_entries: &VersionedFilesEntries,
-    molecule_access_levels_map: &MoleculeAccessLevelEntryMap,
-) -> Vec<ChangedVersionedFile> {
-    let mut changes = Vec::with_capacity(
-        versioned_files_entries.added_entities.len()
-            + versioned_files_entries.removed_entities.len(),
-    );
-
-    for (added_dataset_id, versioned_file_entry) in &versioned_files_entries.added_entities {
-        let Some(molecule_access_levels) =
-            molecule_access_levels_map.get(added_dataset_id).copied()
-        else {
-            tracing::warn!(
-                "Skip '{}' adding file ({added_dataset_id}) because molecule_access_level is missing for it",
-                versioned_file_entry.path,
-            );
-            continue;
-        };
-
-        // NOTE: If the project is deleted, consider all files deleted as well.
-        let change = if project_entry.is_deleted() {
-            DataRoomFileChange::Removed
-        } else {
-            DataRoomFileChange::Added(molecule_access_levels)
-        };
-
-        changes.push(ChangedVersionedFile {
-            dataset_id: added_dataset_id.clone(),
-            change,
-        });
-    }
-    for removed_dataset_id in versioned_files_entries.removed_entities.keys() {
-        changes.push(ChangedVersionedFile {
-            dataset_id: removed_dataset_id.clone(),
-            change: DataRoomFileChange::Removed,
-        });
-    }
-
-    changes
-}
-
-fn prepare_changes_based_on_changed_molecule_access_levels(
+fn prepare_events_based_on_changed_molecule_access_levels(
     project_entry: &MoleculeProjectEntry,
     project_actual_files_map: &HashMap<DatasetID, VersionedFileEntryWithMoleculeAccessLevel>,
+    maybe_versioned_files_entries: Option<&VersionedFilesEntries>,
     molecule_access_levels_map: &MoleculeAccessLevelEntryMap,
-) -> Vec<ChangedVersionedFile> {
-    let mut changes = Vec::new();
+    is_file_changed: impl Fn(&DatasetID) -> bool,
+) -> Vec<BridgeEvent> {
+    let mut events = Vec::new();
 
     for (dataset_id, versioned_file) in project_actual_files_map {
-        let current_access = versioned_file.molecule_access_level;
-        let Some(new_access) = molecule_access_levels_map.get(dataset_id).copied() else {
+        // NOTE: Access levels of unchanged files are not queried.
+        if !is_file_changed(dataset_id) {
+            continue;
+        }
+
+        // NOTE: Files added or removed in this batch are handled by the data-room changes.
+        if let Some(versioned_files_entries) = maybe_versioned_files_entries
+            && (versioned_files_entries
+                .added_entities
+                .contains_key(dataset_id)
+                || versioned_files_entries
+                    .removed_entities
+                    .contains_key(dataset_id))
+        {
+            continue;
+        }
+
+        let current_access = &versioned_file.molecule_access_level;
+        let Some(new_access) = molecule_access_levels_map.get(dataset_id) else {
             tracing::warn!(
                 "Skip '{}' file ({dataset_id}) because molecule_access_level is missing for it",
                 versioned_file.entry.path,
@@ -1265,153 +2894,161 @@ fn prepare_changes_based_on_changed_molecule_access_levels(
             continue;
         };
 
-        // NOTE: If the project is deleted, consider all files deleted as well.
-        if project_entry.is_deleted() {
-            changes.push(ChangedVersionedFile {
-                dataset_id: dataset_id.clone(),
-                change: DataRoomFileChange::Removed,
-            });
-        } else if current_access != new_access {
-            changes.push(ChangedVersionedFile {
+        if current_access != new_access {
+            events.push(BridgeEvent::MoleculeAccessLevelChanged {
+                ocl_id: project_entry.ocl_id,
                 dataset_id: dataset_id.clone(),
-                change: DataRoomFileChange::MoleculeAccessLevelChanged {
-                    from: current_access,
-                    to: new_access,
-                },
+                from: current_access.clone(),
+                to: new_access.clone(),
             });
         }
     }
 
-    changes
+    events
 }
 
-fn account_access_sanity_checks(
-    current_owners: &HashSet<Address>,
-    revoke_access_accounts: &mut HashSet<Address>,
-) {
-    for owner in current_owners {
-        revoke_access_accounts.remove(owner);
+/// Returns OCLs that are (or were) owned by the address, directly or through
+/// token-bound accounts of other such OCLs
+fn find_ocls_owned_by(
+    ocl_ownership_projection_map: &OclOwnershipProjectionMap,
+    address: Address,
+) -> Vec<OclId> {
+    let mut owned_ocl_ids = HashSet::new();
+    let mut owners_to_visit = vec![address];
+
+    while let Some(owner) = owners_to_visit.pop() {
+        for (ocl_id, ownership_projection) in ocl_ownership_projection_map.iter() {
+            if ocl_owner_addresses(ownership_projection).any(|owner_address| owner_address == owner)
+                && owned_ocl_ids.insert(*ocl_id)
+            {
+                owners_to_visit.extend(ownership_projection.identity_account);
+            }
+        }
     }
+
+    owned_ocl_ids.into_iter().collect()
 }
 
-fn partition_dataset_id_by_molecule_access_level<'a>(
-    dataset_id: &'a DatasetID,
-    molecule_access_level: MoleculeAccessLevel,
-    owner_file_dataset_ids: &mut Vec<&'a DatasetID>,
-    holder_file_dataset_ids: &mut Vec<&'a DatasetID>,
-) {
-    use MoleculeAccessLevel as Access;
+/// Returns on-chain events per OCL whose access they affect: directly, or
+/// through multisigs and token-bound accounts that own the OCL
+fn build_on_chain_triggers_map(
+    chain_id: u64,
+    on_chain_events: &[BridgeEvent],
+    ocl_ownership_projection_map: &OclOwnershipProjectionMap,
+) -> HashMap<OclId, Vec<OnChainTrigger>> {
+    let mut on_chain_triggers_map = HashMap::<OclId, Vec<OnChainTrigger>>::new();
+
+    for event in on_chain_events {
+        let Some((block_number, _)) = event.block_position() else {
+            continue;
+        };
+        let trigger = OnChainTrigger {
+            chain_id,
+            block_number,
+            tx_hash: event.tx_hash(),
+        };
 
-    match molecule_access_level {
-        Access::Public | Access::Holder => {
-            holder_file_dataset_ids.push(dataset_id);
-        }
-        Access::Admin | Access::Admin2 => {
-            owner_file_dataset_ids.push(dataset_id);
+        let affected_ocl_ids = match event {
+            BridgeEvent::OclTransferred { ocl_id, .. }
+            | BridgeEvent::OclIdentityCreated { ocl_id, .. } => {
+                let mut ocl_ids = vec![*ocl_id];
+                // NOTE: OCLs owned by the token-bound account change their owners as well.
+                if let Some(identity_account) = ocl_ownership_projection_map
+                    .get(ocl_id)
+                    .and_then(|ownership_projection| ownership_projection.identity_account)
+                {
+                    ocl_ids.extend(find_ocls_owned_by(
+                        ocl_ownership_projection_map,
+                        identity_account,
+                    ));
+                }
+                ocl_ids
+            }
+            BridgeEvent::HolderTokenTransferred { ocl_id, .. } => vec![*ocl_id],
+            BridgeEvent::MultisigOwnerAdded { multisig, .. }
+            | BridgeEvent::MultisigOwnerRemoved { multisig, .. }
+            | BridgeEvent::MultisigThresholdChanged { multisig, .. } => {
+                find_ocls_owned_by(ocl_ownership_projection_map, *multisig)
+            }
+            _ => Vec::new(),
+        };
+
+        for ocl_id in affected_ocl_ids {
+            on_chain_triggers_map
+                .entry(ocl_id)
+                .or_default()
+                .push(trigger.clone());
         }
     }
-}
 
-fn get_project_dataset_ids(
-    off_chain_project: &OffChainMoleculeProjectProjection,
-) -> ProjectDatasetIds<'_> {
-    let mut owner_file_dataset_ids = Vec::new();
-    let mut holder_file_dataset_ids = Vec::new();
-
-    for (dataset_id, entry_with_access_level) in &off_chain_project.actual_files_map {
-        partition_dataset_id_by_molecule_access_level(
-            dataset_id,
-            entry_with_access_level.molecule_access_level,
-            &mut owner_file_dataset_ids,
-            &mut holder_file_dataset_ids,
-        );
+    for on_chain_triggers in on_chain_triggers_map.values_mut() {
+        on_chain_triggers.sort();
+        on_chain_triggers.dedup();
     }
 
-    let mut removed_file_dataset_ids = Vec::new();
-    removed_file_dataset_ids.extend(off_chain_project.removed_files_map.keys());
+    on_chain_triggers_map
+}
 
-    ProjectDatasetIds {
-        core_file_dataset_ids: vec![
-            &off_chain_project.entry.data_room_dataset_id,
-            &off_chain_project.entry.announcements_dataset_id,
-        ],
-        owner_file_dataset_ids,
-        holder_file_dataset_ids,
-        removed_file_dataset_ids,
+/// Replaces token-bound accounts with the owner addresses behind them
+/// (TBA -> OCL -> owner), as only the latter can be multisigs or wallets
+fn resolve_token_bound_accounts(
+    addresses: HashSet<Address>,
+    ocl_ownership_projection_map: &OclOwnershipProjectionMap,
+) -> HashSet<Address> {
+    let mut resolved_addresses = HashSet::with_capacity(addresses.len());
+    let mut visited_addresses = addresses.clone();
+    let mut addresses_to_visit = addresses.into_iter().collect::<Vec<_>>();
+
+    while let Some(address) = addresses_to_visit.pop() {
+        let Some((_, ownership_projection)) =
+            ocl_ownership_projection_map.find_by_identity_account(address)
+        else {
+            resolved_addresses.insert(address);
+            continue;
+        };
+
+        for owner in ocl_owner_addresses(ownership_projection) {
+            if visited_addresses.insert(owner) {
+                addresses_to_visit.push(owner);
+            }
+        }
     }
+
+    resolved_addresses
 }
 
 fn build_operations(
     ProjectDatasetIds {
-        core_file_dataset_ids,
-        owner_file_dataset_ids,
-        holder_file_dataset_ids,
+        dataset_ids,
         removed_file_dataset_ids,
     }: ProjectDatasetIds,
-    current_owners_did_pkhs: &[DidPhk],
-    revoke_access_accounts_did_pkh: &[DidPhk],
+    accounts: &OclAccounts,
+    accounts_did_pkhs: &HashMap<Address, DidPhk>,
+    access_policy: &AccessPolicy,
 ) -> Vec<AccountDatasetRelationOperation> {
-    let all_accounts_count = current_owners_did_pkhs.len() + revoke_access_accounts_did_pkh.len();
-    let all_datasets_count = core_file_dataset_ids.len()
-        + owner_file_dataset_ids.len()
-        + holder_file_dataset_ids.len()
-        + removed_file_dataset_ids.len();
-
-    let mut operations = Vec::with_capacity(all_accounts_count * all_datasets_count);
-
-    for core_file_dataset_id in core_file_dataset_ids {
-        for owner in current_owners_did_pkhs {
-            operations.push(AccountDatasetRelationOperation::maintainer_access(
-                owner.to_string(),
-                (*core_file_dataset_id).clone(),
-            ));
-        }
-        for revoke_access_account in revoke_access_accounts_did_pkh {
-            operations.push(AccountDatasetRelationOperation::revoke_access(
-                revoke_access_account.to_string(),
-                (*core_file_dataset_id).clone(),
-            ));
-        }
-    }
-    for owner_file_dataset_id in owner_file_dataset_ids {
-        for owner in current_owners_did_pkhs {
-            operations.push(AccountDatasetRelationOperation::maintainer_access(
-                owner.to_string(),
-                (*owner_file_dataset_id).clone(),
-            ));
-        }
-        for revoke_access_account in revoke_access_accounts_did_pkh {
-            operations.push(AccountDatasetRelationOperation::revoke_access(
-                revoke_access_account.to_string(),
-                (*owner_file_dataset_id).clone(),
-            ));
-        }
-    }
-    for holder_file_dataset_id in holder_file_dataset_ids {
-        for owner in current_owners_did_pkhs {
-            operations.push(AccountDatasetRelationOperation::maintainer_access(
-                owner.to_string(),
-                (*holder_file_dataset_id).clone(),
-            ));
-        }
-        for revoke_access_account in revoke_access_accounts_did_pkh {
-            operations.push(AccountDatasetRelationOperation::revoke_access(
-                revoke_access_account.to_string(),
-                (*holder_file_dataset_id).clone(),
-            ));
+    let all_datasets_count = dataset_ids.len() + removed_file_dataset_ids.len();
+
+    let mut operations = Vec::with_capacity(accounts_did_pkhs.len() * all_datasets_count);
+
+    for (dataset_id, dataset_kind) in dataset_ids {
+        for (address, did_pkh) in accounts_did_pkhs {
+            let Some(operation) = accounts.role_operation(*address, &dataset_kind, access_policy)
+            else {
+                continue;
+            };
+
+            operations.push(AccountDatasetRelationOperation {
+                account_id: did_pkh.to_string(),
+                operation,
+                dataset_id: dataset_id.clone(),
+            });
         }
     }
     for removed_file_dataset_id in removed_file_dataset_ids {
-        for owner in current_owners_did_pkhs {
-            operations.push(AccountDatasetRelationOperation::revoke_access(
-                owner.to_string(),
-                (*removed_file_dataset_id).clone(),
-            ));
-        }
-        for revoke_access_account in revoke_access_accounts_did_pkh {
+        for did_pkh in accounts_did_pkhs.values() {
             operations.push(AccountDatasetRelationOperation::revoke_access(
-                revoke_access_account.to_string(),
-                (*removed_file_dataset_id).clone(),
+                did_pkh.to_string(),
+                removed_file_dataset_id.clone(),
             ));
         }
     }
@@ -1419,18 +3056,39 @@ fn build_operations(
     operations
 }
 
-fn parse_safe_added_owner_event(log: &Log) -> eyre::Result<Address> {
-    // NOTE: We can use the actual event signature hashes because
-    //       the indexed mark doesn't participate in hash calculation.
-
-    // First, try to parse the actual event signature (indexed "owner" field), ...
-    let added_owner = if let Ok(event) = Safe::AddedOwner::decode_log(log) {
-        event.owner
-    } else {
-        // Try to parse an old version event (w/o indexed mark) -- may be relevant for older Safe Wallet versions
-        let event = safe::v1_3_0::Safe::AddedOwner::decode_log(log)?;
-        event.owner
+/// Files are made public when their access level is public according to the
+/// access policy. A file is only made private when it stops being public,
+/// so visibility of other files is left as is.
+fn get_dataset_visibility_changes(
+    changed_files: &[ChangedVersionedFile],
+    access_policy: &AccessPolicy,
+) -> DatasetVisibilityChanges {
+    let is_public = |molecule_access_level: &MoleculeAccessLevel| {
+        access_policy.is_public(&DatasetKind::File(molecule_access_level.clone()))
     };
 
-    Ok(added_owner)
+    let mut visibility_changes = DatasetVisibilityChanges::default();
+
+    for changed_file in changed_files {
+        match &changed_file.change {
+            DataRoomFileChange::Added(to)
+            | DataRoomFileChange::MoleculeAccessLevelChanged { to, .. }
+                if is_public(to) =>
+            {
+                visibility_changes
+                    .public_dataset_ids
+                    .push(changed_file.dataset_id.clone());
+            }
+            DataRoomFileChange::MoleculeAccessLevelChanged { from, .. } if is_public(from) => {
+                visibility_changes
+                    .private_dataset_ids
+                    .push(changed_file.dataset_id.clone());
+            }
+            DataRoomFileChange::Added(_)
+            | DataRoomFileChange::Removed
+            | DataRoomFileChange::MoleculeAccessLevelChanged { .. } => {}
+        }
+    }
+
+    visibility_changes
 }
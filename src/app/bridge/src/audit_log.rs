@@ -0,0 +1,302 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+use alloy::primitives::B256;
+use chrono::{DateTime, Utc};
+use kamu_node_api_client::{AccountDatasetRelationOperation, DatasetID};
+use molecule_ocl::entities::OclId;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt as _;
+use tokio::sync::{Mutex, RwLock};
+
+/// Access change applied (or attempted) by the bridge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessAuditRecord {
+    /// Monotonically increasing ID of the record
+    pub id: u64,
+    pub recorded_at: DateTime<Utc>,
+    /// Human-readable description of the change
+    pub reason: String,
+    pub cause: AccessChangeCause,
+    pub operations: Vec<AccountDatasetRelationOperation>,
+    pub result: AccessChangeResult,
+}
+
+/// Access change to be recorded: the ID is assigned by the log
+#[derive(Debug, Clone)]
+pub struct NewAccessAuditRecord {
+    pub reason: String,
+    pub cause: AccessChangeCause,
+    pub operations: Vec<AccountDatasetRelationOperation>,
+    pub result: AccessChangeResult,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AccessChangeCause {
+    /// Changes of the OCL observed during an update iteration
+    OclChanged {
+        ocl_id: OclId,
+        /// Transfers, multisig owner changes and IP-token transfers. Empty if
+        /// the access is revoked because of a reorg.
+        on_chain: Vec<OnChainTrigger>,
+        /// Changes of the project files
+        off_chain: Option<OffChainTrigger>,
+    },
+    /// Access of all owners and holders of the OCL is applied from scratch
+    /// (catch-up phase or refresh via the admin API)
+    OclFullUpdate { ocl_id: OclId },
+    /// Actual roles are brought in line with the indexed state
+    Reconciliation,
+    /// Access granted for the OCL is revoked, because it is not allowed by
+    /// the OCL id filter anymore
+    OclIgnored { ocl_id: OclId },
+    /// Visibility of the project datasets is changed according to the
+    /// reloaded access policy
+    AccessPolicyChanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OnChainTrigger {
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub tx_hash: Option<B256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffChainTrigger {
+    pub data_room_dataset_id: DatasetID,
+    /// Offset of the data room up to which the changes were loaded
+    pub data_room_offset: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum AccessChangeResult {
+    /// Operations were applied, with the Kamu Node response message
+    Applied {
+        message: String,
+    },
+    Failed {
+        error: String,
+    },
+    /// Operations were not applied: the bridge runs in dry-run mode
+    DryRun,
+}
+
+/// Log of access changes kept for audit. Only the latest records are
+/// retained.
+#[async_trait::async_trait]
+pub trait AccessAuditLog: Send + Sync {
+    /// Assigns the next ID to the record and appends it to the log
+    async fn append(&self, record: NewAccessAuditRecord) -> eyre::Result<AccessAuditRecord>;
+
+    /// Returns up to `limit` retained records starting from the `from_id` ID
+    async fn read(&self, from_id: u64, limit: usize) -> eyre::Result<Vec<AccessAuditRecord>>;
+
+    /// ID that the next appended record will get
+    async fn next_id(&self) -> eyre::Result<u64>;
+}
+
+pub struct InMemoryAccessAuditLog {
+    max_records: usize,
+    records: RwLock<AccessAuditRecords>,
+}
+
+#[derive(Default)]
+struct AccessAuditRecords {
+    /// Ordered by ID
+    records: VecDeque<AccessAuditRecord>,
+    next_id: u64,
+}
+
+impl InMemoryAccessAuditLog {
+    pub fn new(max_records: usize) -> Self {
+        Self::restore(max_records, Vec::new())
+    }
+
+    /// Continues the log after the given records (ordered by ID)
+    fn restore(max_records: usize, records: Vec<AccessAuditRecord>) -> Self {
+        let next_id = records.last().map_or(0, |record| record.id + 1);
+
+        let mut records = VecDeque::from(records);
+        while records.len() > max_records {
+            records.pop_front();
+        }
+
+        Self {
+            max_records,
+            records: RwLock::new(AccessAuditRecords { records, next_id }),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AccessAuditLog for InMemoryAccessAuditLog {
+    async fn append(&self, record: NewAccessAuditRecord) -> eyre::Result<AccessAuditRecord> {
+        let mut writable_records = self.records.write().await;
+
+        let NewAccessAuditRecord {
+            reason,
+            cause,
+            operations,
+            result,
+        } = record;
+        let record = AccessAuditRecord {
+            id: writable_records.next_id,
+            recorded_at: Utc::now(),
+            reason,
+            cause,
+            operations,
+            result,
+        };
+
+        writable_records.next_id += 1;
+        writable_records.records.push_back(record.clone());
+        while writable_records.records.len() > self.max_records {
+            writable_records.records.pop_front();
+        }
+
+        Ok(record)
+    }
+
+    async fn read(&self, from_id: u64, limit: usize) -> eyre::Result<Vec<AccessAuditRecord>> {
+        let readable_records = self.records.read().await;
+
+        let from = readable_records
+            .records
+            .partition_point(|record| record.id < from_id);
+
+        Ok(readable_records
+            .records
+            .range(from..)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn next_id(&self) -> eyre::Result<u64> {
+        Ok(self.records.read().await.next_id)
+    }
+}
+
+/// Keeps the log in a local file as well, so that it survives restarts.
+///
+/// File layout: one JSON-serialized record per line (NDJSON). Records are
+/// appended, and the file is rewritten with the retained records only once it
+/// holds twice as many.
+pub struct FileAccessAuditLog {
+    path: PathBuf,
+    max_records: usize,
+    in_memory: InMemoryAccessAuditLog,
+    /// Number of records in the file. The lock also keeps the order of
+    /// records in the file the same as their IDs.
+    file_records_count: Mutex<usize>,
+}
+
+impl FileAccessAuditLog {
+    #[tracing::instrument(level = "info", skip_all, fields(path = %path.display()))]
+    pub async fn open(path: PathBuf, max_records: usize) -> eyre::Result<Self> {
+        let records = match tokio::fs::read(&path).await {
+            Ok(content) => parse_records(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::info!("Audit log file not found");
+                Vec::new()
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let file_records_count = records.len();
+
+        tracing::info!(records_count = file_records_count, "Audit log is loaded");
+
+        let audit_log = Self {
+            path,
+            max_records,
+            in_memory: InMemoryAccessAuditLog::restore(max_records, records),
+            file_records_count: Mutex::new(file_records_count),
+        };
+
+        if file_records_count > max_records {
+            let mut file_records_count = audit_log.file_records_count.lock().await;
+            audit_log.compact(&mut file_records_count).await?;
+        }
+
+        Ok(audit_log)
+    }
+
+    async fn compact(&self, file_records_count: &mut usize) -> eyre::Result<()> {
+        let records = self.in_memory.read(0, self.max_records).await?;
+        let content = records_to_ndjson(&records)?;
+
+        // NOTE: Write to a temporary file first and then rename it, so that a crash
+        //       in the middle of writing doesn't leave a truncated log behind.
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, content).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+
+        *file_records_count = records.len();
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AccessAuditLog for FileAccessAuditLog {
+    async fn append(&self, record: NewAccessAuditRecord) -> eyre::Result<AccessAuditRecord> {
+        let mut file_records_count = self.file_records_count.lock().await;
+
+        let record = self.in_memory.append(record).await?;
+
+        let line = records_to_ndjson(std::slice::from_ref(&record))?;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+
+        *file_records_count += 1;
+
+        if *file_records_count >= 2 * self.max_records {
+            self.compact(&mut file_records_count).await?;
+        }
+
+        Ok(record)
+    }
+
+    async fn read(&self, from_id: u64, limit: usize) -> eyre::Result<Vec<AccessAuditRecord>> {
+        self.in_memory.read(from_id, limit).await
+    }
+
+    async fn next_id(&self) -> eyre::Result<u64> {
+        self.in_memory.next_id().await
+    }
+}
+
+/// Serializes records as NDJSON: one JSON object per line
+pub fn records_to_ndjson<T: Serialize>(records: &[T]) -> eyre::Result<String> {
+    let mut ndjson = String::new();
+    for record in records {
+        ndjson.push_str(&serde_json::to_string(record)?);
+        ndjson.push('\n');
+    }
+
+    Ok(ndjson)
+}
+
+fn parse_records(content: &[u8]) -> Vec<AccessAuditRecord> {
+    content
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| match serde_json::from_slice(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                // NOTE: E.g. the last line was not written completely because of a crash.
+                tracing::warn!(error = ?e, error_msg = %e, "Skip audit log record: cannot parse");
+                None
+            }
+        })
+        .collect()
+}
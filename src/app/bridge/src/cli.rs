@@ -25,6 +25,9 @@ pub struct Cli {
 pub enum Command {
     Run(RunArgs),
     State(StateArgs),
+    /// Compares dataset roles in Kamu Node with the indexed state and fixes
+    /// the differences
+    Reconcile(ReconcileArgs),
 }
 
 #[derive(Debug, clap::Args)]
@@ -36,3 +39,10 @@ pub struct RunArgs {
 
 #[derive(Debug, clap::Args)]
 pub struct StateArgs {}
+
+#[derive(Debug, clap::Args)]
+pub struct ReconcileArgs {
+    /// Only print the operations without applying them.
+    #[clap(long)]
+    pub dry_run: bool,
+}
@@ -1,4 +1,17 @@
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+
+use alloy::eips::BlockNumberOrTag;
 use alloy::primitives::Address;
+use eyre::WrapErr as _;
+use kamu_node_api_client::OclIdFilter;
+use molecule_ocl::entities::OclId;
+use serde::Deserialize;
+
+use crate::access_policy::{
+    AccessPolicies, AccessPolicy, MultisigAccessPolicies, MultisigAccessPolicy,
+};
+use crate::ocl_id_filter_file::OclIdFilterFile;
 
 #[derive(confique::Config, Debug)]
 pub struct Config {
@@ -15,7 +28,7 @@ pub struct Config {
     #[config(env = "KAMU_MOLECULE_BRIDGE_KAMU_NODE_GQL_API_ENDPOINT")]
     pub kamu_node_gql_api_endpoint: String,
     #[config(env = "KAMU_MOLECULE_BRIDGE_KAMU_NODE_TOKEN")]
-    pub kamu_node_token: String,
+    pub kamu_node_token: Secret,
 
     #[config(env = "KAMU_MOLECULE_BRIDGE_MOLECULE_PROJECTS_DATASET_ALIAS")]
     pub molecule_projects_dataset_alias: String,
@@ -23,28 +36,461 @@ pub struct Config {
     #[config(env = "KAMU_MOLECULE_BRIDGE_MOLECULE_PROJECTS_LOADING_INTERVAL_IN_SECS")]
     pub molecule_projects_loading_interval_in_secs: u64,
 
+    /// Chains with LabNFT deployments to index. Can only be set in the config
+    /// file. If not set, a single chain is configured by the `chain_id`,
+    /// `rpc_url`, `labnft_*`, `indexing_*` and `ocl_holder_tokens` settings.
+    pub chains: Option<Vec<ChainConfig>>,
+
     /// ID of the chain that RCP URL is expected to point to
     #[config(env = "KAMU_MOLECULE_BRIDGE_CHAIN_ID")]
-    pub chain_id: u64,
+    pub chain_id: Option<u64>,
 
     #[config(env = "KAMU_MOLECULE_BRIDGE_RPC_URL")]
-    pub rpc_url: String,
+    pub rpc_url: Option<String>,
 
     #[config(env = "KAMU_MOLECULE_BRIDGE_LABNFT_CONTRACT_ADDRESS")]
-    pub labnft_contract_address: Address,
+    pub labnft_contract_address: Option<Address>,
     #[config(env = "KAMU_MOLECULE_BRIDGE_LABNFT_CONTRACT_BIRTH_BLOCK")]
-    pub labnft_contract_birth_block: u64,
+    pub labnft_contract_birth_block: Option<u64>,
 
     #[config(env = "KAMU_MOLECULE_BRIDGE_INDEXING_DELAY_BETWEEN_ITERATIONS_IN_SECS")]
     pub indexing_delay_between_iterations_in_secs: u64,
 
+    /// Block up to which the chain is indexed: `finalized`, `safe` or `latest`.
+    /// Non-finalized heads are indexed with reorg detection and rollback.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_INDEXING_HEAD")]
+    #[config(default = "finalized")]
+    pub indexing_head: IndexingHead,
+
+    /// Number of blocks behind the `safe`/`latest` head that are not indexed yet.
+    /// Ignored for the `finalized` head.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_INDEXING_CONFIRMATION_DEPTH")]
+    #[config(default = 0)]
+    pub indexing_confirmation_depth: u64,
+
+    /// How owners of Safe multisigs are resolved:
+    /// - `logs`: owner change logs are replayed from the creation of a Safe
+    ///   (the Safe Transaction Service API is used for Safes before v1.3.0);
+    /// - `contract_call`: `getOwners()` is called on the Safe as of the indexed
+    ///   block (batched via Multicall3);
+    /// - `api`: current owners are fetched from the Safe Transaction Service
+    ///   API.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_SAFE_OWNERS_RESOLVER")]
+    #[config(default = "logs")]
+    pub safe_owners_resolver: SafeOwnersResolver,
+
+    /// Path to the file where the indexed state is persisted between restarts.
+    /// If not set, the state is rebuilt from the LabNFT contract birth block
+    /// on every start.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_STATE_FILE_PATH")]
+    pub state_file_path: Option<std::path::PathBuf>,
+
+    /// Path to the file where the access-change audit log is persisted between
+    /// restarts. If not set, the audit log is only kept in memory.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_AUDIT_LOG_FILE_PATH")]
+    pub audit_log_file_path: Option<std::path::PathBuf>,
+
+    /// Number of the latest access-change audit records that are retained
+    #[config(env = "KAMU_MOLECULE_BRIDGE_AUDIT_LOG_MAX_RECORDS")]
+    #[config(default = 10000)]
+    pub audit_log_max_records: usize,
+
+    /// Number of the latest event log entries that are retained in memory
+    #[config(env = "KAMU_MOLECULE_BRIDGE_EVENT_LOG_MAX_ENTRIES")]
+    #[config(default = 10000)]
+    pub event_log_max_entries: usize,
+
+    /// Path to the file where the whole event log is kept. If set, the state
+    /// is rebuilt by replaying the log when there is no state snapshot to
+    /// resume from. If not set, only the latest entries are kept in memory.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_EVENT_LOG_FILE_PATH")]
+    pub event_log_file_path: Option<std::path::PathBuf>,
+
+    /// Alias of a root dataset in Kamu Node that access-change audit records
+    /// are pushed to. If not set, the records are not pushed.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_AUDIT_LOG_DATASET_ALIAS")]
+    pub audit_log_dataset_alias: Option<String>,
+
+    /// Alias of a root dataset in Kamu Node that applied access changes are
+    /// published to (one row per operation). If not set, the changes are not
+    /// published.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_ACCESS_CHANGES_DATASET_ALIAS")]
+    pub access_changes_dataset_alias: Option<String>,
+
+    /// Maximum number of concurrent jobs: log fetching per contract kind,
+    /// multisig resolution per address and access applying per project
+    #[config(env = "KAMU_MOLECULE_BRIDGE_CONCURRENCY_LIMIT")]
+    #[config(default = 8)]
+    pub concurrency_limit: NonZeroUsize,
+
+    /// Maximum number of dataset role operations sent to Kamu Node in a
+    /// single request. Larger batches are split.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_KAMU_NODE_MAX_OPERATIONS_PER_REQUEST")]
+    #[config(default = 1000)]
+    pub kamu_node_max_operations_per_request: NonZeroUsize,
+
+    /// Interval between reconciliations of the actual dataset roles in Kamu
+    /// Node with the indexed state. If not set, reconciliation is only
+    /// performed via the `reconcile` command.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_RECONCILIATION_INTERVAL_IN_SECS")]
+    pub reconciliation_interval_in_secs: Option<u64>,
+
+    /// IP-tokens (ERC-20) per OCL id. Holders of a token get `Reader` access to
+    /// `Holder` and `Public` files of the project. Can only be set in the
+    /// config file.
+    pub ocl_holder_tokens: Option<HashMap<OclId, Address>>,
+
+    /// Roles of owners, multisig signers, holders and everyone for the project
+    /// datasets per dataset kind and `molecule_access_level`. Can only be set
+    /// in the config file. If not set, the default roles are used (see
+    /// [`AccessPolicy::default()`]).
+    pub access_policy: Option<AccessPolicy>,
+
+    /// Roles of multisig signers (and of the multisig itself) for the files of
+    /// projects owned by a multisig. Can only be set in the config file.
+    /// If not set, every signer gets `Maintainer`.
+    pub multisig_access_policy: Option<MultisigAccessPolicy>,
+
+    /// Multisig access policies per OCL id, take precedence over the policy
+    /// of the chain. Can only be set in the config file.
+    pub ocl_multisig_access_policies: Option<HashMap<OclId, MultisigAccessPolicy>>,
+
+    /// Token for the admin HTTP API (`Authorization: Bearer <token>`).
+    /// If not set, the admin API is disabled (requests are rejected with 403).
+    #[config(env = "KAMU_MOLECULE_BRIDGE_ADMIN_TOKEN")]
+    pub admin_token: Option<Secret>,
+
+    /// Token of the Kamu Node webhook receiver (`?token=<token>` of the target
+    /// URL), notified about updates of the project datasets. If not set, the
+    /// receiver is disabled.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_KAMU_WEBHOOK_TOKEN")]
+    pub kamu_webhook_token: Option<Secret>,
+
     /// List of OCL ids that should be ignored
     #[config(env = "KAMU_MOLECULE_BRIDGE_IGNORE_OCL_IDS", parse_env = confique::env::parse::list_by_comma)]
-    pub ignore_ocl_ids: Option<std::collections::HashSet<String>>,
+    pub ignore_ocl_ids: Option<HashSet<OclId>>,
+
+    /// List of OCL ids that should only be loaded (e.g. for staged rollouts).
+    /// `ignore_ocl_ids` take precedence. If not set, all projects are loaded.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_ONLY_OCL_IDS", parse_env = confique::env::parse::list_by_comma)]
+    pub only_ocl_ids: Option<HashSet<OclId>>,
+
+    /// Path to a YAML file with `ignore_ocl_ids` and `only_ocl_ids` that is
+    /// reloaded together with the config (see [`crate::config_watcher`]), so
+    /// projects can be toggled without a restart. If set, the lists above are
+    /// not used.
+    #[config(env = "KAMU_MOLECULE_BRIDGE_OCL_ID_FILTER_FILE_PATH")]
+    pub ocl_id_filter_file_path: Option<std::path::PathBuf>,
 }
 
 impl Config {
     pub fn builder() -> confique::Builder<Config> {
         confique::Config::builder()
     }
+
+    /// Returns the configured chains: either the `chains` list or a single
+    /// chain built from the top-level settings
+    pub fn chain_configs(&self) -> eyre::Result<Vec<ChainConfig>> {
+        let Some(chains) = &self.chains else {
+            let (
+                Some(chain_id),
+                Some(rpc_url),
+                Some(labnft_contract_address),
+                Some(labnft_contract_birth_block),
+            ) = (
+                self.chain_id,
+                &self.rpc_url,
+                self.labnft_contract_address,
+                self.labnft_contract_birth_block,
+            )
+            else {
+                eyre::bail!(
+                    "Either `chains` or `chain_id`, `rpc_url`, `labnft_contract_address` and \
+                     `labnft_contract_birth_block` must be configured"
+                );
+            };
+
+            return Ok(vec![ChainConfig {
+                chain_id,
+                rpc_url: rpc_url.clone(),
+                labnft_contract_address,
+                labnft_contract_birth_block,
+                indexing_head: self.indexing_head,
+                indexing_confirmation_depth: self.indexing_confirmation_depth,
+                ocl_holder_tokens: self.ocl_holder_tokens.clone().unwrap_or_default(),
+                multisig_access_policy: self.multisig_access_policy.clone().unwrap_or_default(),
+            }]);
+        };
+
+        if chains.is_empty() {
+            eyre::bail!("At least one chain must be configured in `chains`");
+        }
+
+        let mut chain_ids = HashSet::new();
+        for chain in chains {
+            if !chain_ids.insert(chain.chain_id) {
+                eyre::bail!("Chain ID '{}' is configured more than once", chain.chain_id);
+            }
+        }
+
+        Ok(chains.clone())
+    }
+
+    /// Returns the validated access policies of the configured chains
+    pub fn access_policies<'a>(
+        &self,
+        chain_configs: impl IntoIterator<Item = &'a ChainConfig>,
+    ) -> eyre::Result<AccessPolicies> {
+        let access = self.access_policy.clone().unwrap_or_default();
+        access.validate()?;
+
+        let multisig = MultisigAccessPolicies {
+            per_chain: chain_configs
+                .into_iter()
+                .map(|chain_config| {
+                    (
+                        chain_config.chain_id,
+                        chain_config.multisig_access_policy.clone(),
+                    )
+                })
+                .collect(),
+            per_ocl: self
+                .ocl_multisig_access_policies
+                .clone()
+                .unwrap_or_default(),
+        };
+
+        Ok(AccessPolicies { access, multisig })
+    }
+
+    /// Returns the filter of the projects to load built from `ignore_ocl_ids`
+    /// and `only_ocl_ids`
+    pub fn ocl_id_filter(&self) -> OclIdFilter {
+        OclIdFilter {
+            ignore: self.ignore_ocl_ids.clone().unwrap_or_default(),
+            only: self.only_ocl_ids.clone(),
+        }
+    }
+
+    /// Returns the filter of the projects to load: the one from
+    /// `ocl_id_filter_file_path` if it is set, otherwise [`Self::ocl_id_filter`]
+    pub fn load_ocl_id_filter(&self) -> eyre::Result<OclIdFilter> {
+        let Some(file_path) = &self.ocl_id_filter_file_path else {
+            return Ok(self.ocl_id_filter());
+        };
+
+        OclIdFilterFile::load(file_path)
+            .wrap_err_with(|| format!("Failed to load OCL id filter from {}", file_path.display()))
+    }
+
+    /// Takes the parameters that are safe to change without a restart from
+    /// the reloaded config: intervals, limits, OCL id filters and access
+    /// policies. Returns the names of other changed parameters, which are
+    /// only applied on restart.
+    pub fn apply_reloadable(&mut self, reloaded: Config) -> Vec<&'static str> {
+        // NOTE: Multisig access policies of the chains are applied live.
+        let chains_changed = without_multisig_access_policies(self.chains.as_deref())
+            != without_multisig_access_policies(reloaded.chains.as_deref());
+
+        let restart_required_changes = [
+            ("http_address", self.http_address != reloaded.http_address),
+            ("http_port", self.http_port != reloaded.http_port),
+            (
+                "kamu_node_gql_api_endpoint",
+                self.kamu_node_gql_api_endpoint != reloaded.kamu_node_gql_api_endpoint,
+            ),
+            (
+                "kamu_node_token",
+                self.kamu_node_token != reloaded.kamu_node_token,
+            ),
+            (
+                "molecule_projects_dataset_alias",
+                self.molecule_projects_dataset_alias != reloaded.molecule_projects_dataset_alias,
+            ),
+            ("chains", chains_changed),
+            ("chain_id", self.chain_id != reloaded.chain_id),
+            ("rpc_url", self.rpc_url != reloaded.rpc_url),
+            (
+                "labnft_contract_address",
+                self.labnft_contract_address != reloaded.labnft_contract_address,
+            ),
+            (
+                "labnft_contract_birth_block",
+                self.labnft_contract_birth_block != reloaded.labnft_contract_birth_block,
+            ),
+            (
+                "indexing_head",
+                self.indexing_head != reloaded.indexing_head,
+            ),
+            (
+                "indexing_confirmation_depth",
+                self.indexing_confirmation_depth != reloaded.indexing_confirmation_depth,
+            ),
+            (
+                "safe_owners_resolver",
+                self.safe_owners_resolver != reloaded.safe_owners_resolver,
+            ),
+            (
+                "state_file_path",
+                self.state_file_path != reloaded.state_file_path,
+            ),
+            (
+                "audit_log_file_path",
+                self.audit_log_file_path != reloaded.audit_log_file_path,
+            ),
+            (
+                "audit_log_max_records",
+                self.audit_log_max_records != reloaded.audit_log_max_records,
+            ),
+            (
+                "audit_log_dataset_alias",
+                self.audit_log_dataset_alias != reloaded.audit_log_dataset_alias,
+            ),
+            (
+                "access_changes_dataset_alias",
+                self.access_changes_dataset_alias != reloaded.access_changes_dataset_alias,
+            ),
+            (
+                "event_log_max_entries",
+                self.event_log_max_entries != reloaded.event_log_max_entries,
+            ),
+            (
+                "event_log_file_path",
+                self.event_log_file_path != reloaded.event_log_file_path,
+            ),
+            (
+                "ocl_holder_tokens",
+                self.ocl_holder_tokens != reloaded.ocl_holder_tokens,
+            ),
+            ("admin_token", self.admin_token != reloaded.admin_token),
+            (
+                "kamu_webhook_token",
+                self.kamu_webhook_token != reloaded.kamu_webhook_token,
+            ),
+        ];
+
+        self.molecule_projects_loading_interval_in_secs =
+            reloaded.molecule_projects_loading_interval_in_secs;
+        self.indexing_delay_between_iterations_in_secs =
+            reloaded.indexing_delay_between_iterations_in_secs;
+        self.reconciliation_interval_in_secs = reloaded.reconciliation_interval_in_secs;
+        self.concurrency_limit = reloaded.concurrency_limit;
+        self.kamu_node_max_operations_per_request = reloaded.kamu_node_max_operations_per_request;
+        self.ignore_ocl_ids = reloaded.ignore_ocl_ids;
+        self.only_ocl_ids = reloaded.only_ocl_ids;
+        self.ocl_id_filter_file_path = reloaded.ocl_id_filter_file_path;
+        self.access_policy = reloaded.access_policy;
+        self.multisig_access_policy = reloaded.multisig_access_policy;
+        self.ocl_multisig_access_policies = reloaded.ocl_multisig_access_policies;
+        if !chains_changed {
+            self.chains = reloaded.chains;
+        }
+
+        restart_required_changes
+            .into_iter()
+            .filter_map(|(name, is_changed)| is_changed.then_some(name))
+            .collect()
+    }
+}
+
+/// Config value that is redacted in the `Debug` output, so that it doesn't
+/// leak into logs
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+/// Chain with a LabNFT deployment
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ChainConfig {
+    /// ID of the chain that RCP URL is expected to point to
+    pub chain_id: u64,
+    pub rpc_url: String,
+
+    pub labnft_contract_address: Address,
+    pub labnft_contract_birth_block: u64,
+
+    /// Block up to which the chain is indexed: `finalized`, `safe` or `latest`
+    #[serde(default = "IndexingHead::default_head")]
+    pub indexing_head: IndexingHead,
+    /// Number of blocks behind the `safe`/`latest` head that are not indexed yet
+    #[serde(default)]
+    pub indexing_confirmation_depth: u64,
+
+    /// IP-tokens (ERC-20) deployed on this chain per OCL id
+    #[serde(default)]
+    pub ocl_holder_tokens: HashMap<OclId, Address>,
+
+    /// Roles of multisig signers for the files of projects owned by a multisig
+    #[serde(default)]
+    pub multisig_access_policy: MultisigAccessPolicy,
+}
+
+impl ChainConfig {
+    /// Returns the OCL id for each configured IP-token address
+    pub fn ocl_ids_by_holder_token(&self) -> HashMap<Address, OclId> {
+        self.ocl_holder_tokens
+            .iter()
+            .map(|(ocl_id, token_address)| (*token_address, *ocl_id))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafeOwnersResolver {
+    Logs,
+    ContractCall,
+    Api,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexingHead {
+    Finalized,
+    Safe,
+    Latest,
+}
+
+impl IndexingHead {
+    fn default_head() -> Self {
+        Self::Finalized
+    }
+
+    pub fn block_tag(self) -> BlockNumberOrTag {
+        match self {
+            Self::Finalized => BlockNumberOrTag::Finalized,
+            Self::Safe => BlockNumberOrTag::Safe,
+            Self::Latest => BlockNumberOrTag::Latest,
+        }
+    }
+}
+
+fn without_multisig_access_policies(
+    maybe_chains: Option<&[ChainConfig]>,
+) -> Option<Vec<ChainConfig>> {
+    maybe_chains.map(|chains| {
+        chains
+            .iter()
+            .map(|chain| ChainConfig {
+                multisig_access_policy: MultisigAccessPolicy::default(),
+                ..chain.clone()
+            })
+            .collect()
+    })
 }
@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use kamu_node_api_client::OclIdFilter;
+
+use crate::config::Config;
+
+/// Interval between checks of the config and OCL id filter files modification
+/// time
+const CONFIG_FILE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Config that was reloaded and passed validation
+#[derive(Debug)]
+pub struct ReloadedConfig {
+    pub config: Config,
+    /// Loaded from `ocl_id_filter_file_path` if it is set
+    pub ocl_id_filter: OclIdFilter,
+}
+
+impl ReloadedConfig {
+    /// Loads the config the same way as on start: environment variables take
+    /// precedence over the file
+    pub fn load(config_path: &Path) -> eyre::Result<Self> {
+        let config = Config::builder().env().file(config_path).load()?;
+
+        // NOTE: Validated the same way as on start.
+        let chain_configs = config.chain_configs()?;
+        config.access_policies(&chain_configs)?;
+        let ocl_id_filter = config.load_ocl_id_filter()?;
+
+        Ok(Self {
+            config,
+            ocl_id_filter,
+        })
+    }
+}
+
+pub type ReloadedConfigSender = tokio::sync::mpsc::UnboundedSender<ReloadedConfig>;
+pub type ReloadedConfigReceiver = tokio::sync::mpsc::UnboundedReceiver<ReloadedConfig>;
+
+/// Reloads the config when the config or OCL id filter file is modified or on
+/// `SIGHUP` and passes it to the main loop. An invalid config (including the
+/// OCL id filter file) is reported and skipped, so the current one is kept.
+pub async fn watch_config(
+    config_path: PathBuf,
+    mut maybe_ocl_id_filter_file_path: Option<PathBuf>,
+    reloaded_config_sender: ReloadedConfigSender,
+) {
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        .expect("failed to install signal handler");
+    let mut last_modified_at =
+        files_modified_at(&config_path, maybe_ocl_id_filter_file_path.as_deref());
+
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => {
+                tracing::info!("SIGHUP signal received, reloading the config");
+            },
+            () = tokio::time::sleep(CONFIG_FILE_CHECK_INTERVAL) => {
+                if files_modified_at(&config_path, maybe_ocl_id_filter_file_path.as_deref())
+                    == last_modified_at
+                {
+                    continue;
+                }
+                tracing::info!(
+                    ?config_path,
+                    ?maybe_ocl_id_filter_file_path,
+                    "Config or OCL id filter file is modified, reloading the config"
+                );
+            },
+        }
+
+        last_modified_at =
+            files_modified_at(&config_path, maybe_ocl_id_filter_file_path.as_deref());
+
+        match ReloadedConfig::load(&config_path) {
+            Ok(reloaded_config) => {
+                if reloaded_config.config.ocl_id_filter_file_path != maybe_ocl_id_filter_file_path {
+                    maybe_ocl_id_filter_file_path =
+                        reloaded_config.config.ocl_id_filter_file_path.clone();
+                    last_modified_at =
+                        files_modified_at(&config_path, maybe_ocl_id_filter_file_path.as_deref());
+                }
+
+                if reloaded_config_sender.send(reloaded_config).is_err() {
+                    // The main loop is stopped
+                    return;
+                }
+            }
+            Err(e) => {
+                tracing::error!(
+                    error = ?e,
+                    error_msg = %e,
+                    "Keep the current config: the reloaded one is invalid"
+                );
+            }
+        }
+    }
+}
+
+fn files_modified_at(
+    config_path: &Path,
+    maybe_ocl_id_filter_file_path: Option<&Path>,
+) -> (Option<SystemTime>, Option<SystemTime>) {
+    (
+        modified_at(config_path),
+        maybe_ocl_id_filter_file_path.and_then(modified_at),
+    )
+}
+
+/// `None` if the file is missing
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
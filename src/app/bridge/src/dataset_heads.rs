@@ -0,0 +1,42 @@
+use kamu_node_api_client::{DatasetHead, DatasetHeadsMap, DatasetID};
+use serde::{Deserialize, Serialize};
+
+/// Heads of `molecule/projects` and of the data rooms and files of the known
+/// projects. The heads of the last scan are kept in the state, so that only
+/// datasets whose head moved are queried with SQL.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetHeads {
+    pub molecule_projects: Option<DatasetHead>,
+    #[serde(default)]
+    pub datasets: DatasetHeadsMap,
+}
+
+impl DatasetHeads {
+    /// Whether `molecule/projects` may have changed since the scan
+    pub fn is_molecule_projects_changed(&self, scanned: &DatasetHeads) -> bool {
+        is_head_changed(
+            self.molecule_projects.as_ref(),
+            scanned.molecule_projects.as_ref(),
+        )
+    }
+
+    /// Whether the dataset may have changed since the scan
+    pub fn is_dataset_changed(&self, scanned: &DatasetHeads, dataset_id: &DatasetID) -> bool {
+        is_head_changed(
+            self.datasets.get(dataset_id),
+            scanned.datasets.get(dataset_id),
+        )
+    }
+}
+
+/// An unknown head (e.g. a dataset that was not scanned yet or a failed
+/// request) is always treated as a change, so it falls back to polling.
+fn is_head_changed(
+    maybe_current: Option<&DatasetHead>,
+    maybe_scanned: Option<&DatasetHead>,
+) -> bool {
+    match (maybe_current, maybe_scanned) {
+        (Some(current), Some(scanned)) => current != scanned,
+        _ => true,
+    }
+}
@@ -0,0 +1,854 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use alloy::primitives::{Address, U256};
+use kamu_node_api_client::{DatasetID, MoleculeAccessLevel, VersionedFileEntry};
+use molecule_ocl::entities::{
+    OclId, OclIdentityCreatedEvent, OclOwnershipChange, OclOwnershipDiffMap,
+    OclOwnershipProjectionMap, OclTransferEvent,
+};
+
+use crate::events::{BridgeEvent, EventLogEntry};
+use crate::state::{
+    AppState, ChainState, MultisigState, OclHoldersState, UnfinalizedCheckpoint,
+    VersionedFileEntryWithMoleculeAccessLevel,
+};
+
+/// Changes detected while projecting events onto the state.
+/// Used to decide which permissions need to be [re]applied.
+#[derive(Debug, Default)]
+pub struct ProjectionChanges {
+    /// Per chain ID
+    pub on_chain: BTreeMap<u64, OnChainProjectionChanges>,
+    pub changed_files_per_project: ChangedVersionedFilePerProjectMap,
+}
+
+/// Changes of the on-chain state of a single chain
+#[derive(Debug, Default)]
+pub struct OnChainProjectionChanges {
+    pub ocl_ownership_diff_map: OclOwnershipDiffMap,
+    pub changed_ocl_multisig_owners:
+        HashMap<OclId, /* updated multisig (changed owners) */ Address>,
+    /// OCLs owned by a token-bound account of another changed OCL (transitively)
+    pub changed_ocl_token_bound_account_owners:
+        HashMap<OclId, /* token-bound account (changed owners) */ Address>,
+    pub changed_ocl_holders: HashMap<OclId, OclHolderChanges>,
+    /// OCLs left without an owner by a chain reorganization
+    pub dropped_ocl_owners: HashMap<OclId, /* orphaned owner */ Address>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OclHolderChanges {
+    /// Balance became non-zero
+    pub added: HashSet<Address>,
+    /// Balance dropped to zero
+    pub removed: HashSet<Address>,
+}
+
+impl OclHolderChanges {
+    fn add(&mut self, holder: Address) {
+        self.removed.remove(&holder);
+        self.added.insert(holder);
+    }
+
+    fn remove(&mut self, holder: Address) {
+        self.added.remove(&holder);
+        self.removed.insert(holder);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedVersionedFile {
+    pub dataset_id: DatasetID,
+    pub change: DataRoomFileChange,
+}
+
+pub type ChangedVersionedFilePerProjectMap = HashMap<OclId, Vec<ChangedVersionedFile>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataRoomFileChange {
+    Added(MoleculeAccessLevel),
+    Removed,
+    MoleculeAccessLevelChanged {
+        from: MoleculeAccessLevel,
+        to: MoleculeAccessLevel,
+    },
+}
+
+/// Derives [`AppState`] from the event log entries.
+///
+/// OCL transfers and identities, as well as IP-token transfers, are buffered
+/// until the [`BridgeEvent::BlocksIndexed`] checkpoint and applied as a single
+/// batch, so replaying the log reproduces exactly the same state as the live
+/// indexing did.
+///
+/// Chains are projected independently of each other.
+#[derive(Debug, Default)]
+pub struct AppStateProjector {
+    chain_projectors: BTreeMap<u64, ChainStateProjector>,
+    changed_files_per_project: ChangedVersionedFilePerProjectMap,
+}
+
+#[derive(Debug, Default)]
+struct ChainStateProjector {
+    pending_ocl_transfers: Vec<OclTransferEvent>,
+    pending_ocl_identities: Vec<OclIdentityCreatedEvent>,
+    pending_holder_token_transfers: Vec<HolderTokenTransfer>,
+    pending_changed_multisigs: HashSet<Address>,
+    /// Multisig state before the first owner change in the current range
+    /// (owner changes are applied immediately, unlike OCL transfers)
+    maybe_range_start_multisig: Option<HashMap<Address, Option<MultisigState>>>,
+    /// On-chain state before the first rollback, used to compensate
+    /// permissions granted based on orphaned blocks
+    maybe_pre_reorg_state: Option<PreReorgState>,
+    changes: OnChainProjectionChanges,
+}
+
+#[derive(Debug)]
+struct HolderTokenTransfer {
+    ocl_id: OclId,
+    from: Address,
+    to: Address,
+    value: U256,
+}
+
+#[derive(Debug)]
+struct PreReorgState {
+    ocl_ownership_projection_map: OclOwnershipProjectionMap,
+    multisig: HashMap<Address, Option<MultisigState>>,
+    ocl_holders: HashMap<OclId, OclHoldersState>,
+}
+
+impl AppStateProjector {
+    pub fn project(app_state: &mut AppState, entries: &[EventLogEntry]) -> ProjectionChanges {
+        let mut projector = Self::default();
+
+        for entry in entries {
+            projector.apply(app_state, &entry.event);
+            app_state.event_log_next_sequence = entry.sequence + 1;
+        }
+
+        projector.finish(app_state)
+    }
+
+    /// Rebuilds the state from scratch
+    pub fn replay(entries: &[EventLogEntry]) -> AppState {
+        let mut app_state = AppState::default();
+
+        Self::project(&mut app_state, entries);
+
+        app_state
+    }
+
+    fn apply(&mut self, app_state: &mut AppState, event: &BridgeEvent) {
+        match event {
+            BridgeEvent::OclTransferred {
+                chain_id,
+                ocl_id,
+                from,
+                to,
+                ..
+            } => {
+                self.chain_projector(*chain_id)
+                    .pending_ocl_transfers
+                    .push(OclTransferEvent {
+                        ocl_id: *ocl_id,
+                        from: *from,
+                        to: *to,
+                    });
+            }
+            BridgeEvent::OclIdentityCreated {
+                chain_id,
+                ocl_id,
+                account,
+                ..
+            } => {
+                self.chain_projector(*chain_id).pending_ocl_identities.push(
+                    OclIdentityCreatedEvent {
+                        ocl_id: *ocl_id,
+                        account: *account,
+                    },
+                );
+            }
+            BridgeEvent::HolderTokenTransferred {
+                chain_id,
+                ocl_id,
+                from,
+                to,
+                value,
+                ..
+            } => {
+                self.chain_projector(*chain_id)
+                    .pending_holder_token_transfers
+                    .push(HolderTokenTransfer {
+                        ocl_id: *ocl_id,
+                        from: *from,
+                        to: *to,
+                        value: *value,
+                    });
+            }
+            BridgeEvent::MultisigOwnerAdded {
+                chain_id,
+                multisig,
+                owner,
+                ..
+            } => {
+                let (chain_projector, chain_state) = self.chain(app_state, *chain_id);
+
+                chain_projector
+                    .maybe_range_start_multisig
+                    .get_or_insert_with(|| chain_state.multisig.clone());
+
+                let Some(Some(multisig_state)) = chain_state.multisig.get_mut(multisig) else {
+                    tracing::warn!(
+                        chain_id,
+                        %multisig,
+                        %owner,
+                        "Skip added owner: unknown multisig"
+                    );
+                    return;
+                };
+
+                multisig_state.current_owners.insert(*owner);
+                chain_projector.pending_changed_multisigs.insert(*multisig);
+            }
+            BridgeEvent::MultisigOwnerRemoved {
+                chain_id,
+                multisig,
+                owner,
+                ..
+            } => {
+                let (chain_projector, chain_state) = self.chain(app_state, *chain_id);
+
+                chain_projector
+                    .maybe_range_start_multisig
+                    .get_or_insert_with(|| chain_state.multisig.clone());
+
+                let Some(Some(multisig_state)) = chain_state.multisig.get_mut(multisig) else {
+                    tracing::warn!(
+                        chain_id,
+                        %multisig,
+                        %owner,
+                        "Skip removed owner: unknown multisig"
+                    );
+                    return;
+                };
+
+                multisig_state.current_owners.remove(owner);
+                multisig_state.former_owners.insert(*owner);
+                chain_projector.pending_changed_multisigs.insert(*multisig);
+            }
+            BridgeEvent::MultisigThresholdChanged {
+                chain_id,
+                multisig,
+                threshold,
+                ..
+            } => {
+                let (chain_projector, chain_state) = self.chain(app_state, *chain_id);
+
+                chain_projector
+                    .maybe_range_start_multisig
+                    .get_or_insert_with(|| chain_state.multisig.clone());
+
+                let Some(Some(multisig_state)) = chain_state.multisig.get_mut(multisig) else {
+                    tracing::warn!(
+                        chain_id,
+                        %multisig,
+                        threshold,
+                        "Skip changed threshold: unknown multisig"
+                    );
+                    return;
+                };
+
+                // NOTE: Signers may get a different role (see `MultisigAccessPolicy`).
+                multisig_state.threshold = *threshold;
+                chain_projector.pending_changed_multisigs.insert(*multisig);
+            }
+            BridgeEvent::MultisigDiscovered {
+                chain_id,
+                address,
+                state,
+                ..
+            } => {
+                let chain_state = app_state.chains.entry(*chain_id).or_default();
+
+                chain_state.multisig.insert(*address, state.clone());
+            }
+            BridgeEvent::BlocksIndexed { chain_id, to_block } => {
+                let (chain_projector, chain_state) = self.chain(app_state, *chain_id);
+
+                chain_projector.maybe_range_start_multisig = None;
+                chain_projector.flush_on_chain_changes(chain_state);
+
+                chain_state.latest_indexed_block_number = *to_block;
+            }
+            BridgeEvent::HeadBlocksIndexed {
+                chain_id,
+                to_block,
+                to_block_hash,
+                finalized_block_number,
+            } => {
+                let (chain_projector, chain_state) = self.chain(app_state, *chain_id);
+
+                let checkpoint = UnfinalizedCheckpoint {
+                    from_block: chain_state.latest_indexed_block_number + 1,
+                    to_block: *to_block,
+                    to_block_hash: *to_block_hash,
+                    ocl_ownership_projection_map: chain_state
+                        .on_chain_ocl_ownership_projection_map
+                        .clone(),
+                    multisig: chain_projector
+                        .maybe_range_start_multisig
+                        .take()
+                        .unwrap_or_else(|| chain_state.multisig.clone()),
+                    ocl_holders: chain_state.ocl_holders.clone(),
+                };
+
+                chain_projector.flush_on_chain_changes(chain_state);
+
+                chain_state.latest_indexed_block_number = *to_block;
+
+                // Finalized ranges cannot be orphaned anymore
+                chain_state
+                    .unfinalized_checkpoints
+                    .retain(|checkpoint| checkpoint.to_block > *finalized_block_number);
+                if checkpoint.to_block > *finalized_block_number {
+                    chain_state.unfinalized_checkpoints.push(checkpoint);
+                }
+            }
+            BridgeEvent::ChainReorganized {
+                chain_id,
+                fork_block_number,
+            } => {
+                let (chain_projector, chain_state) = self.chain(app_state, *chain_id);
+
+                chain_projector.rollback(chain_state, *chain_id, *fork_block_number);
+            }
+            BridgeEvent::ChainStateReset {
+                chain_id,
+                latest_indexed_block_number,
+            } => {
+                self.chain_projectors.remove(chain_id);
+
+                app_state
+                    .chains
+                    .insert(*chain_id, ChainState::new(*latest_indexed_block_number));
+            }
+            BridgeEvent::StateReset => {
+                *self = Self::default();
+                *app_state = AppState::default();
+            }
+            BridgeEvent::MoleculeProjectLoaded { project } => {
+                let ocl_id = project.entry.ocl_id;
+                let changes = prepare_file_changes(
+                    project.entry.is_deleted(),
+                    &project.actual_files_map,
+                    &project.removed_files_map,
+                );
+
+                app_state
+                    .off_chain_ocl_project_map
+                    .insert(ocl_id, project.clone());
+
+                self.add_file_changes(ocl_id, changes);
+            }
+            BridgeEvent::DataRoomFilesChanged {
+                ocl_id,
+                latest_data_room_offset,
+                added_files,
+                removed_files,
+            } => {
+                let Some(project) = app_state.off_chain_ocl_project_map.get_mut(ocl_id) else {
+                    tracing::warn!(%ocl_id, "Skip data-room changes: unknown project");
+                    return;
+                };
+
+                let changes =
+                    prepare_file_changes(project.entry.is_deleted(), added_files, removed_files);
+
+                project
+                    .actual_files_map
+                    .retain(|dataset_id, _| !removed_files.contains_key(dataset_id));
+                project.actual_files_map.extend(added_files.clone());
+
+                project
+                    .removed_files_map
+                    .retain(|dataset_id, _| !added_files.contains_key(dataset_id));
+                project.removed_files_map.extend(removed_files.clone());
+
+                project.latest_data_room_offset = *latest_data_room_offset;
+
+                self.add_file_changes(*ocl_id, changes);
+            }
+            BridgeEvent::MoleculeAccessLevelChanged {
+                ocl_id,
+                dataset_id,
+                from,
+                to,
+            } => {
+                let Some(file) = app_state
+                    .off_chain_ocl_project_map
+                    .get_mut(ocl_id)
+                    .and_then(|project| project.actual_files_map.get_mut(dataset_id))
+                else {
+                    tracing::warn!(%ocl_id, dataset_id, "Skip access level change: unknown file");
+                    return;
+                };
+
+                file.molecule_access_level = to.clone();
+
+                self.add_file_changes(
+                    *ocl_id,
+                    vec![ChangedVersionedFile {
+                        dataset_id: dataset_id.clone(),
+                        change: DataRoomFileChange::MoleculeAccessLevelChanged {
+                            from: from.clone(),
+                            to: to.clone(),
+                        },
+                    }],
+                );
+            }
+            BridgeEvent::MoleculeProjectsScanned {
+                molecule_projects_dataset_offset,
+                ocl_id_filter,
+            } => {
+                app_state.molecule_projects_dataset_offset = *molecule_projects_dataset_offset;
+                app_state.molecule_projects_ocl_id_filter = ocl_id_filter.clone();
+            }
+            BridgeEvent::MoleculeProjectIgnored { ocl_id } => {
+                app_state.off_chain_ocl_project_map.remove(ocl_id);
+                // NOTE: Access is not derived for the project anymore.
+                self.changed_files_per_project.remove(ocl_id);
+            }
+        }
+    }
+
+    fn add_file_changes(&mut self, ocl_id: OclId, changes: Vec<ChangedVersionedFile>) {
+        if changes.is_empty() {
+            return;
+        }
+
+        self.changed_files_per_project
+            .entry(ocl_id)
+            .or_default()
+            .extend(changes);
+    }
+
+    fn finish(self, app_state: &mut AppState) -> ProjectionChanges {
+        let mut on_chain = BTreeMap::new();
+
+        for (chain_id, chain_projector) in self.chain_projectors {
+            let chain_state = app_state.chains.entry(chain_id).or_default();
+
+            on_chain.insert(chain_id, chain_projector.finish(chain_state, chain_id));
+        }
+
+        ProjectionChanges {
+            on_chain,
+            changed_files_per_project: self.changed_files_per_project,
+        }
+    }
+
+    fn chain_projector(&mut self, chain_id: u64) -> &mut ChainStateProjector {
+        self.chain_projectors.entry(chain_id).or_default()
+    }
+
+    fn chain<'a>(
+        &'a mut self,
+        app_state: &'a mut AppState,
+        chain_id: u64,
+    ) -> (&'a mut ChainStateProjector, &'a mut ChainState) {
+        (
+            self.chain_projectors.entry(chain_id).or_default(),
+            app_state.chains.entry(chain_id).or_default(),
+        )
+    }
+}
+
+impl ChainStateProjector {
+    fn rollback(&mut self, chain_state: &mut ChainState, chain_id: u64, fork_block_number: u64) {
+        let Some(checkpoint_index) = chain_state.first_orphaned_checkpoint_index(fork_block_number)
+        else {
+            tracing::warn!(
+                chain_id,
+                fork_block_number,
+                "Skip rollback: no orphaned checkpoints"
+            );
+            return;
+        };
+
+        // NOTE: Events that were not checkpointed yet belong to the orphaned blocks as well.
+        self.pending_ocl_transfers.clear();
+        self.pending_ocl_identities.clear();
+        self.pending_holder_token_transfers.clear();
+        self.pending_changed_multisigs.clear();
+        self.maybe_range_start_multisig = None;
+
+        if self.maybe_pre_reorg_state.is_none() {
+            self.maybe_pre_reorg_state = Some(PreReorgState {
+                ocl_ownership_projection_map: chain_state
+                    .on_chain_ocl_ownership_projection_map
+                    .clone(),
+                multisig: chain_state.multisig.clone(),
+                ocl_holders: chain_state.ocl_holders.clone(),
+            });
+        }
+
+        let mut orphaned_checkpoints = chain_state
+            .unfinalized_checkpoints
+            .split_off(checkpoint_index);
+        let checkpoint = orphaned_checkpoints.swap_remove(0);
+
+        tracing::warn!(
+            chain_id,
+            fork_block_number,
+            rollback_to_block = checkpoint.from_block - 1,
+            orphaned_to_block = chain_state.latest_indexed_block_number,
+            "Rolling back the on-chain state",
+        );
+
+        chain_state.on_chain_ocl_ownership_projection_map = checkpoint.ocl_ownership_projection_map;
+        // NOTE: Addresses discovered after the checkpoint are kept as is: whether an address
+        //       is a multisig does not depend on the orphaned blocks.
+        chain_state.multisig.extend(checkpoint.multisig);
+        chain_state.ocl_holders = checkpoint.ocl_holders;
+        chain_state.latest_indexed_block_number = checkpoint.from_block - 1;
+    }
+
+    /// Replaces the changes collected since the rollback with the difference
+    /// between the state before the rollback (what permissions were actually
+    /// given for) and the current one
+    fn compensate_reorg(
+        &mut self,
+        chain_state: &mut ChainState,
+        chain_id: u64,
+        pre_reorg_state: PreReorgState,
+    ) {
+        let mut ocl_ownership_diff_map = OclOwnershipDiffMap::new();
+
+        for (ocl_id, ownership_projection) in
+            chain_state.on_chain_ocl_ownership_projection_map.iter()
+        {
+            let Some(current_owner) = ownership_projection.current else {
+                continue;
+            };
+            let pre_reorg_owner = pre_reorg_state
+                .ocl_ownership_projection_map
+                .get(ocl_id)
+                .and_then(|projection| projection.current);
+
+            if pre_reorg_owner != Some(current_owner) {
+                ocl_ownership_diff_map.insert(
+                    *ocl_id,
+                    OclOwnershipChange {
+                        former_owner: pre_reorg_owner,
+                        current_owner,
+                    },
+                );
+            }
+        }
+
+        // Owners of OCLs that were transferred only in orphaned blocks must lose their access.
+        let mut dropped_ocl_owners = HashMap::new();
+        for (ocl_id, pre_reorg_projection) in pre_reorg_state.ocl_ownership_projection_map.iter() {
+            let Some(orphaned_owner) = pre_reorg_projection.current else {
+                continue;
+            };
+            if chain_state
+                .on_chain_ocl_ownership_projection_map
+                .get(ocl_id)
+                .is_some_and(|ownership_projection| ownership_projection.current.is_some())
+            {
+                continue;
+            }
+
+            tracing::warn!(
+                chain_id,
+                %ocl_id,
+                %orphaned_owner,
+                "OCL is owned only in orphaned blocks, revoking its access",
+            );
+
+            // NOTE: The OCL is kept without an owner, so that the access can be revoked.
+            chain_state
+                .on_chain_ocl_ownership_projection_map
+                .drop_owner(
+                    *ocl_id,
+                    orphaned_owner,
+                    pre_reorg_projection.identity_account,
+                );
+            dropped_ocl_owners.insert(*ocl_id, orphaned_owner);
+        }
+
+        self.propagate_token_bound_account_changes(
+            chain_state,
+            ocl_ownership_diff_map
+                .keys()
+                .chain(dropped_ocl_owners.keys())
+                .copied()
+                .collect(),
+        );
+        self.changes.ocl_ownership_diff_map = ocl_ownership_diff_map;
+        self.changes.dropped_ocl_owners = dropped_ocl_owners;
+
+        // Holders that exist only in orphaned blocks must lose their access.
+        let mut changed_ocl_holders = HashMap::new();
+        for (ocl_id, pre_reorg_holders) in &pre_reorg_state.ocl_holders {
+            let holders = chain_state.ocl_holders.entry(*ocl_id).or_default();
+            let holder_changes: &mut OclHolderChanges =
+                changed_ocl_holders.entry(*ocl_id).or_default();
+
+            for orphaned_holder in pre_reorg_holders
+                .balances
+                .keys()
+                .filter(|holder| !holders.balances.contains_key(holder))
+            {
+                holders.former_holders.insert(*orphaned_holder);
+                holder_changes.remove(*orphaned_holder);
+            }
+        }
+        for (ocl_id, holders) in &chain_state.ocl_holders {
+            let pre_reorg_balances = pre_reorg_state
+                .ocl_holders
+                .get(ocl_id)
+                .map(|pre_reorg_holders| &pre_reorg_holders.balances);
+
+            for holder in holders.balances.keys().filter(|holder| {
+                !pre_reorg_balances.is_some_and(|balances| balances.contains_key(holder))
+            }) {
+                changed_ocl_holders.entry(*ocl_id).or_default().add(*holder);
+            }
+        }
+        changed_ocl_holders.retain(|_, holder_changes| {
+            !holder_changes.added.is_empty() || !holder_changes.removed.is_empty()
+        });
+
+        self.changes.changed_ocl_holders = changed_ocl_holders;
+
+        // Owners added to multisigs in orphaned blocks must lose their access.
+        let mut changed_multisigs = HashSet::new();
+        for (address, maybe_pre_reorg_multisig_state) in pre_reorg_state.multisig {
+            let (Some(pre_reorg_multisig_state), Some(Some(multisig_state))) = (
+                maybe_pre_reorg_multisig_state,
+                chain_state.multisig.get_mut(&address),
+            ) else {
+                continue;
+            };
+            if pre_reorg_multisig_state == *multisig_state {
+                continue;
+            }
+
+            for orphaned_owner in pre_reorg_multisig_state
+                .current_owners
+                .difference(&multisig_state.current_owners)
+            {
+                multisig_state.former_owners.insert(*orphaned_owner);
+            }
+
+            changed_multisigs.insert(address);
+        }
+
+        self.pending_changed_multisigs = changed_multisigs;
+        self.flush_on_chain_changes(chain_state);
+    }
+
+    fn flush_on_chain_changes(&mut self, chain_state: &mut ChainState) {
+        let ocl_identities = std::mem::take(&mut self.pending_ocl_identities);
+        let changed_identity_ocl_ids = chain_state
+            .on_chain_ocl_ownership_projection_map
+            .apply_identity_events(ocl_identities);
+
+        // NOTE: For a transfer chain A -> B -> C within one batch, only the final owner
+        //       matters, which is handled by the projection map itself.
+        let ocl_transfers = std::mem::take(&mut self.pending_ocl_transfers);
+        let ocl_ownership_diff_map = chain_state
+            .on_chain_ocl_ownership_projection_map
+            .apply_events(ocl_transfers);
+        let mut changed_ocl_ids = ocl_ownership_diff_map
+            .keys()
+            .copied()
+            .collect::<HashSet<_>>();
+
+        for (ocl_id, ownership_change) in ocl_ownership_diff_map {
+            match self.changes.ocl_ownership_diff_map.entry(ocl_id) {
+                Entry::Occupied(mut e) => {
+                    // Keep the former owner from the first batch
+                    e.get_mut().current_owner = ownership_change.current_owner;
+                }
+                Entry::Vacant(e) => {
+                    e.insert(ownership_change);
+                }
+            }
+        }
+
+        let holder_token_transfers = std::mem::take(&mut self.pending_holder_token_transfers);
+        for transfer in holder_token_transfers {
+            self.apply_holder_token_transfer(chain_state, transfer);
+        }
+
+        let changed_multisigs = std::mem::take(&mut self.pending_changed_multisigs);
+        if !changed_multisigs.is_empty() {
+            let changed_ocl_multisig_owners = chain_state
+                .on_chain_ocl_ownership_projection_map
+                .iter()
+                .filter_map(|(ocl_id, ownership_projection)| {
+                    ownership_projection
+                        .current
+                        .filter(|owner| changed_multisigs.contains(owner))
+                        .map(|owner| (*ocl_id, owner))
+                })
+                .collect::<Vec<_>>();
+
+            changed_ocl_ids.extend(
+                changed_ocl_multisig_owners
+                    .iter()
+                    .map(|(ocl_id, _)| *ocl_id),
+            );
+            self.changes
+                .changed_ocl_multisig_owners
+                .extend(changed_ocl_multisig_owners);
+        }
+
+        changed_ocl_ids.extend(changed_identity_ocl_ids);
+        self.propagate_token_bound_account_changes(chain_state, changed_ocl_ids);
+    }
+
+    /// If the owners of an OCL have changed, so have the owners of OCLs owned
+    /// by its token-bound account (TBA -> OCL -> owner)
+    fn propagate_token_bound_account_changes(
+        &mut self,
+        chain_state: &ChainState,
+        changed_ocl_ids: HashSet<OclId>,
+    ) {
+        let ocl_ownership_projection_map = &chain_state.on_chain_ocl_ownership_projection_map;
+
+        // NOTE: Visited OCLs protect against ownership cycles.
+        let mut visited_ocl_ids = changed_ocl_ids.clone();
+        let mut ocl_ids_to_visit = changed_ocl_ids.into_iter().collect::<Vec<_>>();
+
+        while let Some(ocl_id) = ocl_ids_to_visit.pop() {
+            let Some(identity_account) = ocl_ownership_projection_map
+                .get(&ocl_id)
+                .and_then(|ownership_projection| ownership_projection.identity_account)
+            else {
+                continue;
+            };
+
+            for (owned_ocl_id, ownership_projection) in ocl_ownership_projection_map.iter() {
+                if ownership_projection.current != Some(identity_account)
+                    || !visited_ocl_ids.insert(*owned_ocl_id)
+                {
+                    continue;
+                }
+
+                self.changes
+                    .changed_ocl_token_bound_account_owners
+                    .insert(*owned_ocl_id, identity_account);
+                ocl_ids_to_visit.push(*owned_ocl_id);
+            }
+        }
+    }
+
+    fn apply_holder_token_transfer(
+        &mut self,
+        chain_state: &mut ChainState,
+        HolderTokenTransfer {
+            ocl_id,
+            from,
+            to,
+            value,
+        }: HolderTokenTransfer,
+    ) {
+        let holders = chain_state.ocl_holders.entry(ocl_id).or_default();
+
+        // NOTE: Transfers from/to the zero address are mints/burns.
+        if from != Address::ZERO {
+            let balance = holders.balances.get(&from).copied().unwrap_or_default();
+            if balance < value {
+                tracing::warn!(%ocl_id, %from, %balance, %value, "IP-token balance underflow");
+            }
+
+            let new_balance = balance.saturating_sub(value);
+            if new_balance.is_zero() {
+                if holders.balances.remove(&from).is_some() {
+                    holders.former_holders.insert(from);
+                    self.changes
+                        .changed_ocl_holders
+                        .entry(ocl_id)
+                        .or_default()
+                        .remove(from);
+                }
+            } else {
+                holders.balances.insert(from, new_balance);
+            }
+        }
+
+        if to != Address::ZERO && !value.is_zero() {
+            let balance = holders.balances.entry(to).or_default();
+            let is_new_holder = balance.is_zero();
+            *balance = balance.saturating_add(value);
+
+            if is_new_holder {
+                holders.former_holders.remove(&to);
+                self.changes
+                    .changed_ocl_holders
+                    .entry(ocl_id)
+                    .or_default()
+                    .add(to);
+            }
+        }
+    }
+
+    fn finish(mut self, chain_state: &mut ChainState, chain_id: u64) -> OnChainProjectionChanges {
+        if !self.pending_ocl_transfers.is_empty()
+            || !self.pending_ocl_identities.is_empty()
+            || !self.pending_holder_token_transfers.is_empty()
+            || !self.pending_changed_multisigs.is_empty()
+        {
+            // NOTE: On-chain events are always recorded together with the checkpoint,
+            //       so this indicates a truncated batch.
+            tracing::warn!(
+                chain_id,
+                "On-chain events are not followed by the blocks indexed checkpoint"
+            );
+
+            self.flush_on_chain_changes(chain_state);
+        }
+
+        if let Some(pre_reorg_state) = self.maybe_pre_reorg_state.take() {
+            self.compensate_reorg(chain_state, chain_id, pre_reorg_state);
+        }
+
+        self.changes
+    }
+}
+
+fn prepare_file_changes(
+    is_project_deleted: bool,
+    added_files: &HashMap<DatasetID, VersionedFileEntryWithMoleculeAccessLevel>,
+    removed_files: &HashMap<DatasetID, VersionedFileEntry>,
+) -> Vec<ChangedVersionedFile> {
+    let mut changes = Vec::with_capacity(added_files.len() + removed_files.len());
+
+    for (dataset_id, added_file) in added_files {
+        // NOTE: If the project is deleted, consider all files deleted as well.
+        let change = if is_project_deleted {
+            DataRoomFileChange::Removed
+        } else {
+            DataRoomFileChange::Added(added_file.molecule_access_level.clone())
+        };
+
+        changes.push(ChangedVersionedFile {
+            dataset_id: dataset_id.clone(),
+            change,
+        });
+    }
+    for dataset_id in removed_files.keys() {
+        changes.push(ChangedVersionedFile {
+            dataset_id: dataset_id.clone(),
+            change: DataRoomFileChange::Removed,
+        });
+    }
+
+    changes
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, B256, U256};
+use chrono::{DateTime, Utc};
+use kamu_node_api_client::{DatasetID, MoleculeAccessLevel, OclIdFilter, VersionedFileEntry};
+use molecule_ocl::entities::OclId;
+use serde::{Deserialize, Serialize};
+
+use crate::state::{
+    MultisigState, OffChainMoleculeProjectProjection, VersionedFileEntryWithMoleculeAccessLevel,
+};
+
+/// A single fact observed by the bridge in one of its sources.
+///
+/// Sources:
+/// - On-chain: LabNFT transfers and identities, multisig changes, IP-token
+///   transfers (source of truth, indexed by block number of the `chain_id`
+///   chain)
+/// - Off-chain: OCL project changes (polled periodically, indexed by offset)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum BridgeEvent {
+    // On-chain
+    OclTransferred {
+        chain_id: u64,
+        block_number: u64,
+        log_index: u64,
+        tx_hash: Option<B256>,
+        ocl_id: OclId,
+        from: Address,
+        to: Address,
+    },
+    /// ERC-6551 token-bound account was created for the OCL
+    OclIdentityCreated {
+        chain_id: u64,
+        block_number: u64,
+        log_index: u64,
+        tx_hash: Option<B256>,
+        ocl_id: OclId,
+        account: Address,
+    },
+    MultisigOwnerAdded {
+        chain_id: u64,
+        block_number: u64,
+        log_index: u64,
+        tx_hash: Option<B256>,
+        multisig: Address,
+        owner: Address,
+    },
+    MultisigOwnerRemoved {
+        chain_id: u64,
+        block_number: u64,
+        log_index: u64,
+        tx_hash: Option<B256>,
+        multisig: Address,
+        owner: Address,
+    },
+    MultisigThresholdChanged {
+        chain_id: u64,
+        block_number: u64,
+        log_index: u64,
+        tx_hash: Option<B256>,
+        multisig: Address,
+        threshold: u64,
+    },
+    /// Transfer of the IP-token configured for the OCL
+    HolderTokenTransferred {
+        chain_id: u64,
+        block_number: u64,
+        log_index: u64,
+        tx_hash: Option<B256>,
+        ocl_id: OclId,
+        from: Address,
+        to: Address,
+        value: U256,
+    },
+    /// The address was checked for being a multisig for the first time.
+    /// `state` is `None` for regular wallets.
+    MultisigDiscovered {
+        chain_id: u64,
+        at_block: u64,
+        address: Address,
+        state: Option<MultisigState>,
+    },
+    /// All on-chain events up to (and including) `to_block` were recorded.
+    BlocksIndexed { chain_id: u64, to_block: u64 },
+    /// Same as [`BridgeEvent::BlocksIndexed`], but `to_block` is not finalized
+    /// yet, so the range may be orphaned by a reorg.
+    HeadBlocksIndexed {
+        chain_id: u64,
+        to_block: u64,
+        to_block_hash: B256,
+        finalized_block_number: u64,
+    },
+    /// Blocks after `fork_block_number` were orphaned. The on-chain state is
+    /// rolled back to the nearest checkpoint and the range is indexed again.
+    ChainReorganized {
+        chain_id: u64,
+        fork_block_number: u64,
+    },
+    /// The on-chain state of the chain was dropped: indexing starts over
+    /// after `latest_indexed_block_number`.
+    ChainStateReset {
+        chain_id: u64,
+        latest_indexed_block_number: u64,
+    },
+
+    /// The whole state was dropped to be rebuilt from scratch (resync)
+    StateReset,
+
+    // Off-chain
+    MoleculeProjectLoaded {
+        project: OffChainMoleculeProjectProjection,
+    },
+    DataRoomFilesChanged {
+        ocl_id: OclId,
+        latest_data_room_offset: u64,
+        added_files: HashMap<DatasetID, VersionedFileEntryWithMoleculeAccessLevel>,
+        removed_files: HashMap<DatasetID, VersionedFileEntry>,
+    },
+    MoleculeAccessLevelChanged {
+        ocl_id: OclId,
+        dataset_id: DatasetID,
+        from: MoleculeAccessLevel,
+        to: MoleculeAccessLevel,
+    },
+    /// All `molecule/projects` entries up to (and including) the offset that
+    /// are allowed by the OCL id filter were recorded.
+    MoleculeProjectsScanned {
+        molecule_projects_dataset_offset: Option<u64>,
+        #[serde(default)]
+        ocl_id_filter: OclIdFilter,
+    },
+    /// The project is not allowed by the OCL id filter anymore, so it is
+    /// dropped (access granted for it is revoked separately).
+    MoleculeProjectIgnored { ocl_id: OclId },
+}
+
+impl BridgeEvent {
+    /// Returns the chain of on-chain events
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            Self::OclTransferred { chain_id, .. }
+            | Self::OclIdentityCreated { chain_id, .. }
+            | Self::MultisigOwnerAdded { chain_id, .. }
+            | Self::MultisigOwnerRemoved { chain_id, .. }
+            | Self::MultisigThresholdChanged { chain_id, .. }
+            | Self::HolderTokenTransferred { chain_id, .. }
+            | Self::MultisigDiscovered { chain_id, .. }
+            | Self::BlocksIndexed { chain_id, .. }
+            | Self::HeadBlocksIndexed { chain_id, .. }
+            | Self::ChainReorganized { chain_id, .. }
+            | Self::ChainStateReset { chain_id, .. } => Some(*chain_id),
+            Self::StateReset
+            | Self::MoleculeProjectLoaded { .. }
+            | Self::DataRoomFilesChanged { .. }
+            | Self::MoleculeAccessLevelChanged { .. }
+            | Self::MoleculeProjectsScanned { .. }
+            | Self::MoleculeProjectIgnored { .. } => None,
+        }
+    }
+
+    /// Returns the hash of the transaction that emitted the on-chain log event
+    pub fn tx_hash(&self) -> Option<B256> {
+        match self {
+            Self::OclTransferred { tx_hash, .. }
+            | Self::OclIdentityCreated { tx_hash, .. }
+            | Self::MultisigOwnerAdded { tx_hash, .. }
+            | Self::MultisigOwnerRemoved { tx_hash, .. }
+            | Self::MultisigThresholdChanged { tx_hash, .. }
+            | Self::HolderTokenTransferred { tx_hash, .. } => *tx_hash,
+            Self::MultisigDiscovered { .. }
+            | Self::BlocksIndexed { .. }
+            | Self::HeadBlocksIndexed { .. }
+            | Self::ChainReorganized { .. }
+            | Self::ChainStateReset { .. }
+            | Self::StateReset
+            | Self::MoleculeProjectLoaded { .. }
+            | Self::DataRoomFilesChanged { .. }
+            | Self::MoleculeAccessLevelChanged { .. }
+            | Self::MoleculeProjectsScanned { .. }
+            | Self::MoleculeProjectIgnored { .. } => None,
+        }
+    }
+
+    /// Returns the position of the event in the chain for on-chain log events
+    pub fn block_position(&self) -> Option<(u64, u64)> {
+        match self {
+            Self::OclTransferred {
+                block_number,
+                log_index,
+                ..
+            }
+            | Self::OclIdentityCreated {
+                block_number,
+                log_index,
+                ..
+            }
+            | Self::MultisigOwnerAdded {
+                block_number,
+                log_index,
+                ..
+            }
+            | Self::MultisigOwnerRemoved {
+                block_number,
+                log_index,
+                ..
+            }
+            | Self::MultisigThresholdChanged {
+                block_number,
+                log_index,
+                ..
+            }
+            | Self::HolderTokenTransferred {
+                block_number,
+                log_index,
+                ..
+            } => Some((*block_number, *log_index)),
+            Self::MultisigDiscovered { .. }
+            | Self::BlocksIndexed { .. }
+            | Self::HeadBlocksIndexed { .. }
+            | Self::ChainReorganized { .. }
+            | Self::ChainStateReset { .. }
+            | Self::StateReset
+            | Self::MoleculeProjectLoaded { .. }
+            | Self::DataRoomFilesChanged { .. }
+            | Self::MoleculeAccessLevelChanged { .. }
+            | Self::MoleculeProjectsScanned { .. }
+            | Self::MoleculeProjectIgnored { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLogEntry {
+    /// Monotonically increasing position of the entry in the log
+    pub sequence: u64,
+    /// Used for cross-source ordering
+    pub recorded_at: DateTime<Utc>,
+    pub event: BridgeEvent,
+}
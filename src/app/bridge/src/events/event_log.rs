@@ -0,0 +1,375 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use alloy::primitives::B256;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt as _, AsyncWriteExt as _};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::audit_log::records_to_ndjson;
+use crate::events::{BridgeEvent, EventLogEntry};
+
+/// Increment when [`BridgeEvent`] changes in an incompatible way. Logs
+/// written with a different version are rejected.
+pub const EVENT_LOG_FILE_FORMAT_VERSION: u32 = 1;
+
+/// Append-only ordered log of events observed by the bridge
+#[async_trait::async_trait]
+pub trait EventLog: Send + Sync {
+    /// Appends events to the end of the log, preserving their order, and
+    /// returns the recorded entries
+    async fn append(&self, events: Vec<BridgeEvent>) -> eyre::Result<Vec<EventLogEntry>>;
+
+    /// Returns up to `limit` retained entries starting from the
+    /// `from_sequence` position
+    async fn read(&self, from_sequence: u64, limit: usize) -> eyre::Result<Vec<EventLogEntry>>;
+
+    /// Returns all entries from the beginning of the log, so that the state
+    /// can be rebuilt by replaying them (see
+    /// [`crate::events::AppStateProjector::replay`]). `None` if the log
+    /// doesn't keep all entries.
+    async fn read_all(&self) -> eyre::Result<Option<Vec<EventLogEntry>>>;
+
+    /// Continues the log after the entries a restored state was projected
+    /// from (see [`crate::state::AppState::event_log_next_sequence`]). Entries
+    /// recorded after them are dropped: the state doesn't reflect them, so
+    /// they are observed again.
+    async fn resume(&self, next_sequence: u64) -> eyre::Result<()>;
+}
+
+/// Keeps only the latest entries in memory, so the state can't be rebuilt
+/// from it (see [`FileEventLog`]).
+pub struct InMemoryEventLog {
+    max_entries: usize,
+    entries: RwLock<EventLogEntries>,
+}
+
+#[derive(Default)]
+struct EventLogEntries {
+    /// Ordered by sequence
+    entries: VecDeque<EventLogEntry>,
+    next_sequence: u64,
+}
+
+impl InMemoryEventLog {
+    pub fn new(max_entries: usize) -> Self {
+        Self::restore(max_entries, Vec::new())
+    }
+
+    /// Continues the log after the given entries (ordered by sequence)
+    fn restore(max_entries: usize, entries: Vec<EventLogEntry>) -> Self {
+        let next_sequence = entries.last().map_or(0, |entry| entry.sequence + 1);
+
+        let mut entries = VecDeque::from(entries);
+        while entries.len() > max_entries {
+            entries.pop_front();
+        }
+
+        Self {
+            max_entries,
+            entries: RwLock::new(EventLogEntries {
+                entries,
+                next_sequence,
+            }),
+        }
+    }
+
+    /// Sequence of the oldest retained entry
+    async fn first_retained_sequence(&self) -> Option<u64> {
+        let readable_entries = self.entries.read().await;
+
+        readable_entries.entries.front().map(|entry| entry.sequence)
+    }
+
+    async fn next_sequence(&self) -> u64 {
+        self.entries.read().await.next_sequence
+    }
+}
+
+#[async_trait::async_trait]
+impl EventLog for InMemoryEventLog {
+    async fn append(&self, events: Vec<BridgeEvent>) -> eyre::Result<Vec<EventLogEntry>> {
+        let mut writable_entries = self.entries.write().await;
+
+        let recorded_at = Utc::now();
+        let first_sequence = writable_entries.next_sequence;
+
+        let new_entries = events
+            .into_iter()
+            .zip(first_sequence..)
+            .map(|(event, sequence)| EventLogEntry {
+                sequence,
+                recorded_at,
+                event,
+            })
+            .collect::<Vec<_>>();
+
+        writable_entries.next_sequence += new_entries.len() as u64;
+        writable_entries.entries.extend(new_entries.iter().cloned());
+        while writable_entries.entries.len() > self.max_entries {
+            writable_entries.entries.pop_front();
+        }
+
+        Ok(new_entries)
+    }
+
+    async fn read(&self, from_sequence: u64, limit: usize) -> eyre::Result<Vec<EventLogEntry>> {
+        let readable_entries = self.entries.read().await;
+
+        let from = readable_entries
+            .entries
+            .partition_point(|entry| entry.sequence < from_sequence);
+
+        Ok(readable_entries
+            .entries
+            .range(from..)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn read_all(&self) -> eyre::Result<Option<Vec<EventLogEntry>>> {
+        Ok(None)
+    }
+
+    async fn resume(&self, next_sequence: u64) -> eyre::Result<()> {
+        let mut writable_entries = self.entries.write().await;
+
+        match writable_entries.next_sequence.cmp(&next_sequence) {
+            Ordering::Greater => {
+                tracing::warn!(
+                    log_next_sequence = writable_entries.next_sequence,
+                    next_sequence,
+                    "Drop event log entries that the restored state doesn't reflect"
+                );
+                writable_entries
+                    .entries
+                    .retain(|entry| entry.sequence < next_sequence);
+            }
+            // NOTE: Entries the restored state was projected from are not retained.
+            Ordering::Less => writable_entries.entries.clear(),
+            Ordering::Equal => {}
+        }
+        writable_entries.next_sequence = next_sequence;
+
+        Ok(())
+    }
+}
+
+/// Keeps the whole log in a local file, so that it survives restarts and the
+/// state can be rebuilt by replaying it. The latest entries are also kept in
+/// memory, so that they are read without reading the file.
+///
+/// File layout: a single-line JSON header followed by one JSON-serialized
+/// entry per line (NDJSON). Entries are only appended, except for the ones
+/// dropped on [`EventLog::resume`].
+pub struct FileEventLog {
+    path: PathBuf,
+    fingerprint: B256,
+    in_memory: InMemoryEventLog,
+    /// Keeps the order of entries in the file the same as their sequences
+    file_lock: Mutex<()>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct EventLogFileHeader {
+    format_version: u32,
+    /// Identifies the configuration the events were observed with (see
+    /// [`crate::state_store::app_state_fingerprint`])
+    fingerprint: B256,
+}
+
+impl FileEventLog {
+    #[tracing::instrument(level = "info", skip_all, fields(path = %path.display()))]
+    pub async fn open(path: PathBuf, fingerprint: B256, max_entries: usize) -> eyre::Result<Self> {
+        let entries = match tokio::fs::read(&path).await {
+            Ok(content) => Self::parse_file(&path, &content, fingerprint).await?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::info!("Event log file not found, starting a new one");
+                write_file(&path, fingerprint, &[]).await?;
+                Vec::new()
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        tracing::info!(entries_count = entries.len(), "Event log is loaded");
+
+        Ok(Self {
+            path,
+            fingerprint,
+            in_memory: InMemoryEventLog::restore(max_entries, entries),
+            file_lock: Mutex::new(()),
+        })
+    }
+
+    async fn parse_file(
+        path: &Path,
+        content: &[u8],
+        fingerprint: B256,
+    ) -> eyre::Result<Vec<EventLogEntry>> {
+        let Some(header_end) = content.iter().position(|b| *b == b'\n') else {
+            eyre::bail!("Event log header is missing");
+        };
+        let header: EventLogFileHeader = serde_json::from_slice(&content[..header_end])?;
+
+        if header.format_version != EVENT_LOG_FILE_FORMAT_VERSION
+            || header.fingerprint != fingerprint
+        {
+            // NOTE: The log is the history of access changes, so it is never dropped silently.
+            eyre::bail!(
+                "Event log was recorded with a different format version or configuration \
+                 ({header:?}): move it away to start a new one"
+            );
+        }
+
+        // NOTE: The last line may not be written completely because of a crash.
+        let entries_end = content.iter().rposition(|b| *b == b'\n').unwrap_or(0) + 1;
+        let entries = parse_entries(&content[header_end + 1..entries_end])?;
+        if entries_end < content.len() {
+            tracing::warn!("Drop the incomplete last event log entry");
+            write_file(path, fingerprint, &entries).await?;
+        }
+
+        Ok(entries)
+    }
+
+    /// Reads the entries from the file, skipping the ones before
+    /// `from_sequence`
+    async fn read_file(
+        &self,
+        from_sequence: u64,
+        limit: usize,
+    ) -> eyre::Result<Vec<EventLogEntry>> {
+        let file = tokio::fs::File::open(&self.path).await?;
+        let mut lines = tokio::io::BufReader::new(file).lines();
+
+        // Header
+        lines.next_line().await?;
+
+        let mut entries = Vec::new();
+        while entries.len() < limit {
+            let Some(line) = lines.next_line().await? else {
+                break;
+            };
+
+            let entry: EventLogEntry = serde_json::from_str(&line)?;
+            if entry.sequence >= from_sequence {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+#[async_trait::async_trait]
+impl EventLog for FileEventLog {
+    async fn append(&self, events: Vec<BridgeEvent>) -> eyre::Result<Vec<EventLogEntry>> {
+        let _file_guard = self.file_lock.lock().await;
+
+        let entries = self.in_memory.append(events).await?;
+
+        let lines = records_to_ndjson(&entries)?;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(lines.as_bytes()).await?;
+        file.flush().await?;
+
+        Ok(entries)
+    }
+
+    async fn read(&self, from_sequence: u64, limit: usize) -> eyre::Result<Vec<EventLogEntry>> {
+        // NOTE: Older entries are only kept in the file.
+        if self
+            .in_memory
+            .first_retained_sequence()
+            .await
+            .is_some_and(|first_sequence| first_sequence <= from_sequence)
+        {
+            return self.in_memory.read(from_sequence, limit).await;
+        }
+
+        let _file_guard = self.file_lock.lock().await;
+
+        self.read_file(from_sequence, limit).await
+    }
+
+    async fn read_all(&self) -> eyre::Result<Option<Vec<EventLogEntry>>> {
+        let _file_guard = self.file_lock.lock().await;
+
+        Ok(Some(self.read_file(0, usize::MAX).await?))
+    }
+
+    async fn resume(&self, next_sequence: u64) -> eyre::Result<()> {
+        let _file_guard = self.file_lock.lock().await;
+
+        let log_next_sequence = self.in_memory.next_sequence().await;
+        if log_next_sequence < next_sequence {
+            eyre::bail!(
+                "Event log is behind the restored state: {log_next_sequence} < {next_sequence}"
+            );
+        }
+
+        if log_next_sequence > next_sequence {
+            let entries = self.read_file(0, usize::MAX).await?;
+            let retained_entries = entries
+                .into_iter()
+                .filter(|entry| entry.sequence < next_sequence)
+                .collect::<Vec<_>>();
+
+            write_file(&self.path, self.fingerprint, &retained_entries).await?;
+        }
+
+        self.in_memory.resume(next_sequence).await
+    }
+}
+
+/// Rewrites the whole file
+async fn write_file(path: &Path, fingerprint: B256, entries: &[EventLogEntry]) -> eyre::Result<()> {
+    let header = EventLogFileHeader {
+        format_version: EVENT_LOG_FILE_FORMAT_VERSION,
+        fingerprint,
+    };
+
+    let mut content = serde_json::to_string(&header)?;
+    content.push('\n');
+    content.push_str(&records_to_ndjson(entries)?);
+
+    // NOTE: Write to a temporary file first and then rename it, so that a crash
+    //       in the middle of writing doesn't leave a truncated log behind.
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, content).await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+
+    Ok(())
+}
+
+/// Unlike audit log records, entries can't be skipped: the state would be
+/// rebuilt without them
+fn parse_entries(content: &[u8]) -> eyre::Result<Vec<EventLogEntry>> {
+    let mut entries = Vec::<EventLogEntry>::new();
+
+    for line in content
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+    {
+        let entry: EventLogEntry = serde_json::from_slice(line)?;
+
+        let expected_sequence = entries.last().map_or(0, |last| last.sequence + 1);
+        if entry.sequence != expected_sequence {
+            eyre::bail!(
+                "Event log entries are not contiguous: expected {expected_sequence}, found {}",
+                entry.sequence
+            );
+        }
+
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,7 @@
+mod app_state_projector;
+mod bridge_event;
+mod event_log;
+
+pub use app_state_projector::*;
+pub use bridge_event::*;
+pub use event_log::*;
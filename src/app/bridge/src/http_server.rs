@@ -1,6 +1,18 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use alloy::primitives::Address;
+use kamu_node_api_client::DatasetID;
+use molecule_ocl::entities::OclId;
+use subtle::ConstantTimeEq as _;
+
+use crate::access::DesiredAccess;
+use crate::access_query::{DatasetAccessView, OclView, Page, PageArgs};
+use crate::admin_command::{AdminCommand, AdminCommandSender};
+use crate::audit_log::{AccessAuditLog, AccessAuditRecord, records_to_ndjson};
+use crate::config::Secret;
+use crate::events::{EventLog, EventLogEntry};
+
 pub type HttpServeFuture = axum::serve::Serve<
     tokio::net::TcpListener,
     axum::routing::IntoMakeService<axum::Router>,
@@ -10,6 +22,20 @@ pub type HttpServeFuture = axum::serve::Serve<
 #[async_trait::async_trait]
 pub trait StateRequester: Send + Sync {
     async fn request_as_json(&self) -> serde_json::Value;
+
+    async fn request_ocl(&self, ocl_id: OclId) -> Option<OclView>;
+
+    async fn request_account_access(
+        &self,
+        address: Address,
+        page_args: PageArgs,
+    ) -> eyre::Result<Page<DesiredAccess>>;
+
+    async fn request_dataset_access(
+        &self,
+        dataset_id: DatasetID,
+        page_args: PageArgs,
+    ) -> eyre::Result<Option<DatasetAccessView>>;
 }
 
 pub async fn build(
@@ -17,8 +43,12 @@ pub async fn build(
     http_port: u16,
     metrics_reg: prometheus::Registry,
     state_requester: Arc<dyn StateRequester>,
+    event_log: Arc<dyn EventLog>,
+    audit_log: Arc<dyn AccessAuditLog>,
+    admin_api: AdminApi,
+    maybe_kamu_webhook_api: Option<KamuWebhookApi>,
 ) -> eyre::Result<(HttpServeFuture, SocketAddr)> {
-    let app = axum::Router::new()
+    let mut app = axum::Router::new()
         .route("/system/health", axum::routing::get(health_handler))
         .route(
             "/system/metrics",
@@ -28,9 +58,60 @@ pub async fn build(
             "/system/state",
             axum::routing::get(axum::routing::get(state_handler)),
         )
+        .route(
+            "/system/access-changes",
+            axum::routing::get(access_changes_handler),
+        )
+        .route(
+            "/system/access-changes/export",
+            axum::routing::get(access_changes_export_handler),
+        )
         .fallback(observability::axum::unknown_fallback_handler)
         .layer(axum::extract::Extension(metrics_reg))
-        .layer(axum::extract::Extension(state_requester));
+        .layer(axum::extract::Extension(state_requester.clone()))
+        .layer(axum::extract::Extension(audit_log));
+
+    // NOTE: Registered even without a token, so that requests are rejected
+    //       with 403 instead of 404.
+    let admin_router = axum::Router::new()
+        .route(
+            "/system/admin/resync",
+            axum::routing::post(admin_resync_handler),
+        )
+        .route(
+            "/system/admin/ocls/{ocl_id}/refresh",
+            axum::routing::post(admin_refresh_ocl_handler),
+        )
+        .route(
+            "/system/admin/chains/{chain_id}/multisigs/{address}/refresh",
+            axum::routing::post(admin_refresh_multisig_handler),
+        )
+        .route(
+            "/system/admin/chains/{chain_id}/rewind",
+            axum::routing::post(admin_rewind_indexing_handler),
+        )
+        .route("/system/events", axum::routing::get(events_handler))
+        .route(
+            "/system/admin/ocls/{ocl_id}",
+            axum::routing::get(admin_ocl_handler),
+        )
+        .route(
+            "/system/admin/accounts/{address}",
+            axum::routing::get(admin_account_access_handler),
+        )
+        .route(
+            "/system/admin/datasets/{dataset_id}",
+            axum::routing::get(admin_dataset_access_handler),
+        )
+        .layer(axum::extract::Extension(admin_api))
+        .layer(axum::extract::Extension(state_requester))
+        .layer(axum::extract::Extension(event_log));
+
+    app = app.merge(admin_router);
+
+    if let Some(kamu_webhook_api) = maybe_kamu_webhook_api {
+        app = app.merge(kamu_webhook_router(kamu_webhook_api));
+    }
 
     let addr = SocketAddr::from((address, http_port));
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -53,3 +134,315 @@ pub async fn state_handler(
 
     Ok(axum::Json(state_json))
 }
+
+const DEFAULT_EVENTS_LIMIT: usize = 100;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct EventsArgs {
+    #[serde(default)]
+    from: u64,
+    limit: Option<usize>,
+}
+
+pub async fn events_handler(
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(args): axum::extract::Query<EventsArgs>,
+    axum::extract::Extension(admin_api): axum::extract::Extension<AdminApi>,
+    axum::extract::Extension(event_log): axum::extract::Extension<Arc<dyn EventLog>>,
+) -> Result<axum::Json<Vec<EventLogEntry>>, axum::http::StatusCode> {
+    admin_api.authorize(&headers)?;
+
+    let limit = args.limit.unwrap_or(DEFAULT_EVENTS_LIMIT);
+
+    let entries = event_log.read(args.from, limit).await.map_err(|e| {
+        tracing::error!(error = ?e, error_msg = %e, "Failed to read the event log");
+        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(axum::Json(entries))
+}
+
+const DEFAULT_ACCESS_CHANGES_LIMIT: usize = 100;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AccessChangesArgs {
+    #[serde(default)]
+    from: u64,
+    limit: Option<usize>,
+}
+
+pub async fn access_changes_handler(
+    axum::extract::Query(args): axum::extract::Query<AccessChangesArgs>,
+    axum::extract::Extension(audit_log): axum::extract::Extension<Arc<dyn AccessAuditLog>>,
+) -> Result<axum::Json<Vec<AccessAuditRecord>>, axum::http::StatusCode> {
+    let limit = args.limit.unwrap_or(DEFAULT_ACCESS_CHANGES_LIMIT);
+
+    let records = read_access_changes(audit_log.as_ref(), args.from, limit).await?;
+
+    Ok(axum::Json(records))
+}
+
+/// Returns all retained records (starting from `from`) as NDJSON
+pub async fn access_changes_export_handler(
+    axum::extract::Query(args): axum::extract::Query<AccessChangesArgs>,
+    axum::extract::Extension(audit_log): axum::extract::Extension<Arc<dyn AccessAuditLog>>,
+) -> Result<([(axum::http::HeaderName, &'static str); 1], String), axum::http::StatusCode> {
+    let limit = args.limit.unwrap_or(usize::MAX);
+
+    let records = read_access_changes(audit_log.as_ref(), args.from, limit).await?;
+    let ndjson = records_to_ndjson(&records).map_err(|e| {
+        tracing::error!(error = ?e, error_msg = %e, "Failed to export access changes");
+        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok((
+        [(axum::http::header::CONTENT_TYPE, "application/x-ndjson")],
+        ndjson,
+    ))
+}
+
+async fn read_access_changes(
+    audit_log: &dyn AccessAuditLog,
+    from: u64,
+    limit: usize,
+) -> Result<Vec<AccessAuditRecord>, axum::http::StatusCode> {
+    audit_log.read(from, limit).await.map_err(|e| {
+        tracing::error!(error = ?e, error_msg = %e, "Failed to read the audit log");
+        axum::http::StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Admin API: authenticated endpoints that enqueue commands for the main loop
+/// and query the access the bridge maintains
+#[derive(Clone)]
+pub struct AdminApi {
+    /// If not set, all requests are forbidden
+    pub maybe_token: Option<Secret>,
+    pub command_sender: AdminCommandSender,
+}
+
+impl AdminApi {
+    fn authorize(&self, headers: &axum::http::HeaderMap) -> Result<(), axum::http::StatusCode> {
+        let Some(token) = &self.maybe_token else {
+            return Err(axum::http::StatusCode::FORBIDDEN);
+        };
+
+        if !is_bearer_authorized(headers, token) {
+            return Err(axum::http::StatusCode::UNAUTHORIZED);
+        }
+
+        Ok(())
+    }
+
+    fn enqueue(
+        &self,
+        headers: &axum::http::HeaderMap,
+        command: AdminCommand,
+    ) -> axum::http::StatusCode {
+        if let Err(status) = self.authorize(headers) {
+            return status;
+        }
+
+        tracing::info!(?command, "Admin command is enqueued");
+
+        match self.command_sender.send(command) {
+            Ok(()) => axum::http::StatusCode::ACCEPTED,
+            Err(_) => axum::http::StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+pub async fn admin_resync_handler(
+    headers: axum::http::HeaderMap,
+    axum::extract::Extension(admin_api): axum::extract::Extension<AdminApi>,
+) -> axum::http::StatusCode {
+    admin_api.enqueue(&headers, AdminCommand::Resync)
+}
+
+pub async fn admin_refresh_ocl_handler(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ocl_id): axum::extract::Path<OclId>,
+    axum::extract::Extension(admin_api): axum::extract::Extension<AdminApi>,
+) -> axum::http::StatusCode {
+    admin_api.enqueue(&headers, AdminCommand::RefreshOcl { ocl_id })
+}
+
+pub async fn admin_refresh_multisig_handler(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path((chain_id, address)): axum::extract::Path<(u64, Address)>,
+    axum::extract::Extension(admin_api): axum::extract::Extension<AdminApi>,
+) -> axum::http::StatusCode {
+    admin_api.enqueue(
+        &headers,
+        AdminCommand::RefreshMultisig { chain_id, address },
+    )
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RewindIndexingArgs {
+    to_block: u64,
+}
+
+pub async fn admin_rewind_indexing_handler(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(chain_id): axum::extract::Path<u64>,
+    axum::extract::Extension(admin_api): axum::extract::Extension<AdminApi>,
+    axum::Json(args): axum::Json<RewindIndexingArgs>,
+) -> axum::http::StatusCode {
+    admin_api.enqueue(
+        &headers,
+        AdminCommand::RewindIndexing {
+            chain_id,
+            to_block: args.to_block,
+        },
+    )
+}
+
+pub async fn admin_ocl_handler(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ocl_id): axum::extract::Path<OclId>,
+    axum::extract::Extension(admin_api): axum::extract::Extension<AdminApi>,
+    axum::extract::Extension(state_requester): axum::extract::Extension<Arc<dyn StateRequester>>,
+) -> Result<axum::Json<OclView>, axum::http::StatusCode> {
+    admin_api.authorize(&headers)?;
+
+    let ocl_view = state_requester
+        .request_ocl(ocl_id)
+        .await
+        .ok_or(axum::http::StatusCode::NOT_FOUND)?;
+
+    Ok(axum::Json(ocl_view))
+}
+
+pub async fn admin_account_access_handler(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(address): axum::extract::Path<Address>,
+    axum::extract::Query(page_args): axum::extract::Query<PageArgs>,
+    axum::extract::Extension(admin_api): axum::extract::Extension<AdminApi>,
+    axum::extract::Extension(state_requester): axum::extract::Extension<Arc<dyn StateRequester>>,
+) -> Result<axum::Json<Page<DesiredAccess>>, axum::http::StatusCode> {
+    admin_api.authorize(&headers)?;
+
+    let account_access = state_requester
+        .request_account_access(address, page_args)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, error_msg = %e, "Failed to query the account access");
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(axum::Json(account_access))
+}
+
+pub async fn admin_dataset_access_handler(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(dataset_id): axum::extract::Path<DatasetID>,
+    axum::extract::Query(page_args): axum::extract::Query<PageArgs>,
+    axum::extract::Extension(admin_api): axum::extract::Extension<AdminApi>,
+    axum::extract::Extension(state_requester): axum::extract::Extension<Arc<dyn StateRequester>>,
+) -> Result<axum::Json<DatasetAccessView>, axum::http::StatusCode> {
+    admin_api.authorize(&headers)?;
+
+    let dataset_access = state_requester
+        .request_dataset_access(dataset_id, page_args)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, error_msg = %e, "Failed to query the dataset access");
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(axum::http::StatusCode::NOT_FOUND)?;
+
+    Ok(axum::Json(dataset_access))
+}
+
+/// Receiver of Kamu Node webhooks (`DATASET.REF.UPDATED` events). Kamu Node
+/// can't send custom headers, so the token is passed in the target URL
+/// (`?token=<token>`) and is redacted whenever the URL is logged.
+#[derive(Clone)]
+pub struct KamuWebhookApi {
+    pub token: Secret,
+    /// Notifications are coalesced until the main loop loads the projects
+    pub projects_loading_requested: Arc<tokio::sync::Notify>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct KamuWebhookArgs {
+    token: Option<String>,
+}
+
+pub fn kamu_webhook_router(kamu_webhook_api: KamuWebhookApi) -> axum::Router {
+    axum::Router::new()
+        .route(
+            "/system/webhooks/kamu",
+            axum::routing::post(kamu_webhook_handler),
+        )
+        .layer(axum::extract::Extension(kamu_webhook_api))
+}
+
+/// Only the dataset is needed: changes are detected by the dataset heads
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetUpdatedWebhookPayload {
+    dataset_id: DatasetID,
+}
+
+pub async fn kamu_webhook_handler(
+    uri: axum::http::Uri,
+    axum::extract::Query(args): axum::extract::Query<KamuWebhookArgs>,
+    axum::extract::Extension(kamu_webhook_api): axum::extract::Extension<KamuWebhookApi>,
+    axum::Json(payload): axum::Json<DatasetUpdatedWebhookPayload>,
+) -> axum::http::StatusCode {
+    let is_authorized = args.token.is_some_and(|token| {
+        bool::from(
+            token
+                .as_bytes()
+                .ct_eq(kamu_webhook_api.token.expose().as_bytes()),
+        )
+    });
+    if !is_authorized {
+        tracing::warn!(uri = %redact_query_token(&uri), "Kamu Node webhook with a wrong token is rejected");
+        return axum::http::StatusCode::UNAUTHORIZED;
+    }
+
+    tracing::debug!(dataset_id = %payload.dataset_id, "Kamu Node webhook is received");
+
+    // NOTE: Changed datasets are detected by their heads during the loading.
+    kamu_webhook_api.projects_loading_requested.notify_one();
+
+    axum::http::StatusCode::ACCEPTED
+}
+
+/// Replaces the value of the `token` query parameter, so that the URI can be
+/// logged
+pub fn redact_query_token(uri: &axum::http::Uri) -> String {
+    let Some(query) = uri.query() else {
+        return uri.path().to_string();
+    };
+
+    let redacted_query = query
+        .split('&')
+        .map(|parameter| match parameter.split_once('=') {
+            Some(("token", _)) => "token=<redacted>",
+            _ => parameter,
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    format!("{}?{redacted_query}", uri.path())
+}
+
+/// Checks the `Authorization: Bearer <token>` header. The token is compared in
+/// constant time, so that it can't be guessed by timing the responses.
+fn is_bearer_authorized(headers: &axum::http::HeaderMap, token: &Secret) -> bool {
+    let expected_authorization = format!("Bearer {}", token.expose());
+
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .is_some_and(|authorization| {
+            bool::from(
+                authorization
+                    .as_bytes()
+                    .ct_eq(expected_authorization.as_bytes()),
+            )
+        })
+}
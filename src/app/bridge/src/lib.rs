@@ -1,6 +1,20 @@
+pub mod access;
+pub mod access_changes_dataset;
+pub mod access_policy;
+pub mod access_query;
+pub mod admin_command;
 pub mod app;
+pub mod audit_log;
 pub mod cli;
 pub mod config;
+pub mod config_watcher;
+pub mod dataset_heads;
+pub mod events;
 pub mod http_server;
 pub mod metrics;
+pub mod ocl_id_filter_file;
+pub mod operation_planner;
 pub mod prelude;
+pub mod reconciliation;
+pub mod state;
+pub mod state_store;
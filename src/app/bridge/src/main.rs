@@ -3,11 +3,21 @@ use std::sync::Arc;
 use alloy::providers::fillers::ChainIdFiller;
 use alloy::providers::{DynProvider, Provider};
 use clap::Parser as _;
+use kamu_molecule_bridge::audit_log::{AccessAuditLog, FileAccessAuditLog, InMemoryAccessAuditLog};
 use kamu_molecule_bridge::cli;
+use kamu_molecule_bridge::config::SafeOwnersResolver;
+use kamu_molecule_bridge::config_watcher::watch_config;
+use kamu_molecule_bridge::events::{EventLog, FileEventLog, InMemoryEventLog};
 use kamu_molecule_bridge::metrics::BridgeMetrics;
 use kamu_molecule_bridge::prelude::*;
+use kamu_molecule_bridge::state_store::{
+    AppStateStore, FileAppStateStore, ReadOnlyAppStateStore, app_state_fingerprint,
+};
 use kamu_node_api_client::KamuNodeApiClientImpl;
-use multisig_safe_wallet::services::SafeWalletApiService;
+use multisig::services::{MultisigProvider, MultisigProviderRegistry, MultisigResolver};
+use multisig_safe_wallet::services::{
+    SafeContractCallResolver, SafeMultisigProvider, SafeOnChainResolver, SafeWalletApiService,
+};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const BINARY_NAME: &str = env!("CARGO_PKG_NAME");
@@ -59,24 +69,41 @@ async fn main_async(config: Config, args: cli::Cli) -> eyre::Result<()> {
 }
 
 async fn main_app(config: Config, args: cli::Cli) -> eyre::Result<()> {
-    let (metrics_registry, metrics) = init_metrics(&config)?;
+    let (metrics_registry, metrics) = init_metrics()?;
 
-    let rpc_client = build_rpc_client(&config, &metrics).await?;
+    let chain_configs = config.chain_configs()?;
+    let access_policies = config.access_policies(&chain_configs)?;
+    let ocl_id_filter = config.load_ocl_id_filter()?;
 
-    let safe_wallet_api_service = Arc::new(SafeWalletApiService::new_from_chain_id(
-        config.chain_id,
-        rpc_client.clone(),
-    )?);
+    let mut chain_sources = Vec::with_capacity(chain_configs.len());
+    for chain_config in &chain_configs {
+        chain_sources
+            .push(build_chain_source(chain_config, config.safe_owners_resolver, &metrics).await?);
+    }
+
+    let dry_run = is_dry_run(&args);
+    let kamu_node_api_client = build_kamu_node_client(&config, dry_run, &metrics);
+
+    let maybe_state_store = build_state_store(&config, &chain_configs, dry_run);
 
-    let kamu_node_api_client = build_kamu_node_client(&config, &args, &metrics);
+    let event_log = build_event_log(&config, &chain_configs, &args).await?;
+
+    let audit_log = build_audit_log(&config).await?;
 
     tracing::info!(version = VERSION, ?config, ?args, "Running {BINARY_NAME}");
 
+    // NOTE: The file is watched along with the config.
+    let ocl_id_filter_file_path = config.ocl_id_filter_file_path.clone();
     let mut app = App::new(
         config,
-        rpc_client,
-        safe_wallet_api_service,
+        chain_sources,
+        access_policies,
+        ocl_id_filter,
         kamu_node_api_client,
+        dry_run,
+        maybe_state_store,
+        event_log,
+        audit_log,
         metrics,
         metrics_registry,
     );
@@ -84,6 +111,11 @@ async fn main_app(config: Config, args: cli::Cli) -> eyre::Result<()> {
     match args.command {
         cli::Command::Run(cli::RunArgs { .. }) => {
             let shutdown_requested = trap_signals();
+            tokio::spawn(watch_config(
+                args.config.clone(),
+                ocl_id_filter_file_path,
+                app.reloaded_config_sender(),
+            ));
             app.run(shutdown_requested).await
         }
         cli::Command::State(cli::StateArgs {}) => {
@@ -91,10 +123,71 @@ async fn main_app(config: Config, args: cli::Cli) -> eyre::Result<()> {
             serde_json::to_writer(std::io::stdout(), &state)?;
             Ok(())
         }
+        cli::Command::Reconcile(cli::ReconcileArgs { .. }) => {
+            let operations = app.reconcile().await?;
+            serde_json::to_writer(std::io::stdout(), &operations)?;
+            Ok(())
+        }
     }
 }
 
-async fn build_rpc_client(config: &Config, metrics: &BridgeMetrics) -> eyre::Result<DynProvider> {
+async fn build_chain_source(
+    chain_config: &ChainConfig,
+    safe_owners_resolver: SafeOwnersResolver,
+    metrics: &BridgeMetrics,
+) -> eyre::Result<ChainSource> {
+    let rpc_client = build_rpc_client(chain_config, metrics).await?;
+
+    let safe_resolver: Arc<dyn MultisigResolver> = match safe_owners_resolver {
+        SafeOwnersResolver::Logs => {
+            // NOTE: The Safe Transaction Service API is only needed for Safes before v1.3.0.
+            let maybe_legacy_resolver: Option<Arc<dyn MultisigResolver>> =
+                match SafeWalletApiService::new_from_chain_id(
+                    chain_config.chain_id,
+                    rpc_client.clone(),
+                ) {
+                    Ok(safe_wallet_api_service) => Some(Arc::new(safe_wallet_api_service)),
+                    Err(e) => {
+                        tracing::warn!(
+                            chain_id = chain_config.chain_id,
+                            error = ?e,
+                            error_msg = %e,
+                            "Safe Transaction Service API is not available, Safes before v1.3.0 will not be resolved"
+                        );
+                        None
+                    }
+                };
+
+            Arc::new(SafeOnChainResolver::new(
+                rpc_client.clone(),
+                maybe_legacy_resolver,
+            ))
+        }
+        SafeOwnersResolver::ContractCall => {
+            Arc::new(SafeContractCallResolver::new(rpc_client.clone()))
+        }
+        SafeOwnersResolver::Api => Arc::new(SafeWalletApiService::new_from_chain_id(
+            chain_config.chain_id,
+            rpc_client.clone(),
+        )?),
+    };
+
+    // NOTE: Other kinds of smart accounts are supported by adding their providers here.
+    let safe_provider: Arc<dyn MultisigProvider> =
+        Arc::new(SafeMultisigProvider::new(safe_resolver));
+    let multisig_registry = Arc::new(MultisigProviderRegistry::new(vec![safe_provider]));
+
+    Ok(ChainSource {
+        config: chain_config.clone(),
+        rpc_client,
+        multisig_registry,
+    })
+}
+
+async fn build_rpc_client(
+    chain_config: &ChainConfig,
+    metrics: &BridgeMetrics,
+) -> eyre::Result<DynProvider> {
     let retry_backoff_layer = {
         let retry_count = 3;
         let initial_backoff_ms = 1000;
@@ -106,14 +199,20 @@ async fn build_rpc_client(config: &Config, metrics: &BridgeMetrics) -> eyre::Res
         )
     };
 
+    let chain_id_label = chain_config.chain_id.to_string();
+
     let client = alloy::rpc::client::ClientBuilder::default()
         .layer(alloy_ext::metrics::MetricsLayer::new(
-            metrics.evm_rpc_requests_num_total.clone(),
-            metrics.evm_rpc_errors_num_total.clone(),
+            metrics
+                .evm_rpc_requests_num_total
+                .with_label_values(&[chain_id_label.as_str()]),
+            metrics
+                .evm_rpc_errors_num_total
+                .with_label_values(&[chain_id_label.as_str()]),
         ))
         .layer(alloy_ext::tracing::TracingLayer)
         .layer(retry_backoff_layer)
-        .connect(&config.rpc_url)
+        .connect(&chain_config.rpc_url)
         .await?;
 
     let provider = alloy::providers::ProviderBuilder::new()
@@ -126,10 +225,10 @@ async fn build_rpc_client(config: &Config, metrics: &BridgeMetrics) -> eyre::Res
 
     // Check that we are looking at the right chain
     let actual_chain_id = provider.get_chain_id().await?;
-    if actual_chain_id != config.chain_id {
+    if actual_chain_id != chain_config.chain_id {
         eyre::bail!(
             "Expected to communicate with chain ID '{}' but RPC returned '{actual_chain_id}' instead",
-            config.chain_id,
+            chain_config.chain_id,
         );
     }
 
@@ -138,21 +237,86 @@ async fn build_rpc_client(config: &Config, metrics: &BridgeMetrics) -> eyre::Res
 
 fn build_kamu_node_client(
     config: &Config,
-    args: &cli::Cli,
+    dry_run: bool,
     metrics: &BridgeMetrics,
 ) -> Arc<KamuNodeApiClientImpl> {
-    let dry_run = matches!(args.command, cli::Command::Run(cli::RunArgs { dry_run }) if dry_run);
-
     Arc::new(KamuNodeApiClientImpl::new(
         config.kamu_node_gql_api_endpoint.clone(),
-        config.kamu_node_token.clone(),
+        config.kamu_node_token.expose().to_string(),
         config.molecule_projects_dataset_alias.clone(),
         metrics.kamu_gql_requests_num_total.clone(),
         metrics.kamu_gql_errors_num_total.clone(),
+        metrics.unknown_molecule_access_levels_num_total.clone(),
         dry_run,
     ))
 }
 
+fn is_dry_run(args: &cli::Cli) -> bool {
+    match args.command {
+        cli::Command::Run(cli::RunArgs { dry_run })
+        | cli::Command::Reconcile(cli::ReconcileArgs { dry_run }) => dry_run,
+        cli::Command::State(_) => false,
+    }
+}
+
+fn build_state_store(
+    config: &Config,
+    chain_configs: &[ChainConfig],
+    dry_run: bool,
+) -> Option<Arc<dyn AppStateStore>> {
+    let state_file_path = config.state_file_path.clone()?;
+
+    let state_store: Arc<dyn AppStateStore> = Arc::new(FileAppStateStore::new(
+        state_file_path,
+        app_state_fingerprint(config, chain_configs),
+    ));
+
+    // NOTE: Nothing is applied in dry-run mode, so its state must not be resumed from
+    //       (e.g. pending access changes are drained without being applied).
+    if dry_run {
+        tracing::warn!("State is not persisted in dry-run mode");
+        return Some(Arc::new(ReadOnlyAppStateStore::new(state_store)));
+    }
+
+    Some(state_store)
+}
+
+async fn build_event_log(
+    config: &Config,
+    chain_configs: &[ChainConfig],
+    args: &cli::Cli,
+) -> eyre::Result<Arc<dyn EventLog>> {
+    let max_entries = config.event_log_max_entries;
+
+    // NOTE: Only the running bridge writes the log: other commands (and dry-run mode)
+    //       would otherwise record events that the stored state doesn't reflect.
+    let is_writer = matches!(args.command, cli::Command::Run(_)) && !is_dry_run(args);
+
+    match config.event_log_file_path.clone() {
+        Some(event_log_file_path) if is_writer => Ok(Arc::new(
+            FileEventLog::open(
+                event_log_file_path,
+                app_state_fingerprint(config, chain_configs),
+                max_entries,
+            )
+            .await?,
+        )),
+        _ => Ok(Arc::new(InMemoryEventLog::new(max_entries))),
+    }
+}
+
+async fn build_audit_log(config: &Config) -> eyre::Result<Arc<dyn AccessAuditLog>> {
+    let max_records = config.audit_log_max_records;
+
+    if let Some(audit_log_file_path) = config.audit_log_file_path.clone() {
+        Ok(Arc::new(
+            FileAccessAuditLog::open(audit_log_file_path, max_records).await?,
+        ))
+    } else {
+        Ok(Arc::new(InMemoryAccessAuditLog::new(max_records)))
+    }
+}
+
 fn init_error_reporting() -> eyre::Result<()> {
     use observability::config::Mode;
 
@@ -194,8 +358,8 @@ fn init_observability() -> observability::init::Guard {
     guard
 }
 
-fn init_metrics(config: &Config) -> eyre::Result<(prometheus::Registry, BridgeMetrics)> {
-    let metrics = BridgeMetrics::new(config.chain_id);
+fn init_metrics() -> eyre::Result<(prometheus::Registry, BridgeMetrics)> {
+    let metrics = BridgeMetrics::new();
 
     let metrics_registry =
         prometheus::Registry::new_custom(Some("kamu_molecule_bridge".into()), None).unwrap();
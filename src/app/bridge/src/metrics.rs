@@ -1,29 +1,32 @@
 pub struct BridgeMetrics {
-    pub evm_rpc_requests_num_total: prometheus::IntCounter,
-    pub evm_rpc_errors_num_total: prometheus::IntCounter,
+    pub evm_rpc_requests_num_total: prometheus::IntCounterVec,
+    pub evm_rpc_errors_num_total: prometheus::IntCounterVec,
     pub kamu_gql_requests_num_total: prometheus::IntCounter,
     pub kamu_gql_errors_num_total: prometheus::IntCounter,
+    pub access_operations_planned_num_total: prometheus::IntCounter,
+    pub access_operations_sent_num_total: prometheus::IntCounter,
+    pub unknown_molecule_access_levels_num_total: prometheus::IntCounterVec,
 }
 
 impl BridgeMetrics {
-    pub fn new(chain_id: u64) -> Self {
+    pub fn new() -> Self {
         use prometheus::*;
 
         Self {
-            evm_rpc_requests_num_total: IntCounter::with_opts(
+            evm_rpc_requests_num_total: IntCounterVec::new(
                 Opts::new(
                     "evm_rpc_requests_num_total",
                     "Number of EVM node RPC requests executed",
-                )
-                .const_label("chain_id", chain_id.to_string()),
+                ),
+                &["chain_id"],
             )
             .unwrap(),
-            evm_rpc_errors_num_total: IntCounter::with_opts(
+            evm_rpc_errors_num_total: IntCounterVec::new(
                 Opts::new(
                     "evm_rpc_errors_num_total",
                     "Number of EVM node RPC requests that resulted in an error",
-                )
-                .const_label("chain_id", chain_id.to_string()),
+                ),
+                &["chain_id"],
             )
             .unwrap(),
             kamu_gql_requests_num_total: IntCounter::with_opts(Opts::new(
@@ -36,6 +39,24 @@ impl BridgeMetrics {
                 "Number of GQL requests executed on Kamu Node that resulted in an error",
             ))
             .unwrap(),
+            access_operations_planned_num_total: IntCounter::with_opts(Opts::new(
+                "access_operations_planned_num_total",
+                "Number of dataset role operations computed by the bridge",
+            ))
+            .unwrap(),
+            access_operations_sent_num_total: IntCounter::with_opts(Opts::new(
+                "access_operations_sent_num_total",
+                "Number of dataset role operations sent to Kamu Node after deduplication",
+            ))
+            .unwrap(),
+            unknown_molecule_access_levels_num_total: IntCounterVec::new(
+                Opts::new(
+                    "unknown_molecule_access_levels_num_total",
+                    "Number of times an unknown molecule_access_level of a versioned file was read",
+                ),
+                &["molecule_access_level"],
+            )
+            .unwrap(),
         }
     }
 
@@ -44,6 +65,11 @@ impl BridgeMetrics {
         reg.register(Box::new(self.evm_rpc_errors_num_total.clone()))?;
         reg.register(Box::new(self.kamu_gql_requests_num_total.clone()))?;
         reg.register(Box::new(self.kamu_gql_errors_num_total.clone()))?;
+        reg.register(Box::new(self.access_operations_planned_num_total.clone()))?;
+        reg.register(Box::new(self.access_operations_sent_num_total.clone()))?;
+        reg.register(Box::new(
+            self.unknown_molecule_access_levels_num_total.clone(),
+        ))?;
         Ok(())
     }
 }
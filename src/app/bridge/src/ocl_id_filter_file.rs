@@ -0,0 +1,33 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use kamu_node_api_client::OclIdFilter;
+use molecule_ocl::entities::OclId;
+
+/// Contents of the file set by `ocl_id_filter_file_path`
+#[derive(confique::Config, Debug)]
+pub struct OclIdFilterFile {
+    /// List of OCL ids that should be ignored
+    pub ignore_ocl_ids: Option<HashSet<OclId>>,
+    /// List of OCL ids that should only be loaded
+    pub only_ocl_ids: Option<HashSet<OclId>>,
+}
+
+impl OclIdFilterFile {
+    pub fn load(path: &Path) -> eyre::Result<OclIdFilter> {
+        // NOTE: A missing file is an error: it must not silently allow all projects.
+        if !path.try_exists()? {
+            eyre::bail!("File does not exist");
+        }
+
+        let OclIdFilterFile {
+            ignore_ocl_ids,
+            only_ocl_ids,
+        } = confique::Config::builder().file(path).load()?;
+
+        Ok(OclIdFilter {
+            ignore: ignore_ocl_ids.unwrap_or_default(),
+            only: only_ocl_ids,
+        })
+    }
+}
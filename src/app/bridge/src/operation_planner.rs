@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use kamu_node_api_client::{
+    AccountDatasetRelationOperation, AccountID, DatasetID, DatasetRoleOperation,
+};
+
+/// Operations last applied by the bridge per dataset and (lowercased) account
+pub type AppliedRolesMap = HashMap<DatasetID, HashMap<AccountID, DatasetRoleOperation>>;
+
+/// Minimizes a batch of operations:
+/// - operations for the same account and dataset are collapsed into the latest one
+///   (e.g. a grant followed by a revoke results in the revoke only);
+/// - operations equal to the last applied ones are dropped as no-ops.
+///
+/// Pairs that were never applied by the bridge are always kept, as their
+/// actual roles are unknown.
+pub fn plan_operations(
+    applied_roles: &AppliedRolesMap,
+    operations: &[AccountDatasetRelationOperation],
+) -> Vec<AccountDatasetRelationOperation> {
+    collapse_operations(operations)
+        .into_iter()
+        .filter(|operation| {
+            let maybe_applied_operation = applied_roles
+                .get(&operation.dataset_id)
+                .and_then(|dataset_roles| dataset_roles.get(&operation.account_id.to_lowercase()));
+
+            maybe_applied_operation != Some(&operation.operation)
+        })
+        .collect()
+}
+
+/// Collapses operations for the same account and dataset into the latest one
+pub fn collapse_operations(
+    operations: &[AccountDatasetRelationOperation],
+) -> Vec<AccountDatasetRelationOperation> {
+    // NOTE: Addresses may differ in case (EIP-55 checksum), so they are compared case-insensitively.
+    let latest_operations = operations
+        .iter()
+        .map(|operation| {
+            (
+                (&operation.dataset_id, operation.account_id.to_lowercase()),
+                operation,
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut collapsed_operations = latest_operations.into_values().cloned().collect::<Vec<_>>();
+
+    // NOTE: Stable order for readable output
+    collapsed_operations
+        .sort_by(|a, b| (&a.dataset_id, &a.account_id).cmp(&(&b.dataset_id, &b.account_id)));
+
+    collapsed_operations
+}
+
+pub fn record_applied_operations(
+    applied_roles: &mut AppliedRolesMap,
+    operations: &[AccountDatasetRelationOperation],
+) {
+    for operation in operations {
+        applied_roles
+            .entry(operation.dataset_id.clone())
+            .or_default()
+            .insert(operation.account_id.to_lowercase(), operation.operation);
+    }
+}
+
+/// Forgets the roles applied for the datasets, so that the next operations
+/// for them are sent even if they look like no-ops
+pub fn forget_applied_roles<'a>(
+    applied_roles: &mut AppliedRolesMap,
+    dataset_ids: impl IntoIterator<Item = &'a DatasetID>,
+) {
+    for dataset_id in dataset_ids {
+        applied_roles.remove(dataset_id);
+    }
+}
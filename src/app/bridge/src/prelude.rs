@@ -1,2 +1,2 @@
-pub use crate::app::App;
-pub use crate::config::Config;
+pub use crate::app::{App, ChainSource};
+pub use crate::config::{ChainConfig, Config};
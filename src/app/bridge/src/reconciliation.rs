@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use kamu_node_api_client::{
+    AccountDatasetRelationOperation, AccountID, DatasetAccessRole, DatasetAccountRolesMap,
+    DatasetRoleOperation,
+};
+
+/// Only wallet accounts are managed by the bridge. Roles of other accounts
+/// (e.g. project accounts) are never revoked.
+const MANAGED_ACCOUNT_ID_PREFIX: &str = "did:pkh:";
+
+/// Computes operations that bring the actual roles in line with the desired
+/// ones.
+///
+/// Only datasets present in `desired_roles` are considered: a dataset without
+/// desired roles (e.g. a removed file) is expected to have no managed accounts
+/// at all.
+pub fn reconcile_dataset_roles(
+    desired_roles: &DatasetAccountRolesMap,
+    actual_roles: &DatasetAccountRolesMap,
+) -> Vec<AccountDatasetRelationOperation> {
+    let mut operations = Vec::new();
+
+    let no_roles = HashMap::new();
+
+    for (dataset_id, desired_dataset_roles) in desired_roles {
+        let actual_dataset_roles =
+            normalize_account_ids(actual_roles.get(dataset_id).unwrap_or(&no_roles));
+
+        // Missing or different roles
+        for (account_id, desired_role) in desired_dataset_roles {
+            let actual_role = actual_dataset_roles
+                .get(&account_id.to_lowercase())
+                .map(|(_, role)| *role);
+
+            if actual_role != Some(*desired_role) {
+                operations.push(AccountDatasetRelationOperation {
+                    account_id: account_id.clone(),
+                    operation: DatasetRoleOperation::Set(*desired_role),
+                    dataset_id: dataset_id.clone(),
+                });
+            }
+        }
+
+        // Stray roles
+        let desired_dataset_roles = normalize_account_ids(desired_dataset_roles);
+        for (normalized_account_id, (account_id, _)) in &actual_dataset_roles {
+            if !is_managed_account(account_id)
+                || desired_dataset_roles.contains_key(normalized_account_id)
+            {
+                continue;
+            }
+
+            operations.push(AccountDatasetRelationOperation::revoke_access(
+                (*account_id).clone(),
+                dataset_id.clone(),
+            ));
+        }
+    }
+
+    // NOTE: Stable order for readable output
+    operations.sort_by(|a, b| (&a.dataset_id, &a.account_id).cmp(&(&b.dataset_id, &b.account_id)));
+
+    operations
+}
+
+fn is_managed_account(account_id: &AccountID) -> bool {
+    account_id.starts_with(MANAGED_ACCOUNT_ID_PREFIX)
+}
+
+// NOTE: Addresses may differ in case (EIP-55 checksum), so they are compared case-insensitively.
+fn normalize_account_ids(
+    roles: &HashMap<AccountID, DatasetAccessRole>,
+) -> HashMap<String, (&AccountID, DatasetAccessRole)> {
+    roles
+        .iter()
+        .map(|(account_id, role)| (account_id.to_lowercase(), (account_id, *role)))
+        .collect()
+}
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use alloy::primitives::{Address, B256, U256};
+use chrono::{DateTime, Utc};
+use kamu_node_api_client::{
+    AccountDatasetRelationOperation, DatasetID, DidPhk, MoleculeAccessLevel, MoleculeProjectEntry,
+    OclIdFilter, VersionedFileEntry,
+};
+use molecule_ocl::entities::{OclId, OclOwnershipProjection, OclOwnershipProjectionMap};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::access::DesiredAccess;
+use crate::access_policy::SharedAccessPolicies;
+use crate::access_query::{
+    DatasetAccessView, OclView, Page, PageArgs, get_account_access, get_dataset_access,
+    get_ocl_view,
+};
+use crate::audit_log::AccessChangeCause;
+use crate::dataset_heads::DatasetHeads;
+use crate::http_server::StateRequester;
+use crate::operation_planner::AppliedRolesMap;
+
+/// State of the bridge derived as a projection over the event log
+/// (see [`crate::events::AppStateProjector`]).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AppState {
+    pub molecule_projects_dataset_offset: Option<u64>,
+    pub molecule_projects_last_requested_at: Option<DateTime<Utc>>,
+    /// Filter the projects were last loaded with
+    #[serde(default)]
+    pub molecule_projects_ocl_id_filter: OclIdFilter,
+    /// Heads of the datasets as of the last loading of projects
+    #[serde(default)]
+    pub scanned_dataset_heads: DatasetHeads,
+    #[serde(default)]
+    pub last_reconciled_at: Option<DateTime<Utc>>,
+    /// ID of the next audit log record to push into the Kamu Node dataset
+    #[serde(default)]
+    pub audit_log_next_pushed_record_id: u64,
+    /// ID of the next audit log record to publish into the access changes
+    /// dataset in Kamu Node
+    #[serde(default)]
+    pub access_changes_next_pushed_record_id: u64,
+    /// Sequence of the next event log entry: the state is a projection of the
+    /// log entries before it
+    #[serde(default)]
+    pub event_log_next_sequence: u64,
+
+    /// On-chain state per chain ID
+    pub chains: BTreeMap<u64, ChainState>,
+    pub off_chain_ocl_project_map: HashMap<OclId, OffChainMoleculeProjectProjection>,
+
+    /// Journal of access changes that are computed, but not applied to Kamu
+    /// Node yet, oldest first. Persisted along with the indexed state, so that
+    /// no change is lost if applying fails.
+    #[serde(default)]
+    pub pending_access_changes: VecDeque<PendingAccessChange>,
+
+    /// Role operations last applied by the bridge, used to drop no-op
+    /// operations
+    #[serde(default)]
+    pub applied_roles: AppliedRolesMap,
+}
+
+impl AppState {
+    /// Returns the on-chain ownership of the OCL on each chain it is present on
+    pub fn ocl_ownership_projections(
+        &self,
+        ocl_id: &OclId,
+    ) -> impl Iterator<Item = (u64, &OclOwnershipProjection)> {
+        self.chains.iter().filter_map(|(chain_id, chain_state)| {
+            chain_state
+                .on_chain_ocl_ownership_projection_map
+                .get(ocl_id)
+                .map(|ownership_projection| (*chain_id, ownership_projection))
+        })
+    }
+}
+
+/// State of a single chain: each chain is indexed (and rolled back in case of
+/// a reorg) independently of the others.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ChainState {
+    pub on_chain_ocl_ownership_projection_map: OclOwnershipProjectionMap,
+    pub latest_indexed_block_number: u64,
+    /// Indexed block ranges above the finalized block, oldest first.
+    /// Empty when only finalized blocks are indexed.
+    pub unfinalized_checkpoints: Vec<UnfinalizedCheckpoint>,
+
+    pub multisig: HashMap<Address, Option<MultisigState>>,
+    /// Only OCLs with a configured IP-token
+    pub ocl_holders: HashMap<OclId, OclHoldersState>,
+}
+
+impl ChainState {
+    pub fn new(latest_indexed_block_number: u64) -> Self {
+        Self {
+            latest_indexed_block_number,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the index of the oldest checkpoint that contains blocks above
+    /// the fork point, i.e. the one to roll back to.
+    pub fn first_orphaned_checkpoint_index(&self, fork_block_number: u64) -> Option<usize> {
+        self.unfinalized_checkpoints
+            .iter()
+            .position(|checkpoint| checkpoint.to_block > fork_block_number)
+    }
+}
+
+/// Indexed range of non-finalized blocks along with the on-chain state
+/// before it, so that the range can be rolled back in case of a reorg.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnfinalizedCheckpoint {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub to_block_hash: B256,
+    pub ocl_ownership_projection_map: OclOwnershipProjectionMap,
+    pub multisig: HashMap<Address, Option<MultisigState>>,
+    #[serde(default)]
+    pub ocl_holders: HashMap<OclId, OclHoldersState>,
+}
+
+/// Batch of access changes to be applied to Kamu Node. All the steps are
+/// idempotent, so the batch can be safely applied again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingAccessChange {
+    pub reason: String,
+    pub cause: AccessChangeCause,
+    /// Wallet accounts to create before the operations are applied
+    pub accounts: Vec<DidPhk>,
+    pub operations: Vec<AccountDatasetRelationOperation>,
+    pub public_dataset_ids: Vec<DatasetID>,
+    pub private_dataset_ids: Vec<DatasetID>,
+}
+
+impl PendingAccessChange {
+    /// Datasets whose roles or visibility are changed
+    pub fn dataset_ids(&self) -> impl Iterator<Item = &DatasetID> {
+        self.operations
+            .iter()
+            .map(|operation| &operation.dataset_id)
+            .chain(&self.public_dataset_ids)
+            .chain(&self.private_dataset_ids)
+    }
+}
+
+/// State shared with the HTTP API along with the settings access is derived
+/// with
+pub struct SharedAppState {
+    pub state: Arc<RwLock<AppState>>,
+    pub access_policies: Arc<SharedAccessPolicies>,
+}
+
+#[async_trait::async_trait]
+impl StateRequester for SharedAppState {
+    async fn request_as_json(&self) -> Value {
+        let readable_state = self.state.read().await;
+        let mut state = serde_json::to_value(&*readable_state).unwrap();
+        // NOTE: Policies are part of the configuration, so they are not persisted with the state.
+        if let Value::Object(fields) = &mut state {
+            fields.insert(
+                "access_policies".to_string(),
+                serde_json::to_value(&*self.access_policies.get()).unwrap(),
+            );
+        }
+        state
+    }
+
+    async fn request_ocl(&self, ocl_id: OclId) -> Option<OclView> {
+        let readable_state = self.state.read().await;
+        get_ocl_view(&readable_state, &self.access_policies.get(), ocl_id)
+    }
+
+    async fn request_account_access(
+        &self,
+        address: Address,
+        page_args: PageArgs,
+    ) -> eyre::Result<Page<DesiredAccess>> {
+        let readable_state = self.state.read().await;
+        get_account_access(
+            &readable_state,
+            &self.access_policies.get(),
+            address,
+            &page_args,
+        )
+    }
+
+    async fn request_dataset_access(
+        &self,
+        dataset_id: DatasetID,
+        page_args: PageArgs,
+    ) -> eyre::Result<Option<DatasetAccessView>> {
+        let readable_state = self.state.read().await;
+        get_dataset_access(
+            &readable_state,
+            &self.access_policies.get(),
+            &dataset_id,
+            &page_args,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigState {
+    pub current_owners: HashSet<Address>,
+    pub former_owners: HashSet<Address>,
+    /// Number of owner confirmations required for a transaction
+    pub threshold: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OclHoldersState {
+    /// Only non-zero balances
+    pub balances: HashMap<Address, U256>,
+    /// Addresses whose balance dropped to zero
+    pub former_holders: HashSet<Address>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OffChainMoleculeProjectProjection {
+    pub entry: MoleculeProjectEntry,
+    pub latest_data_room_offset: u64,
+    pub actual_files_map: HashMap<DatasetID, VersionedFileEntryWithMoleculeAccessLevel>,
+    pub removed_files_map: HashMap<DatasetID, VersionedFileEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionedFileEntryWithMoleculeAccessLevel {
+    pub entry: VersionedFileEntry,
+    pub molecule_access_level: MoleculeAccessLevel,
+}
@@ -0,0 +1,189 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use alloy::primitives::{B256, keccak256};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ChainConfig, Config};
+use crate::state::AppState;
+
+/// Increment when the layout of [`AppState`] changes in an incompatible way.
+/// Snapshots written with a different version are rejected, which forces a
+/// full resync.
+pub const APP_STATE_SNAPSHOT_FORMAT_VERSION: u32 = 4;
+
+#[async_trait::async_trait]
+pub trait AppStateStore: Send + Sync {
+    /// Returns `None` if there is no compatible snapshot to resume from
+    async fn load(&self) -> eyre::Result<Option<AppState>>;
+
+    async fn save(&self, state: &AppState) -> eyre::Result<()>;
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct AppStateSnapshotHeader {
+    format_version: u32,
+    /// Identifies the configuration the state was indexed with (chains,
+    /// contracts, etc.)
+    fingerprint: B256,
+    /// Hash of the serialized state that follows the header
+    checksum: B256,
+}
+
+/// Computes the fingerprint of the config parameters that the indexed state
+/// depends on. If any of them changes, the stored state cannot be reused.
+pub fn app_state_fingerprint(config: &Config, chain_configs: &[ChainConfig]) -> B256 {
+    let mut fingerprint_source = config.molecule_projects_dataset_alias.clone();
+
+    let mut chain_configs = chain_configs.iter().collect::<Vec<_>>();
+    chain_configs.sort_by_key(|chain_config| chain_config.chain_id);
+
+    for chain_config in chain_configs {
+        fingerprint_source.push_str(&format!(
+            ";{}:{}:{}",
+            chain_config.chain_id,
+            chain_config.labnft_contract_address,
+            chain_config.labnft_contract_birth_block,
+        ));
+
+        if !chain_config.ocl_holder_tokens.is_empty() {
+            let mut holder_tokens = chain_config
+                .ocl_holder_tokens
+                .iter()
+                .map(|(ocl_id, token_address)| format!("{ocl_id}={token_address}"))
+                .collect::<Vec<_>>();
+            // NOTE: Stable order regardless of the map iteration order
+            holder_tokens.sort();
+
+            fingerprint_source.push(':');
+            fingerprint_source.push_str(&holder_tokens.join(","));
+        }
+    }
+
+    keccak256(fingerprint_source)
+}
+
+/// Stores the state snapshot in a local file.
+///
+/// File layout: a single-line JSON header followed by the JSON-serialized
+/// state.
+pub struct FileAppStateStore {
+    path: PathBuf,
+    fingerprint: B256,
+}
+
+impl FileAppStateStore {
+    pub fn new(path: PathBuf, fingerprint: B256) -> Self {
+        Self { path, fingerprint }
+    }
+
+    fn parse_snapshot(&self, content: &[u8]) -> eyre::Result<Option<AppState>> {
+        let Some(header_end) = content.iter().position(|b| *b == b'\n') else {
+            eyre::bail!("Snapshot header is missing");
+        };
+        let (header_bytes, state_bytes) = (&content[..header_end], &content[header_end + 1..]);
+
+        let header: AppStateSnapshotHeader = serde_json::from_slice(header_bytes)?;
+
+        if header.format_version != APP_STATE_SNAPSHOT_FORMAT_VERSION {
+            tracing::warn!(
+                snapshot_format_version = header.format_version,
+                expected_format_version = APP_STATE_SNAPSHOT_FORMAT_VERSION,
+                "Ignore state snapshot: incompatible format version",
+            );
+            return Ok(None);
+        }
+        if header.fingerprint != self.fingerprint {
+            tracing::warn!(
+                snapshot_fingerprint = %header.fingerprint,
+                expected_fingerprint = %self.fingerprint,
+                "Ignore state snapshot: it was created with a different configuration",
+            );
+            return Ok(None);
+        }
+        let actual_checksum = keccak256(state_bytes);
+        if header.checksum != actual_checksum {
+            tracing::warn!(
+                snapshot_checksum = %header.checksum,
+                %actual_checksum,
+                "Ignore state snapshot: checksum mismatch",
+            );
+            return Ok(None);
+        }
+
+        let state = serde_json::from_slice(state_bytes)?;
+
+        Ok(Some(state))
+    }
+}
+
+#[async_trait::async_trait]
+impl AppStateStore for FileAppStateStore {
+    #[tracing::instrument(level = "info", skip_all, fields(path = %self.path.display()))]
+    async fn load(&self) -> eyre::Result<Option<AppState>> {
+        let content = match tokio::fs::read(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::info!("State snapshot not found");
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        match self.parse_snapshot(&content) {
+            Ok(maybe_state) => Ok(maybe_state),
+            Err(e) => {
+                // NOTE: A corrupted snapshot is not fatal: we can always resync from scratch.
+                tracing::warn!(error = ?e, error_msg = %e, "Ignore state snapshot: cannot parse");
+                Ok(None)
+            }
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %self.path.display()))]
+    async fn save(&self, state: &AppState) -> eyre::Result<()> {
+        let state_bytes = serde_json::to_vec(state)?;
+        let header = AppStateSnapshotHeader {
+            format_version: APP_STATE_SNAPSHOT_FORMAT_VERSION,
+            fingerprint: self.fingerprint,
+            checksum: keccak256(&state_bytes),
+        };
+
+        let mut content = serde_json::to_vec(&header)?;
+        content.push(b'\n');
+        content.extend(state_bytes);
+
+        // NOTE: Write to a temporary file first and then rename it, so that a crash
+        //       in the middle of writing doesn't leave a truncated snapshot behind.
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, content).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+
+        Ok(())
+    }
+}
+
+/// Restores the state, but never persists it: e.g. in dry-run mode, nothing is
+/// applied, so the pending access changes must not be dropped from the stored
+/// journal.
+pub struct ReadOnlyAppStateStore {
+    inner: Arc<dyn AppStateStore>,
+}
+
+impl ReadOnlyAppStateStore {
+    pub fn new(inner: Arc<dyn AppStateStore>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait::async_trait]
+impl AppStateStore for ReadOnlyAppStateStore {
+    async fn load(&self) -> eyre::Result<Option<AppState>> {
+        self.inner.load().await
+    }
+
+    async fn save(&self, _state: &AppState) -> eyre::Result<()> {
+        tracing::debug!("Skip persisting the state: the store is read-only");
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use alloy::primitives::{B256, b256};
+use chrono::Utc;
+use kamu_molecule_bridge::access_changes_dataset::{
+    AccessChangeOperation, AccessChangeRow, build_access_change_rows,
+    read_unpushed_audit_log_records,
+};
+use kamu_molecule_bridge::audit_log::{
+    AccessAuditLog, AccessAuditRecord, AccessChangeCause, AccessChangeResult,
+    InMemoryAccessAuditLog, NewAccessAuditRecord, OnChainTrigger,
+};
+use kamu_molecule_bridge::state::{AppState, OffChainMoleculeProjectProjection};
+use kamu_node_api_client::{
+    AccountDatasetRelationOperation, DatasetAccessRole, MoleculeProjectEntry, OperationType,
+};
+use molecule_ocl::entities::OclId;
+use pretty_assertions::assert_eq;
+
+const TX_HASH: B256 = b256!("0x00000000000000000000000000000000000000000000000000000000000000ff");
+const ACCOUNT_ID: &str = "did:pkh:eip155:1:0x1111111111111111111111111111111111111111";
+
+#[test]
+fn test_rows_of_ocl_change() {
+    let app_state = app_state();
+    let record = record(
+        AccessChangeCause::OclChanged {
+            ocl_id: ocl_id(),
+            on_chain: vec![
+                OnChainTrigger {
+                    chain_id: 1,
+                    block_number: 10,
+                    tx_hash: None,
+                },
+                OnChainTrigger {
+                    chain_id: 1,
+                    block_number: 12,
+                    tx_hash: Some(TX_HASH),
+                },
+            ],
+            off_chain: None,
+        },
+        AccessChangeResult::Applied {
+            message: "Success".to_string(),
+        },
+    );
+
+    let rows = build_access_change_rows(&app_state, &record);
+
+    assert_eq!(
+        vec![
+            AccessChangeRow {
+                change_id: 7,
+                event_time: record.recorded_at,
+                ocl_id: Some(ocl_id()),
+                symbol: Some("PROJECT".to_string()),
+                account_id: ACCOUNT_ID.to_string(),
+                dataset_id: "data-room".to_string(),
+                operation: AccessChangeOperation::Set,
+                role: Some(DatasetAccessRole::Maintainer),
+                reason: "Test".to_string(),
+                source_chain_id: Some(1),
+                source_block_number: Some(12),
+                source_tx_hash: Some(TX_HASH),
+                source_data_room_offset: None,
+            },
+            AccessChangeRow {
+                change_id: 7,
+                event_time: record.recorded_at,
+                ocl_id: Some(ocl_id()),
+                symbol: Some("PROJECT".to_string()),
+                account_id: ACCOUNT_ID.to_string(),
+                dataset_id: "unknown".to_string(),
+                operation: AccessChangeOperation::Unset,
+                role: None,
+                reason: "Test".to_string(),
+                source_chain_id: Some(1),
+                source_block_number: Some(12),
+                source_tx_hash: Some(TX_HASH),
+                source_data_room_offset: None,
+            },
+        ],
+        rows
+    );
+}
+
+#[test]
+fn test_rows_of_reconciliation_are_attributed_to_projects() {
+    let app_state = app_state();
+    let record = record(
+        AccessChangeCause::Reconciliation,
+        AccessChangeResult::Applied {
+            message: "Success".to_string(),
+        },
+    );
+
+    let rows = build_access_change_rows(&app_state, &record);
+
+    assert_eq!(
+        vec![(Some(ocl_id()), Some("PROJECT")), (None, None)],
+        rows.iter()
+            .map(|row| (row.ocl_id, row.symbol.as_deref()))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_failed_and_dry_run_changes_produce_no_rows() {
+    let app_state = app_state();
+    let record = record(
+        AccessChangeCause::OclFullUpdate { ocl_id: ocl_id() },
+        AccessChangeResult::Failed {
+            error: "Error".to_string(),
+        },
+    );
+
+    assert!(build_access_change_rows(&app_state, &record).is_empty());
+
+    let record = record(
+        AccessChangeCause::OclFullUpdate { ocl_id: ocl_id() },
+        AccessChangeResult::DryRun,
+    );
+
+    assert!(build_access_change_rows(&app_state, &record).is_empty());
+}
+
+#[tokio::test]
+async fn test_unpushed_records_are_read_after_cursor() {
+    let audit_log = InMemoryAccessAuditLog::new(10);
+    for _ in 0..3 {
+        audit_log.append(new_record()).await.unwrap();
+    }
+
+    let mut next_pushed_record_id = 1;
+    let records = read_unpushed_audit_log_records(&audit_log, &mut next_pushed_record_id, 10)
+        .await
+        .unwrap();
+
+    assert_eq!(1, next_pushed_record_id);
+    assert_eq!(vec![1, 2], record_ids(&records));
+}
+
+#[tokio::test]
+async fn test_unpushed_records_are_read_after_restart() {
+    // The cursor is restored from the state snapshot, while the in-memory log
+    // starts from scratch
+    let mut next_pushed_record_id = 5;
+    let audit_log = InMemoryAccessAuditLog::new(10);
+    for _ in 0..2 {
+        audit_log.append(new_record()).await.unwrap();
+    }
+
+    let records = read_unpushed_audit_log_records(&audit_log, &mut next_pushed_record_id, 10)
+        .await
+        .unwrap();
+
+    assert_eq!(0, next_pushed_record_id);
+    assert_eq!(vec![0, 1], record_ids(&records));
+}
+
+// Helpers
+
+fn ocl_id() -> OclId {
+    const RAW: &str = "0x0101000000000000000000a1117b215dcd666dd847cfa84721480d316440faa9";
+    OclId::from_str(RAW).unwrap()
+}
+
+fn record(cause: AccessChangeCause, result: AccessChangeResult) -> AccessAuditRecord {
+    AccessAuditRecord {
+        id: 7,
+        recorded_at: Utc::now(),
+        reason: "Test".to_string(),
+        cause,
+        operations: vec![
+            AccountDatasetRelationOperation::maintainer_access(
+                ACCOUNT_ID.to_string(),
+                "data-room".to_string(),
+            ),
+            AccountDatasetRelationOperation::revoke_access(
+                ACCOUNT_ID.to_string(),
+                "unknown".to_string(),
+            ),
+        ],
+        result,
+    }
+}
+
+fn new_record() -> NewAccessAuditRecord {
+    NewAccessAuditRecord {
+        reason: "Test".to_string(),
+        cause: AccessChangeCause::Reconciliation,
+        operations: Vec::new(),
+        result: AccessChangeResult::Applied {
+            message: "Success".to_string(),
+        },
+    }
+}
+
+fn record_ids(records: &[AccessAuditRecord]) -> Vec<u64> {
+    records.iter().map(|record| record.id).collect()
+}
+
+fn app_state() -> AppState {
+    let mut app_state = AppState::default();
+
+    app_state.off_chain_ocl_project_map.insert(
+        ocl_id(),
+        OffChainMoleculeProjectProjection {
+            entry: MoleculeProjectEntry {
+                offset: 0,
+                op: OperationType::Append,
+                ocl_id: ocl_id(),
+                symbol: "PROJECT".to_string(),
+                project_account_id: "project-account".to_string(),
+                data_room_dataset_id: "data-room".to_string(),
+                announcements_dataset_id: "announcements".to_string(),
+            },
+            latest_data_room_offset: 0,
+            actual_files_map: HashMap::new(),
+            removed_files_map: HashMap::new(),
+        },
+    );
+
+    app_state
+}
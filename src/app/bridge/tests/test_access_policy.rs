@@ -0,0 +1,88 @@
+use kamu_molecule_bridge::access_policy::{
+    AccessPolicy, DatasetKind, PrincipalKind, PrincipalRoles, UnknownAccessLevelFallback,
+};
+use kamu_node_api_client::{DatasetAccessRole, MoleculeAccessLevel};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn test_default_access_policy() {
+    let access_policy = AccessPolicy::default();
+
+    assert!(access_policy.validate().is_ok());
+
+    let public_file = DatasetKind::File(MoleculeAccessLevel::Public);
+    assert!(access_policy.is_public(&public_file));
+    assert_eq!(
+        Some(DatasetAccessRole::Reader),
+        access_policy
+            .roles(&public_file)
+            .role(PrincipalKind::Holder)
+    );
+
+    let admin_file = DatasetKind::File(MoleculeAccessLevel::Admin);
+    assert!(!access_policy.is_public(&admin_file));
+    assert_eq!(None, access_policy.roles(&admin_file).holder);
+    assert_eq!(
+        Some(DatasetAccessRole::Maintainer),
+        access_policy.roles(&admin_file).multisig_signer
+    );
+}
+
+#[test]
+fn test_access_policy_sections_keep_defaults() {
+    let access_policy: AccessPolicy = serde_json::from_value(serde_json::json!({
+        "files": {
+            "admin_2": { "owner": "Editor" },
+        },
+    }))
+    .unwrap();
+
+    assert_eq!(
+        PrincipalRoles {
+            owner: Some(DatasetAccessRole::Editor),
+            ..Default::default()
+        },
+        access_policy.files.admin_2
+    );
+    assert_eq!(AccessPolicy::default().data_room, access_policy.data_room);
+    assert_eq!(
+        AccessPolicy::default().files.holder,
+        access_policy.files.holder
+    );
+}
+
+#[test]
+fn test_unknown_access_level_fallback() {
+    let unknown_file = DatasetKind::File(MoleculeAccessLevel::Unknown("partners".to_string()));
+    let mut access_policy = AccessPolicy::default();
+
+    assert_eq!(
+        &access_policy.files.admin,
+        access_policy.roles(&unknown_file)
+    );
+
+    access_policy.files.unknown_fallback = UnknownAccessLevelFallback::Public;
+    assert!(access_policy.is_public(&unknown_file));
+
+    access_policy.files.unknown_fallback = UnknownAccessLevelFallback::Deny;
+    assert_eq!(&PrincipalRoles::NONE, access_policy.roles(&unknown_file));
+}
+
+#[test]
+fn test_access_policy_rejects_public_roles_other_than_reader() {
+    let mut access_policy = AccessPolicy::default();
+    access_policy.data_room.public = Some(DatasetAccessRole::Editor);
+
+    let error = access_policy.validate().unwrap_err();
+
+    assert!(error.to_string().contains("data_room.public"));
+}
+
+#[test]
+fn test_access_policy_rejects_unknown_principals() {
+    let result = serde_json::from_value::<AccessPolicy>(serde_json::json!({
+        "announcements": { "signer": "Reader" },
+    }));
+
+    assert!(result.is_err());
+}
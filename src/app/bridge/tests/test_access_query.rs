@@ -0,0 +1,386 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+
+use alloy::primitives::{Address, U256, address};
+use kamu_molecule_bridge::access::{AccessReason, DesiredAccess};
+use kamu_molecule_bridge::access_policy::{
+    AccessPolicies, AccessPolicy, FileAccessPolicy, MultisigAccessPolicies, MultisigAccessPolicy,
+    PrincipalRoles,
+};
+use kamu_molecule_bridge::access_query::{
+    OclFileView, PageArgs, ProjectDatasetView, get_account_access, get_dataset_access, get_ocl_view,
+};
+use kamu_molecule_bridge::state::{
+    AppState, ChainState, MultisigState, OclHoldersState, OffChainMoleculeProjectProjection,
+    VersionedFileEntryWithMoleculeAccessLevel,
+};
+use kamu_node_api_client::{
+    DatasetAccessRole, MoleculeAccessLevel, MoleculeProjectEntry, OperationType, VersionedFileEntry,
+};
+use molecule_ocl::entities::{OclId, OclOwnershipProjection, OclOwnershipProjectionMap};
+use pretty_assertions::assert_eq;
+
+const CHAIN_ID: u64 = 1;
+
+const OWNER: Address = address!("0x1111111111111111111111111111111111111111");
+const HOLDER: Address = address!("0x2222222222222222222222222222222222222222");
+const FORMER_OWNER: Address = address!("0x3333333333333333333333333333333333333333");
+const MULTISIG: Address = address!("0x4444444444444444444444444444444444444444");
+
+#[test]
+fn test_ocl_view() {
+    let app_state = app_state();
+
+    let ocl_view = get_ocl_view(&app_state, &AccessPolicies::default(), ocl_id()).unwrap();
+
+    let project = ocl_view.project.unwrap();
+    assert_eq!("PROJECT", project.symbol);
+    assert_eq!(
+        vec!["/file-holder"],
+        file_paths(&project.files.holder).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec!["/file-admin"],
+        file_paths(&project.files.admin).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec!["/file-removed"],
+        file_paths(&project.removed_files).collect::<Vec<_>>()
+    );
+
+    assert_eq!(1, ocl_view.chains.len());
+    let chain = &ocl_view.chains[0];
+    assert_eq!(CHAIN_ID, chain.chain_id);
+    assert_eq!(Some(MULTISIG), chain.current_owner);
+    assert_eq!(vec![FORMER_OWNER], chain.previous_owners);
+    assert_eq!(
+        BTreeMap::from([(MULTISIG, multisig_state())]),
+        chain.multisigs
+    );
+    assert_eq!(vec![OWNER], chain.owner_accounts);
+    assert_eq!(vec![HOLDER], chain.holder_accounts);
+    assert_eq!(vec![FORMER_OWNER], chain.former_owner_accounts);
+}
+
+#[test]
+fn test_ocl_view_of_unknown_ocl() {
+    let app_state = app_state();
+
+    let unknown_ocl_id =
+        OclId::from_str("0x0101000000000000000000992399d367a2fa6f971dbc1647f81f999c19a70d67")
+            .unwrap();
+
+    assert!(get_ocl_view(&app_state, &AccessPolicies::default(), unknown_ocl_id).is_none());
+}
+
+#[test]
+fn test_account_access() {
+    let app_state = app_state();
+
+    let owner_access = get_account_access(
+        &app_state,
+        &AccessPolicies::default(),
+        OWNER,
+        &PageArgs::default(),
+    )
+    .unwrap();
+    assert_eq!(4, owner_access.total_count);
+    assert_eq!(
+        vec![
+            desired_access("announcements", OWNER, DatasetAccessRole::Maintainer),
+            desired_access("data-room", OWNER, DatasetAccessRole::Maintainer),
+            desired_access("file-admin", OWNER, DatasetAccessRole::Maintainer),
+            desired_access("file-holder", OWNER, DatasetAccessRole::Maintainer),
+        ],
+        owner_access.items
+    );
+
+    let holder_access = get_account_access(
+        &app_state,
+        &AccessPolicies::default(),
+        HOLDER,
+        &PageArgs::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        vec![desired_access(
+            "file-holder",
+            HOLDER,
+            DatasetAccessRole::Reader
+        )],
+        holder_access.items
+    );
+
+    let former_owner_access = get_account_access(
+        &app_state,
+        &AccessPolicies::default(),
+        FORMER_OWNER,
+        &PageArgs::default(),
+    )
+    .unwrap();
+    assert_eq!(0, former_owner_access.total_count);
+}
+
+#[test]
+fn test_account_access_follows_multisig_access_policy() {
+    let app_state = app_state();
+    let access_policies = AccessPolicies {
+        multisig: MultisigAccessPolicies {
+            per_ocl: HashMap::from([(
+                ocl_id(),
+                MultisigAccessPolicy {
+                    multisig_role: Some(DatasetAccessRole::Maintainer),
+                    sole_signer_role: Some(DatasetAccessRole::Reader),
+                    signer_role: None,
+                },
+            )]),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let signer_access =
+        get_account_access(&app_state, &access_policies, OWNER, &PageArgs::default()).unwrap();
+    assert_eq!(
+        vec![
+            desired_access("announcements", OWNER, DatasetAccessRole::Reader),
+            desired_access("data-room", OWNER, DatasetAccessRole::Reader),
+            desired_access("file-admin", OWNER, DatasetAccessRole::Reader),
+            desired_access("file-holder", OWNER, DatasetAccessRole::Reader),
+        ],
+        signer_access.items
+    );
+
+    let multisig_access =
+        get_account_access(&app_state, &access_policies, MULTISIG, &PageArgs::default()).unwrap();
+    assert_eq!(4, multisig_access.total_count);
+    assert!(
+        multisig_access
+            .items
+            .iter()
+            .all(|access| access.role == DatasetAccessRole::Maintainer)
+    );
+}
+
+#[test]
+fn test_account_access_follows_access_policy() {
+    let app_state = app_state();
+    let default_policy = AccessPolicy::default();
+    let access_policies = AccessPolicies {
+        access: AccessPolicy {
+            announcements: PrincipalRoles {
+                holder: Some(DatasetAccessRole::Reader),
+                ..default_policy.announcements
+            },
+            files: FileAccessPolicy {
+                admin: PrincipalRoles {
+                    multisig_signer: Some(DatasetAccessRole::Editor),
+                    ..default_policy.files.admin
+                },
+                holder: PrincipalRoles {
+                    holder: None,
+                    ..default_policy.files.holder
+                },
+                ..default_policy.files
+            },
+            ..default_policy
+        },
+        ..Default::default()
+    };
+
+    let signer_access =
+        get_account_access(&app_state, &access_policies, OWNER, &PageArgs::default()).unwrap();
+    assert_eq!(
+        vec![
+            desired_access("announcements", OWNER, DatasetAccessRole::Maintainer),
+            desired_access("data-room", OWNER, DatasetAccessRole::Maintainer),
+            desired_access("file-admin", OWNER, DatasetAccessRole::Editor),
+            desired_access("file-holder", OWNER, DatasetAccessRole::Maintainer),
+        ],
+        signer_access.items
+    );
+
+    let holder_access =
+        get_account_access(&app_state, &access_policies, HOLDER, &PageArgs::default()).unwrap();
+    assert_eq!(
+        vec![desired_access(
+            "announcements",
+            HOLDER,
+            DatasetAccessRole::Reader
+        )],
+        holder_access.items
+    );
+}
+
+#[test]
+fn test_dataset_access_is_paginated() {
+    let app_state = app_state();
+
+    let dataset_access = get_dataset_access(
+        &app_state,
+        &AccessPolicies::default(),
+        &"file-holder".to_string(),
+        &PageArgs {
+            offset: 1,
+            limit: Some(1),
+        },
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(ocl_id(), dataset_access.ocl_id);
+    assert!(matches!(
+        dataset_access.dataset,
+        ProjectDatasetView::File {
+            molecule_access_level: MoleculeAccessLevel::Holder,
+            ..
+        }
+    ));
+    assert_eq!(2, dataset_access.accounts.total_count);
+    assert_eq!(
+        vec![desired_access(
+            "file-holder",
+            HOLDER,
+            DatasetAccessRole::Reader
+        )],
+        dataset_access.accounts.items
+    );
+}
+
+#[test]
+fn test_dataset_access_of_removed_file() {
+    let app_state = app_state();
+
+    let dataset_access = get_dataset_access(
+        &app_state,
+        &AccessPolicies::default(),
+        &"file-removed".to_string(),
+        &PageArgs::default(),
+    )
+    .unwrap()
+    .unwrap();
+
+    assert!(matches!(
+        dataset_access.dataset,
+        ProjectDatasetView::RemovedFile { .. }
+    ));
+    assert_eq!(0, dataset_access.accounts.total_count);
+
+    let unknown_dataset_access = get_dataset_access(
+        &app_state,
+        &AccessPolicies::default(),
+        &"unknown".to_string(),
+        &PageArgs::default(),
+    )
+    .unwrap();
+    assert!(unknown_dataset_access.is_none());
+}
+
+// Helpers
+
+fn ocl_id() -> OclId {
+    const RAW: &str = "0x0101000000000000000000a1117b215dcd666dd847cfa84721480d316440faa9";
+    OclId::from_str(RAW).unwrap()
+}
+
+fn multisig_state() -> MultisigState {
+    MultisigState {
+        current_owners: HashSet::from([OWNER]),
+        former_owners: HashSet::new(),
+        threshold: 1,
+    }
+}
+
+/// OCL owned by a multisig (formerly by a wallet) with one IP-token holder
+fn app_state() -> AppState {
+    let mut app_state = AppState::default();
+
+    app_state.chains.insert(
+        CHAIN_ID,
+        ChainState {
+            on_chain_ocl_ownership_projection_map: OclOwnershipProjectionMap::from_entries([(
+                ocl_id(),
+                OclOwnershipProjection {
+                    current: Some(MULTISIG),
+                    previous: vec![FORMER_OWNER],
+                    identity_account: None,
+                },
+            )]),
+            multisig: HashMap::from([(MULTISIG, Some(multisig_state())), (FORMER_OWNER, None)]),
+            ocl_holders: HashMap::from([(
+                ocl_id(),
+                OclHoldersState {
+                    balances: HashMap::from([(HOLDER, U256::from(1))]),
+                    former_holders: HashSet::new(),
+                },
+            )]),
+            ..ChainState::new(10)
+        },
+    );
+
+    app_state.off_chain_ocl_project_map.insert(
+        ocl_id(),
+        OffChainMoleculeProjectProjection {
+            entry: MoleculeProjectEntry {
+                offset: 0,
+                op: OperationType::Append,
+                ocl_id: ocl_id(),
+                symbol: "PROJECT".to_string(),
+                project_account_id: "project-account".to_string(),
+                data_room_dataset_id: "data-room".to_string(),
+                announcements_dataset_id: "announcements".to_string(),
+            },
+            latest_data_room_offset: 0,
+            actual_files_map: HashMap::from([
+                file("file-admin", MoleculeAccessLevel::Admin),
+                file("file-holder", MoleculeAccessLevel::Holder),
+            ]),
+            removed_files_map: HashMap::from([(
+                "file-removed".to_string(),
+                versioned_file_entry("file-removed"),
+            )]),
+        },
+    );
+
+    app_state
+}
+
+fn versioned_file_entry(dataset_id: &str) -> VersionedFileEntry {
+    VersionedFileEntry {
+        offset: 0,
+        path: format!("/{dataset_id}"),
+    }
+}
+
+fn file(
+    dataset_id: &str,
+    molecule_access_level: MoleculeAccessLevel,
+) -> (String, VersionedFileEntryWithMoleculeAccessLevel) {
+    (
+        dataset_id.to_string(),
+        VersionedFileEntryWithMoleculeAccessLevel {
+            entry: versioned_file_entry(dataset_id),
+            molecule_access_level,
+        },
+    )
+}
+
+fn file_paths(files: &[OclFileView]) -> impl Iterator<Item = &str> {
+    files.iter().map(|file| file.path.as_str())
+}
+
+fn desired_access(dataset_id: &str, address: Address, role: DatasetAccessRole) -> DesiredAccess {
+    DesiredAccess {
+        dataset_id: dataset_id.to_string(),
+        account_id: format!("did:pkh:eip155:{CHAIN_ID}:{address}"),
+        chain_id: CHAIN_ID,
+        address,
+        role,
+        ocl_id: ocl_id(),
+        reason: if address == HOLDER {
+            AccessReason::IpTokenHolder
+        } else {
+            AccessReason::OclOwner
+        },
+    }
+}
@@ -0,0 +1,1116 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use alloy::primitives::{Address, B256, U256, address};
+use chrono::Utc;
+use kamu_molecule_bridge::events::{
+    AppStateProjector, BridgeEvent, ChangedVersionedFile, DataRoomFileChange, EventLogEntry,
+    OclHolderChanges,
+};
+use kamu_molecule_bridge::state::{
+    AppState, MultisigState, OclHoldersState, OffChainMoleculeProjectProjection,
+    VersionedFileEntryWithMoleculeAccessLevel,
+};
+use kamu_node_api_client::{
+    MoleculeAccessLevel, MoleculeProjectEntry, OclIdFilter, OperationType, VersionedFileEntry,
+};
+use molecule_ocl::entities::{OclId, OclOwnershipChange, OclOwnershipProjection};
+use pretty_assertions::assert_eq;
+
+const CHAIN_ID: u64 = 1;
+
+const ADDR_A: Address = address!("0x1111111111111111111111111111111111111111");
+const ADDR_B: Address = address!("0x2222222222222222222222222222222222222222");
+const ADDR_C: Address = address!("0x3333333333333333333333333333333333333333");
+const MULTISIG: Address = address!("0x4444444444444444444444444444444444444444");
+const TOKEN_BOUND_ACCOUNT: Address = address!("0x5555555555555555555555555555555555555555");
+
+const HASH_1: B256 = B256::repeat_byte(0x01);
+const HASH_2: B256 = B256::repeat_byte(0x02);
+
+#[test]
+fn test_ocl_transfers_are_applied_on_checkpoint() {
+    let mut app_state = AppState::default();
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, ADDR_A),
+            ocl_transferred(2, ocl_id_1(), ADDR_A, ADDR_B),
+            ocl_transferred(3, ocl_id_2(), Address::ZERO, ADDR_C),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+        ]),
+    );
+
+    assert_eq!(10, app_state.chains[&CHAIN_ID].latest_indexed_block_number);
+    assert_eq!(
+        Some(&OclOwnershipProjection {
+            current: Some(ADDR_B),
+            previous: vec![],
+            identity_account: None,
+        }),
+        app_state.chains[&CHAIN_ID]
+            .on_chain_ocl_ownership_projection_map
+            .get(&ocl_id_1())
+    );
+    assert_eq!(
+        HashMap::from([
+            (
+                ocl_id_1(),
+                OclOwnershipChange {
+                    former_owner: None,
+                    current_owner: ADDR_B,
+                }
+            ),
+            (
+                ocl_id_2(),
+                OclOwnershipChange {
+                    former_owner: None,
+                    current_owner: ADDR_C,
+                }
+            ),
+        ]),
+        changes.on_chain[&CHAIN_ID].ocl_ownership_diff_map
+    );
+}
+
+#[test]
+fn test_ocl_transfers_keep_former_owner_across_checkpoints() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, ADDR_A),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+        ]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(11, ocl_id_1(), ADDR_A, ADDR_B),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 20,
+            },
+            ocl_transferred(21, ocl_id_1(), ADDR_B, ADDR_C),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 30,
+            },
+        ]),
+    );
+
+    assert_eq!(
+        HashMap::from([(
+            ocl_id_1(),
+            OclOwnershipChange {
+                former_owner: Some(ADDR_A),
+                current_owner: ADDR_C,
+            }
+        )]),
+        changes.on_chain[&CHAIN_ID].ocl_ownership_diff_map
+    );
+}
+
+#[test]
+fn test_multisig_owner_changes_affect_owned_ocls() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, MULTISIG),
+            ocl_transferred(2, ocl_id_2(), Address::ZERO, ADDR_C),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            BridgeEvent::MultisigDiscovered {
+                chain_id: CHAIN_ID,
+                at_block: 10,
+                address: MULTISIG,
+                state: Some(MultisigState {
+                    current_owners: HashSet::from([ADDR_A]),
+                    former_owners: HashSet::new(),
+                    threshold: 1,
+                }),
+            },
+        ]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::MultisigOwnerAdded {
+                chain_id: CHAIN_ID,
+                block_number: 11,
+                log_index: 0,
+                tx_hash: None,
+                multisig: MULTISIG,
+                owner: ADDR_B,
+            },
+            BridgeEvent::MultisigOwnerRemoved {
+                chain_id: CHAIN_ID,
+                block_number: 12,
+                log_index: 0,
+                tx_hash: None,
+                multisig: MULTISIG,
+                owner: ADDR_A,
+            },
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 20,
+            },
+        ]),
+    );
+
+    assert_eq!(
+        Some(&Some(MultisigState {
+            current_owners: HashSet::from([ADDR_B]),
+            former_owners: HashSet::from([ADDR_A]),
+            threshold: 1,
+        })),
+        app_state.chains[&CHAIN_ID].multisig.get(&MULTISIG)
+    );
+    assert_eq!(
+        HashMap::from([(ocl_id_1(), MULTISIG)]),
+        changes.on_chain[&CHAIN_ID].changed_ocl_multisig_owners
+    );
+}
+
+#[test]
+fn test_multisig_threshold_changes_affect_owned_ocls() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, MULTISIG),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            BridgeEvent::MultisigDiscovered {
+                chain_id: CHAIN_ID,
+                at_block: 10,
+                address: MULTISIG,
+                state: Some(MultisigState {
+                    current_owners: HashSet::from([ADDR_A, ADDR_B]),
+                    former_owners: HashSet::new(),
+                    threshold: 1,
+                }),
+            },
+        ]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::MultisigThresholdChanged {
+                chain_id: CHAIN_ID,
+                block_number: 11,
+                log_index: 0,
+                tx_hash: None,
+                multisig: MULTISIG,
+                threshold: 2,
+            },
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 20,
+            },
+        ]),
+    );
+
+    assert_eq!(
+        Some(&Some(MultisigState {
+            current_owners: HashSet::from([ADDR_A, ADDR_B]),
+            former_owners: HashSet::new(),
+            threshold: 2,
+        })),
+        app_state.chains[&CHAIN_ID].multisig.get(&MULTISIG)
+    );
+    assert_eq!(
+        HashMap::from([(ocl_id_1(), MULTISIG)]),
+        changes.on_chain[&CHAIN_ID].changed_ocl_multisig_owners
+    );
+}
+
+#[test]
+fn test_ocl_transfers_affect_ocls_owned_by_token_bound_account() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, ADDR_A),
+            BridgeEvent::OclIdentityCreated {
+                chain_id: CHAIN_ID,
+                block_number: 1,
+                log_index: 1,
+                tx_hash: None,
+                ocl_id: ocl_id_1(),
+                account: TOKEN_BOUND_ACCOUNT,
+            },
+            ocl_transferred(2, ocl_id_2(), Address::ZERO, TOKEN_BOUND_ACCOUNT),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+        ]),
+    );
+
+    assert_eq!(
+        Some(&OclOwnershipProjection {
+            current: Some(ADDR_A),
+            previous: vec![],
+            identity_account: Some(TOKEN_BOUND_ACCOUNT),
+        }),
+        app_state.chains[&CHAIN_ID]
+            .on_chain_ocl_ownership_projection_map
+            .get(&ocl_id_1())
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(11, ocl_id_1(), ADDR_A, ADDR_B),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 20,
+            },
+        ]),
+    );
+
+    assert_eq!(
+        HashMap::from([(
+            ocl_id_1(),
+            OclOwnershipChange {
+                former_owner: Some(ADDR_A),
+                current_owner: ADDR_B,
+            }
+        )]),
+        changes.on_chain[&CHAIN_ID].ocl_ownership_diff_map
+    );
+    assert_eq!(
+        HashMap::from([(ocl_id_2(), TOKEN_BOUND_ACCOUNT)]),
+        changes.on_chain[&CHAIN_ID].changed_ocl_token_bound_account_owners
+    );
+}
+
+#[test]
+fn test_finalized_checkpoints_are_pruned() {
+    let mut app_state = AppState::default();
+
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            head_blocks_indexed(15, HASH_1, 10),
+            head_blocks_indexed(20, HASH_2, 15),
+        ]),
+    );
+
+    assert_eq!(20, app_state.chains[&CHAIN_ID].latest_indexed_block_number);
+    assert_eq!(
+        vec![(16, 20, HASH_2)],
+        app_state.chains[&CHAIN_ID]
+            .unfinalized_checkpoints
+            .iter()
+            .map(|checkpoint| (
+                checkpoint.from_block,
+                checkpoint.to_block,
+                checkpoint.to_block_hash
+            ))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_chain_reorganization_compensates_orphaned_transfers() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, ADDR_A),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            ocl_transferred(12, ocl_id_1(), ADDR_A, ADDR_B),
+            head_blocks_indexed(15, HASH_1, 10),
+        ]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::ChainReorganized {
+                chain_id: CHAIN_ID,
+                fork_block_number: 10,
+            },
+            ocl_transferred(13, ocl_id_1(), ADDR_A, ADDR_C),
+            head_blocks_indexed(16, HASH_2, 10),
+        ]),
+    );
+
+    assert_eq!(16, app_state.chains[&CHAIN_ID].latest_indexed_block_number);
+    assert_eq!(
+        Some(&OclOwnershipProjection {
+            current: Some(ADDR_C),
+            previous: vec![ADDR_A],
+            identity_account: None,
+        }),
+        app_state.chains[&CHAIN_ID]
+            .on_chain_ocl_ownership_projection_map
+            .get(&ocl_id_1())
+    );
+    // Access was granted to the orphaned owner, so it's the one to revoke
+    assert_eq!(
+        HashMap::from([(
+            ocl_id_1(),
+            OclOwnershipChange {
+                former_owner: Some(ADDR_B),
+                current_owner: ADDR_C,
+            }
+        )]),
+        changes.on_chain[&CHAIN_ID].ocl_ownership_diff_map
+    );
+    assert_eq!(
+        vec![(11, 16, HASH_2)],
+        app_state.chains[&CHAIN_ID]
+            .unfinalized_checkpoints
+            .iter()
+            .map(|checkpoint| (
+                checkpoint.from_block,
+                checkpoint.to_block,
+                checkpoint.to_block_hash
+            ))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_chain_reorganization_revokes_orphaned_multisig_owners() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, MULTISIG),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            BridgeEvent::MultisigDiscovered {
+                chain_id: CHAIN_ID,
+                at_block: 10,
+                address: MULTISIG,
+                state: Some(MultisigState {
+                    current_owners: HashSet::from([ADDR_A]),
+                    former_owners: HashSet::new(),
+                    threshold: 1,
+                }),
+            },
+            BridgeEvent::MultisigOwnerAdded {
+                chain_id: CHAIN_ID,
+                block_number: 12,
+                log_index: 0,
+                tx_hash: None,
+                multisig: MULTISIG,
+                owner: ADDR_B,
+            },
+            head_blocks_indexed(15, HASH_1, 10),
+        ]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::ChainReorganized {
+                chain_id: CHAIN_ID,
+                fork_block_number: 10,
+            },
+            head_blocks_indexed(16, HASH_2, 10),
+        ]),
+    );
+
+    assert_eq!(
+        Some(&Some(MultisigState {
+            current_owners: HashSet::from([ADDR_A]),
+            former_owners: HashSet::from([ADDR_B]),
+            threshold: 1,
+        })),
+        app_state.chains[&CHAIN_ID].multisig.get(&MULTISIG)
+    );
+    assert_eq!(
+        HashMap::from([(ocl_id_1(), MULTISIG)]),
+        changes.on_chain[&CHAIN_ID].changed_ocl_multisig_owners
+    );
+    assert_eq!(
+        HashMap::new(),
+        changes.on_chain[&CHAIN_ID].ocl_ownership_diff_map
+    );
+}
+
+#[test]
+fn test_chain_reorganization_revokes_orphaned_ocl_owner() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, ADDR_A),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            ocl_transferred(12, ocl_id_2(), Address::ZERO, ADDR_B),
+            head_blocks_indexed(15, HASH_1, 10),
+        ]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::ChainReorganized {
+                chain_id: CHAIN_ID,
+                fork_block_number: 10,
+            },
+            head_blocks_indexed(16, HASH_2, 10),
+        ]),
+    );
+
+    // The OCL exists only in orphaned blocks, so it's kept without an owner
+    assert_eq!(
+        Some(&OclOwnershipProjection {
+            current: None,
+            previous: vec![ADDR_B],
+            identity_account: None,
+        }),
+        app_state.chains[&CHAIN_ID]
+            .on_chain_ocl_ownership_projection_map
+            .get(&ocl_id_2())
+    );
+    assert_eq!(
+        HashMap::from([(ocl_id_2(), ADDR_B)]),
+        changes.on_chain[&CHAIN_ID].dropped_ocl_owners
+    );
+    assert_eq!(
+        HashMap::new(),
+        changes.on_chain[&CHAIN_ID].ocl_ownership_diff_map
+    );
+}
+
+#[test]
+fn test_chain_reorganization_revokes_orphaned_owner_of_known_ocl() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::OclIdentityCreated {
+                chain_id: CHAIN_ID,
+                block_number: 1,
+                log_index: 0,
+                tx_hash: None,
+                ocl_id: ocl_id_1(),
+                account: TOKEN_BOUND_ACCOUNT,
+            },
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            ocl_transferred(12, ocl_id_1(), Address::ZERO, MULTISIG),
+            holder_token_transferred(12, Address::ZERO, ADDR_C, 100),
+            head_blocks_indexed(15, HASH_1, 10),
+        ]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::ChainReorganized {
+                chain_id: CHAIN_ID,
+                fork_block_number: 10,
+            },
+            head_blocks_indexed(16, HASH_2, 10),
+        ]),
+    );
+
+    // Only the identity is known, the multisig owner is revoked along with the holders
+    assert_eq!(
+        Some(&OclOwnershipProjection {
+            current: None,
+            previous: vec![MULTISIG],
+            identity_account: Some(TOKEN_BOUND_ACCOUNT),
+        }),
+        app_state.chains[&CHAIN_ID]
+            .on_chain_ocl_ownership_projection_map
+            .get(&ocl_id_1())
+    );
+    assert_eq!(
+        HashMap::from([(ocl_id_1(), MULTISIG)]),
+        changes.on_chain[&CHAIN_ID].dropped_ocl_owners
+    );
+    assert_eq!(
+        HashMap::from([(
+            ocl_id_1(),
+            OclHolderChanges {
+                added: HashSet::new(),
+                removed: HashSet::from([ADDR_C]),
+            }
+        )]),
+        changes.on_chain[&CHAIN_ID].changed_ocl_holders
+    );
+}
+
+#[test]
+fn test_holder_token_transfers_track_holders() {
+    let mut app_state = AppState::default();
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            holder_token_transferred(1, Address::ZERO, ADDR_A, 100),
+            holder_token_transferred(2, ADDR_A, ADDR_B, 40),
+            holder_token_transferred(3, ADDR_A, ADDR_C, 60),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+        ]),
+    );
+
+    assert_eq!(
+        Some(&OclHoldersState {
+            balances: HashMap::from([(ADDR_B, U256::from(40)), (ADDR_C, U256::from(60))]),
+            former_holders: HashSet::from([ADDR_A]),
+        }),
+        app_state.chains[&CHAIN_ID].ocl_holders.get(&ocl_id_1())
+    );
+    assert_eq!(
+        HashMap::from([(
+            ocl_id_1(),
+            OclHolderChanges {
+                added: HashSet::from([ADDR_B, ADDR_C]),
+                removed: HashSet::from([ADDR_A]),
+            }
+        )]),
+        changes.on_chain[&CHAIN_ID].changed_ocl_holders
+    );
+
+    // Burning the whole balance revokes the holder, re-acquiring restores it
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            holder_token_transferred(11, ADDR_B, Address::ZERO, 40),
+            holder_token_transferred(12, ADDR_C, ADDR_A, 1),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 20,
+            },
+        ]),
+    );
+
+    assert_eq!(
+        Some(&OclHoldersState {
+            balances: HashMap::from([(ADDR_A, U256::from(1)), (ADDR_C, U256::from(59))]),
+            former_holders: HashSet::from([ADDR_B]),
+        }),
+        app_state.chains[&CHAIN_ID].ocl_holders.get(&ocl_id_1())
+    );
+    assert_eq!(
+        HashMap::from([(
+            ocl_id_1(),
+            OclHolderChanges {
+                added: HashSet::from([ADDR_A]),
+                removed: HashSet::from([ADDR_B]),
+            }
+        )]),
+        changes.on_chain[&CHAIN_ID].changed_ocl_holders
+    );
+}
+
+#[test]
+fn test_chain_reorganization_revokes_orphaned_holders() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            holder_token_transferred(1, Address::ZERO, ADDR_A, 100),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            holder_token_transferred(12, ADDR_A, ADDR_B, 100),
+            head_blocks_indexed(15, HASH_1, 10),
+        ]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::ChainReorganized {
+                chain_id: CHAIN_ID,
+                fork_block_number: 10,
+            },
+            holder_token_transferred(13, ADDR_A, ADDR_C, 100),
+            head_blocks_indexed(16, HASH_2, 10),
+        ]),
+    );
+
+    assert_eq!(
+        Some(&OclHoldersState {
+            balances: HashMap::from([(ADDR_C, U256::from(100))]),
+            former_holders: HashSet::from([ADDR_A, ADDR_B]),
+        }),
+        app_state.chains[&CHAIN_ID].ocl_holders.get(&ocl_id_1())
+    );
+    // Access was granted to the orphaned holder, so it's the one to revoke
+    assert_eq!(
+        HashMap::from([(
+            ocl_id_1(),
+            OclHolderChanges {
+                added: HashSet::from([ADDR_C]),
+                removed: HashSet::from([ADDR_B]),
+            }
+        )]),
+        changes.on_chain[&CHAIN_ID].changed_ocl_holders
+    );
+}
+
+#[test]
+fn test_chains_are_indexed_independently() {
+    const OTHER_CHAIN_ID: u64 = 8453;
+
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, ADDR_A),
+            BridgeEvent::OclTransferred {
+                chain_id: OTHER_CHAIN_ID,
+                block_number: 105,
+                log_index: 0,
+                tx_hash: None,
+                ocl_id: ocl_id_1(),
+                from: Address::ZERO,
+                to: ADDR_B,
+            },
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            BridgeEvent::HeadBlocksIndexed {
+                chain_id: OTHER_CHAIN_ID,
+                to_block: 110,
+                to_block_hash: HASH_1,
+                finalized_block_number: 100,
+            },
+        ]),
+    );
+
+    assert_eq!(10, app_state.chains[&CHAIN_ID].latest_indexed_block_number);
+    assert_eq!(
+        110,
+        app_state.chains[&OTHER_CHAIN_ID].latest_indexed_block_number
+    );
+    assert_eq!(
+        vec![(CHAIN_ID, Some(ADDR_A)), (OTHER_CHAIN_ID, Some(ADDR_B)),],
+        app_state
+            .ocl_ownership_projections(&ocl_id_1())
+            .map(|(chain_id, ownership_projection)| (chain_id, ownership_projection.current))
+            .collect::<Vec<_>>()
+    );
+
+    // The transfer is orphaned on the other chain only
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::ChainReorganized {
+                chain_id: OTHER_CHAIN_ID,
+                fork_block_number: 100,
+            },
+            BridgeEvent::BlocksIndexed {
+                chain_id: OTHER_CHAIN_ID,
+                to_block: 110,
+            },
+        ]),
+    );
+
+    assert_eq!(
+        vec![(CHAIN_ID, Some(ADDR_A))],
+        app_state
+            .ocl_ownership_projections(&ocl_id_1())
+            .map(|(chain_id, ownership_projection)| (chain_id, ownership_projection.current))
+            .collect::<Vec<_>>()
+    );
+    assert!(
+        app_state.chains[&OTHER_CHAIN_ID]
+            .unfinalized_checkpoints
+            .is_empty()
+    );
+    assert!(!changes.on_chain.contains_key(&CHAIN_ID));
+}
+
+#[test]
+fn test_chain_state_reset_keeps_other_state() {
+    const OTHER_CHAIN_ID: u64 = 8453;
+
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            ocl_transferred(1, ocl_id_1(), Address::ZERO, ADDR_A),
+            BridgeEvent::BlocksIndexed {
+                chain_id: CHAIN_ID,
+                to_block: 10,
+            },
+            BridgeEvent::BlocksIndexed {
+                chain_id: OTHER_CHAIN_ID,
+                to_block: 100,
+            },
+            BridgeEvent::MoleculeProjectLoaded {
+                project: project(OperationType::Append, HashMap::new()),
+            },
+        ]),
+    );
+
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![BridgeEvent::ChainStateReset {
+            chain_id: CHAIN_ID,
+            latest_indexed_block_number: 5,
+        }]),
+    );
+
+    assert_eq!(5, app_state.chains[&CHAIN_ID].latest_indexed_block_number);
+    assert_eq!(
+        None,
+        app_state.chains[&CHAIN_ID]
+            .on_chain_ocl_ownership_projection_map
+            .get(&ocl_id_1())
+    );
+    assert_eq!(
+        100,
+        app_state.chains[&OTHER_CHAIN_ID].latest_indexed_block_number
+    );
+    assert!(
+        app_state
+            .off_chain_ocl_project_map
+            .contains_key(&ocl_id_1())
+    );
+
+    AppStateProjector::project(&mut app_state, &entries(vec![BridgeEvent::StateReset]));
+
+    assert!(app_state.chains.is_empty());
+    assert!(app_state.off_chain_ocl_project_map.is_empty());
+}
+
+#[test]
+fn test_data_room_files_changed() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![BridgeEvent::MoleculeProjectLoaded {
+            project: project(
+                OperationType::Append,
+                HashMap::from([
+                    file("file-1", MoleculeAccessLevel::Public),
+                    file("file-2", MoleculeAccessLevel::Admin),
+                ]),
+            ),
+        }]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![BridgeEvent::DataRoomFilesChanged {
+            ocl_id: ocl_id_1(),
+            latest_data_room_offset: 5,
+            added_files: HashMap::from([file("file-3", MoleculeAccessLevel::Holder)]),
+            removed_files: HashMap::from([("file-1".to_string(), versioned_file_entry("file-1"))]),
+        }]),
+    );
+
+    let project = &app_state.off_chain_ocl_project_map[&ocl_id_1()];
+    assert_eq!(5, project.latest_data_room_offset);
+    assert_eq!(
+        HashSet::from(["file-2", "file-3"]),
+        project
+            .actual_files_map
+            .keys()
+            .map(String::as_str)
+            .collect::<HashSet<_>>()
+    );
+    assert_eq!(
+        HashSet::from(["file-1"]),
+        project
+            .removed_files_map
+            .keys()
+            .map(String::as_str)
+            .collect::<HashSet<_>>()
+    );
+    assert_eq!(
+        vec![
+            ChangedVersionedFile {
+                dataset_id: "file-3".to_string(),
+                change: DataRoomFileChange::Added(MoleculeAccessLevel::Holder),
+            },
+            ChangedVersionedFile {
+                dataset_id: "file-1".to_string(),
+                change: DataRoomFileChange::Removed,
+            },
+        ],
+        changes.changed_files_per_project[&ocl_id_1()]
+    );
+}
+
+#[test]
+fn test_molecule_access_level_changed() {
+    let mut app_state = AppState::default();
+    AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![BridgeEvent::MoleculeProjectLoaded {
+            project: project(
+                OperationType::Append,
+                HashMap::from([file("file-1", MoleculeAccessLevel::Public)]),
+            ),
+        }]),
+    );
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![BridgeEvent::MoleculeAccessLevelChanged {
+            ocl_id: ocl_id_1(),
+            dataset_id: "file-1".to_string(),
+            from: MoleculeAccessLevel::Public,
+            to: MoleculeAccessLevel::Admin,
+        }]),
+    );
+
+    assert_eq!(
+        MoleculeAccessLevel::Admin,
+        app_state.off_chain_ocl_project_map[&ocl_id_1()].actual_files_map["file-1"]
+            .molecule_access_level
+    );
+    assert_eq!(
+        vec![ChangedVersionedFile {
+            dataset_id: "file-1".to_string(),
+            change: DataRoomFileChange::MoleculeAccessLevelChanged {
+                from: MoleculeAccessLevel::Public,
+                to: MoleculeAccessLevel::Admin,
+            },
+        }],
+        changes.changed_files_per_project[&ocl_id_1()]
+    );
+}
+
+#[test]
+fn test_deleted_project_files_are_removed() {
+    let mut app_state = AppState::default();
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![BridgeEvent::MoleculeProjectLoaded {
+            project: project(
+                OperationType::Retract,
+                HashMap::from([file("file-1", MoleculeAccessLevel::Public)]),
+            ),
+        }]),
+    );
+
+    assert_eq!(
+        vec![ChangedVersionedFile {
+            dataset_id: "file-1".to_string(),
+            change: DataRoomFileChange::Removed,
+        }],
+        changes.changed_files_per_project[&ocl_id_1()]
+    );
+}
+
+#[test]
+fn test_ignored_project_is_dropped() {
+    let mut app_state = AppState::default();
+
+    let changes = AppStateProjector::project(
+        &mut app_state,
+        &entries(vec![
+            BridgeEvent::MoleculeProjectLoaded {
+                project: project(
+                    OperationType::Append,
+                    HashMap::from([file("file-1", MoleculeAccessLevel::Public)]),
+                ),
+            },
+            BridgeEvent::MoleculeProjectIgnored { ocl_id: ocl_id_1() },
+        ]),
+    );
+
+    assert!(app_state.off_chain_ocl_project_map.is_empty());
+    assert!(changes.changed_files_per_project.is_empty());
+}
+
+#[test]
+fn test_replay_matches_live_projection() {
+    let events = vec![
+        ocl_transferred(1, ocl_id_1(), Address::ZERO, ADDR_A),
+        BridgeEvent::BlocksIndexed {
+            chain_id: CHAIN_ID,
+            to_block: 10,
+        },
+        BridgeEvent::MultisigDiscovered {
+            chain_id: CHAIN_ID,
+            at_block: 10,
+            address: ADDR_A,
+            state: None,
+        },
+        BridgeEvent::MoleculeProjectLoaded {
+            project: project(
+                OperationType::Append,
+                HashMap::from([file("file-1", MoleculeAccessLevel::Public)]),
+            ),
+        },
+        BridgeEvent::MoleculeProjectsScanned {
+            molecule_projects_dataset_offset: Some(0),
+            ocl_id_filter: OclIdFilter {
+                ignore: [ocl_id_2()].into(),
+                only: None,
+            },
+        },
+        ocl_transferred(11, ocl_id_1(), ADDR_A, ADDR_B),
+        BridgeEvent::BlocksIndexed {
+            chain_id: CHAIN_ID,
+            to_block: 20,
+        },
+    ];
+    let entries = entries(events);
+
+    let mut live_app_state = AppState::default();
+    for entry in &entries {
+        AppStateProjector::project(&mut live_app_state, std::slice::from_ref(entry));
+    }
+
+    let replayed_app_state = AppStateProjector::replay(&entries);
+
+    assert_eq!(
+        serde_json::to_value(&live_app_state).unwrap(),
+        serde_json::to_value(&replayed_app_state).unwrap()
+    );
+    assert_eq!(
+        [ocl_id_2()].into_iter().collect::<HashSet<_>>(),
+        replayed_app_state.molecule_projects_ocl_id_filter.ignore
+    );
+}
+
+// Helpers
+
+fn ocl_id_1() -> OclId {
+    const RAW: &str = "0x0101000000000000000000a1117b215dcd666dd847cfa84721480d316440faa9";
+    OclId::from_str(RAW).unwrap()
+}
+
+fn ocl_id_2() -> OclId {
+    const RAW: &str = "0x0101000000000000000000992399d367a2fa6f971dbc1647f81f999c19a70d67";
+    OclId::from_str(RAW).unwrap()
+}
+
+fn entries(events: Vec<BridgeEvent>) -> Vec<EventLogEntry> {
+    let recorded_at = Utc::now();
+
+    events
+        .into_iter()
+        .zip(0..)
+        .map(|(event, sequence)| EventLogEntry {
+            sequence,
+            recorded_at,
+            event,
+        })
+        .collect()
+}
+
+fn ocl_transferred(block_number: u64, ocl_id: OclId, from: Address, to: Address) -> BridgeEvent {
+    BridgeEvent::OclTransferred {
+        chain_id: CHAIN_ID,
+        block_number,
+        log_index: 0,
+        tx_hash: None,
+        ocl_id,
+        from,
+        to,
+    }
+}
+
+fn holder_token_transferred(
+    block_number: u64,
+    from: Address,
+    to: Address,
+    value: u64,
+) -> BridgeEvent {
+    BridgeEvent::HolderTokenTransferred {
+        chain_id: CHAIN_ID,
+        block_number,
+        log_index: 0,
+        tx_hash: None,
+        ocl_id: ocl_id_1(),
+        from,
+        to,
+        value: U256::from(value),
+    }
+}
+
+fn head_blocks_indexed(
+    to_block: u64,
+    to_block_hash: B256,
+    finalized_block_number: u64,
+) -> BridgeEvent {
+    BridgeEvent::HeadBlocksIndexed {
+        chain_id: CHAIN_ID,
+        to_block,
+        to_block_hash,
+        finalized_block_number,
+    }
+}
+
+fn versioned_file_entry(dataset_id: &str) -> VersionedFileEntry {
+    VersionedFileEntry {
+        offset: 0,
+        path: format!("/{dataset_id}"),
+    }
+}
+
+fn file(
+    dataset_id: &str,
+    molecule_access_level: MoleculeAccessLevel,
+) -> (String, VersionedFileEntryWithMoleculeAccessLevel) {
+    (
+        dataset_id.to_string(),
+        VersionedFileEntryWithMoleculeAccessLevel {
+            entry: versioned_file_entry(dataset_id),
+            molecule_access_level,
+        },
+    )
+}
+
+fn project(
+    op: OperationType,
+    actual_files_map: HashMap<String, VersionedFileEntryWithMoleculeAccessLevel>,
+) -> OffChainMoleculeProjectProjection {
+    OffChainMoleculeProjectProjection {
+        entry: MoleculeProjectEntry {
+            offset: 0,
+            op,
+            ocl_id: ocl_id_1(),
+            symbol: "PROJECT".to_string(),
+            project_account_id: "project-account".to_string(),
+            data_room_dataset_id: "data-room".to_string(),
+            announcements_dataset_id: "announcements".to_string(),
+        },
+        latest_data_room_offset: 0,
+        actual_files_map,
+        removed_files_map: HashMap::new(),
+    }
+}
@@ -0,0 +1,89 @@
+use kamu_molecule_bridge::audit_log::{
+    AccessAuditLog, AccessAuditRecord, AccessChangeCause, AccessChangeResult, FileAccessAuditLog,
+    InMemoryAccessAuditLog, NewAccessAuditRecord,
+};
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_in_memory_audit_log_retains_latest_records() {
+    let audit_log = InMemoryAccessAuditLog::new(2);
+
+    for i in 0..3 {
+        let record = audit_log.append(new_record(i)).await.unwrap();
+        assert_eq!(i, record.id);
+    }
+
+    assert_eq!(
+        vec![1, 2],
+        record_ids(&audit_log.read(0, 10).await.unwrap())
+    );
+    assert_eq!(vec![2], record_ids(&audit_log.read(2, 10).await.unwrap()));
+    assert_eq!(vec![1], record_ids(&audit_log.read(0, 1).await.unwrap()));
+    assert!(audit_log.read(3, 10).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_file_audit_log_survives_reopening() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("audit-log.ndjson");
+
+    {
+        let audit_log = FileAccessAuditLog::open(path.clone(), 3).await.unwrap();
+        for i in 0..2 {
+            audit_log.append(new_record(i)).await.unwrap();
+        }
+    }
+
+    let audit_log = FileAccessAuditLog::open(path, 3).await.unwrap();
+    let record = audit_log.append(new_record(2)).await.unwrap();
+    assert_eq!(2, record.id);
+
+    let records = audit_log.read(0, 10).await.unwrap();
+    assert_eq!(vec![0, 1, 2], record_ids(&records));
+    assert_eq!("Change 1", records[1].reason);
+}
+
+#[tokio::test]
+async fn test_file_audit_log_is_compacted() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("audit-log.ndjson");
+
+    {
+        let audit_log = FileAccessAuditLog::open(path.clone(), 2).await.unwrap();
+        for i in 0..5 {
+            audit_log.append(new_record(i)).await.unwrap();
+        }
+        assert_eq!(
+            vec![3, 4],
+            record_ids(&audit_log.read(0, 10).await.unwrap())
+        );
+    }
+
+    // NOTE: The file was rewritten once 4 records were appended.
+    let lines_count = std::fs::read_to_string(&path).unwrap().lines().count();
+    assert_eq!(3, lines_count);
+
+    let audit_log = FileAccessAuditLog::open(path, 2).await.unwrap();
+    assert_eq!(
+        vec![3, 4],
+        record_ids(&audit_log.read(0, 10).await.unwrap())
+    );
+    assert_eq!(5, audit_log.append(new_record(5)).await.unwrap().id);
+}
+
+// Helpers
+
+fn new_record(i: u64) -> NewAccessAuditRecord {
+    NewAccessAuditRecord {
+        reason: format!("Change {i}"),
+        cause: AccessChangeCause::Reconciliation,
+        operations: Vec::new(),
+        result: AccessChangeResult::Applied {
+            message: "Success".to_string(),
+        },
+    }
+}
+
+fn record_ids(records: &[AccessAuditRecord]) -> Vec<u64> {
+    records.iter().map(|record| record.id).collect()
+}
@@ -0,0 +1,152 @@
+use kamu_molecule_bridge::config::Config;
+use kamu_molecule_bridge::config_watcher::ReloadedConfig;
+use pretty_assertions::assert_eq;
+
+const BASE_CONFIG: &str = "\
+kamu_node_gql_api_endpoint: http://localhost:8080/graphql
+kamu_node_token: token
+molecule_projects_dataset_alias: molecule/projects
+molecule_projects_loading_interval_in_secs: 60
+indexing_delay_between_iterations_in_secs: 10
+";
+
+const CHAINS_CONFIG: &str = "\
+chains:
+  - chain_id: 1
+    rpc_url: https://eth.llamarpc.com
+    labnft_contract_address: '0x0000000000000000000000000000000000000002'
+    labnft_contract_birth_block: 1
+";
+
+const LIVE_PARAMETERS_CONFIG: &str = "\
+concurrency_limit: 2
+ignore_ocl_ids:
+  - '0x0101000000000000000000a1117b215dcd666dd847cfa84721480d316440faa9'
+multisig_access_policy:
+  signer_role: Reader
+";
+
+/// Continues the chain entry of `CHAINS_CONFIG`
+const CHAIN_MULTISIG_ACCESS_POLICY_CONFIG: &str =
+    "    multisig_access_policy:\n      signer_role: Reader\n";
+
+#[test]
+fn test_live_parameters_are_applied() {
+    let mut config = load_config(&format!("{BASE_CONFIG}{CHAINS_CONFIG}"));
+    let reloaded = load_config(&format!(
+        "{}{CHAINS_CONFIG}{LIVE_PARAMETERS_CONFIG}",
+        BASE_CONFIG.replace(": 10\n", ": 30\n"),
+    ));
+
+    let restart_required_parameters = config.apply_reloadable(reloaded);
+
+    assert_eq!(Vec::<&str>::new(), restart_required_parameters);
+    assert_eq!(30, config.indexing_delay_between_iterations_in_secs);
+    assert_eq!(2, config.concurrency_limit.get());
+    assert_eq!(1, config.ocl_id_filter().ignore.len());
+    assert!(config.multisig_access_policy.is_some());
+}
+
+#[test]
+fn test_chain_multisig_access_policy_is_applied() {
+    let mut config = load_config(&format!("{BASE_CONFIG}{CHAINS_CONFIG}"));
+    let reloaded = load_config(&format!(
+        "{BASE_CONFIG}{CHAINS_CONFIG}{CHAIN_MULTISIG_ACCESS_POLICY_CONFIG}"
+    ));
+    let expected_chain_configs = reloaded.chain_configs().unwrap();
+
+    let restart_required_parameters = config.apply_reloadable(reloaded);
+
+    assert_eq!(Vec::<&str>::new(), restart_required_parameters);
+    assert_eq!(expected_chain_configs, config.chain_configs().unwrap());
+}
+
+#[test]
+fn test_restart_required_parameters_are_reported() {
+    let mut config = load_config(&format!("{BASE_CONFIG}{CHAINS_CONFIG}"));
+    let reloaded = load_config(&format!(
+        "{}{}",
+        BASE_CONFIG.replace("token: token", "token: new-token"),
+        CHAINS_CONFIG.replace("llamarpc", "example"),
+    ));
+
+    let restart_required_parameters = config.apply_reloadable(reloaded);
+
+    assert_eq!(
+        vec!["kamu_node_token", "chains"],
+        restart_required_parameters
+    );
+    assert_eq!("token", config.kamu_node_token.expose());
+    assert_eq!(
+        "https://eth.llamarpc.com",
+        config.chain_configs().unwrap()[0].rpc_url
+    );
+}
+
+#[test]
+fn test_secrets_are_redacted() {
+    let config = load_config(&format!("{BASE_CONFIG}{CHAINS_CONFIG}"));
+
+    let config_debug = format!("{config:?}");
+
+    assert!(!config_debug.contains("token: token"));
+    assert!(config_debug.contains("kamu_node_token: <redacted>"));
+}
+
+#[test]
+fn test_invalid_reloaded_config_is_rejected() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("config.yaml");
+    // NOTE: Chains are not configured.
+    std::fs::write(&path, BASE_CONFIG).unwrap();
+
+    assert!(ReloadedConfig::load(&path).is_err());
+}
+
+#[test]
+fn test_zero_concurrency_limit_is_rejected() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("config.yaml");
+    std::fs::write(
+        &path,
+        format!("{BASE_CONFIG}{CHAINS_CONFIG}concurrency_limit: 0\n"),
+    )
+    .unwrap();
+
+    assert!(ReloadedConfig::load(&path).is_err());
+}
+
+#[test]
+fn test_reloaded_config_loads_ocl_id_filter_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("config.yaml");
+    let filter_file_path = temp_dir.path().join("ocl-id-filter.yaml");
+    std::fs::write(
+        &path,
+        format!(
+            "{BASE_CONFIG}{CHAINS_CONFIG}ocl_id_filter_file_path: {}\n",
+            filter_file_path.display()
+        ),
+    )
+    .unwrap();
+
+    // NOTE: The file is missing, so the current config is kept.
+    assert!(ReloadedConfig::load(&path).is_err());
+
+    std::fs::write(
+        &filter_file_path,
+        "ignore_ocl_ids:\n  - '0x0101000000000000000000a1117b215dcd666dd847cfa84721480d316440faa9'\n",
+    )
+    .unwrap();
+    let reloaded_config = ReloadedConfig::load(&path).unwrap();
+    assert_eq!(1, reloaded_config.ocl_id_filter.ignore.len());
+    assert_eq!(None, reloaded_config.ocl_id_filter.only);
+}
+
+fn load_config(contents: &str) -> Config {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("config.yaml");
+    std::fs::write(&path, contents).unwrap();
+
+    Config::builder().file(&path).load().unwrap()
+}
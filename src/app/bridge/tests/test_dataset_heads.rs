@@ -0,0 +1,54 @@
+use kamu_molecule_bridge::dataset_heads::DatasetHeads;
+
+const DATA_ROOM_ID: &str = "did:odf:fed01data";
+const FILE_ID: &str = "did:odf:fed01file";
+
+#[test]
+fn test_moved_head_is_changed() {
+    let scanned = dataset_heads(Some("projects-1"), &[(DATA_ROOM_ID, "data-room-1")]);
+    let current = dataset_heads(Some("projects-2"), &[(DATA_ROOM_ID, "data-room-2")]);
+
+    assert!(current.is_molecule_projects_changed(&scanned));
+    assert!(current.is_dataset_changed(&scanned, &DATA_ROOM_ID.to_string()));
+}
+
+#[test]
+fn test_same_head_is_not_changed() {
+    let scanned = dataset_heads(
+        Some("projects-1"),
+        &[(DATA_ROOM_ID, "data-room-1"), (FILE_ID, "file-1")],
+    );
+    let current = dataset_heads(
+        Some("projects-1"),
+        &[(DATA_ROOM_ID, "data-room-1"), (FILE_ID, "file-2")],
+    );
+
+    assert!(!current.is_molecule_projects_changed(&scanned));
+    assert!(!current.is_dataset_changed(&scanned, &DATA_ROOM_ID.to_string()));
+    assert!(current.is_dataset_changed(&scanned, &FILE_ID.to_string()));
+}
+
+#[test]
+fn test_unknown_head_is_changed() {
+    let scanned = dataset_heads(Some("projects-1"), &[(DATA_ROOM_ID, "data-room-1")]);
+
+    // Failed to fetch
+    let current = dataset_heads(None, &[]);
+    assert!(current.is_molecule_projects_changed(&scanned));
+    assert!(current.is_dataset_changed(&scanned, &DATA_ROOM_ID.to_string()));
+
+    // Not scanned yet
+    let current = dataset_heads(Some("projects-1"), &[(FILE_ID, "file-1")]);
+    assert!(current.is_dataset_changed(&scanned, &FILE_ID.to_string()));
+    assert!(current.is_molecule_projects_changed(&DatasetHeads::default()));
+}
+
+fn dataset_heads(molecule_projects: Option<&str>, datasets: &[(&str, &str)]) -> DatasetHeads {
+    DatasetHeads {
+        molecule_projects: molecule_projects.map(ToString::to_string),
+        datasets: datasets
+            .iter()
+            .map(|(dataset_id, head)| (dataset_id.to_string(), head.to_string()))
+            .collect(),
+    }
+}
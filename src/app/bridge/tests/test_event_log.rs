@@ -0,0 +1,185 @@
+use alloy::primitives::B256;
+use kamu_molecule_bridge::events::{
+    AppStateProjector, BridgeEvent, EventLog, EventLogEntry, FileEventLog, InMemoryEventLog,
+};
+use pretty_assertions::assert_eq;
+
+const FINGERPRINT_1: B256 = B256::repeat_byte(0x01);
+const FINGERPRINT_2: B256 = B256::repeat_byte(0x02);
+
+#[tokio::test]
+async fn test_in_memory_event_log_retains_latest_entries() {
+    let event_log = InMemoryEventLog::new(2);
+
+    let entries = event_log.append(events(3)).await.unwrap();
+    assert_eq!(vec![0, 1, 2], sequences(&entries));
+
+    assert_eq!(vec![1, 2], sequences(&event_log.read(0, 10).await.unwrap()));
+    assert_eq!(vec![2], sequences(&event_log.read(2, 10).await.unwrap()));
+    assert_eq!(vec![1], sequences(&event_log.read(0, 1).await.unwrap()));
+    assert!(event_log.read(3, 10).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_in_memory_event_log_resumes_after_restored_state() {
+    let event_log = InMemoryEventLog::new(10);
+
+    event_log.resume(5).await.unwrap();
+
+    let entries = event_log.append(events(2)).await.unwrap();
+    assert_eq!(vec![5, 6], sequences(&entries));
+    assert_eq!(vec![5, 6], sequences(&event_log.read(0, 10).await.unwrap()));
+
+    // Entries that the restored state doesn't reflect are dropped
+    event_log.resume(6).await.unwrap();
+    assert_eq!(vec![5], sequences(&event_log.read(0, 10).await.unwrap()));
+    assert_eq!(
+        vec![6],
+        sequences(&event_log.append(events(1)).await.unwrap())
+    );
+
+    assert!(event_log.read_all().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_file_event_log_survives_reopening() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("event-log.ndjson");
+
+    {
+        let event_log = FileEventLog::open(path.clone(), FINGERPRINT_1, 2)
+            .await
+            .unwrap();
+        event_log.append(events(3)).await.unwrap();
+    }
+
+    let event_log = FileEventLog::open(path, FINGERPRINT_1, 2).await.unwrap();
+    assert_eq!(
+        vec![3],
+        sequences(&event_log.append(events(1)).await.unwrap())
+    );
+
+    // NOTE: Entries beyond the retained ones are read from the file.
+    assert_eq!(vec![0, 1], sequences(&event_log.read(0, 2).await.unwrap()));
+    assert_eq!(vec![2, 3], sequences(&event_log.read(2, 10).await.unwrap()));
+    assert_eq!(
+        vec![0, 1, 2, 3],
+        sequences(&event_log.read_all().await.unwrap().unwrap())
+    );
+}
+
+#[tokio::test]
+async fn test_file_event_log_state_is_replayed() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("event-log.ndjson");
+
+    {
+        let event_log = FileEventLog::open(path.clone(), FINGERPRINT_1, 1)
+            .await
+            .unwrap();
+        event_log
+            .append(vec![
+                BridgeEvent::ChainStateReset {
+                    chain_id: 1,
+                    latest_indexed_block_number: 9,
+                },
+                BridgeEvent::BlocksIndexed {
+                    chain_id: 1,
+                    to_block: 20,
+                },
+            ])
+            .await
+            .unwrap();
+    }
+
+    let event_log = FileEventLog::open(path, FINGERPRINT_1, 1).await.unwrap();
+    let app_state = AppStateProjector::replay(&event_log.read_all().await.unwrap().unwrap());
+
+    assert_eq!(20, app_state.chains[&1].latest_indexed_block_number);
+    assert_eq!(2, app_state.event_log_next_sequence);
+}
+
+#[tokio::test]
+async fn test_file_event_log_resumes_after_restored_state() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("event-log.ndjson");
+
+    let event_log = FileEventLog::open(path.clone(), FINGERPRINT_1, 10)
+        .await
+        .unwrap();
+    event_log.append(events(3)).await.unwrap();
+
+    // The restored state is ahead of the log
+    assert!(event_log.resume(4).await.is_err());
+
+    // Entries that the restored state doesn't reflect are dropped from the file
+    event_log.resume(2).await.unwrap();
+    assert_eq!(
+        vec![2],
+        sequences(&event_log.append(events(1)).await.unwrap())
+    );
+
+    let event_log = FileEventLog::open(path, FINGERPRINT_1, 10).await.unwrap();
+    assert_eq!(
+        vec![0, 1, 2],
+        sequences(&event_log.read_all().await.unwrap().unwrap())
+    );
+}
+
+#[tokio::test]
+async fn test_file_event_log_drops_incomplete_last_entry() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("event-log.ndjson");
+
+    {
+        let event_log = FileEventLog::open(path.clone(), FINGERPRINT_1, 10)
+            .await
+            .unwrap();
+        event_log.append(events(2)).await.unwrap();
+    }
+
+    // NOTE: As if a crash happened in the middle of writing.
+    let mut content = std::fs::read_to_string(&path).unwrap();
+    content.truncate(content.len() - 10);
+    std::fs::write(&path, content).unwrap();
+
+    let event_log = FileEventLog::open(path, FINGERPRINT_1, 10).await.unwrap();
+    assert_eq!(
+        vec![1],
+        sequences(&event_log.append(events(1)).await.unwrap())
+    );
+    assert_eq!(
+        vec![0, 1],
+        sequences(&event_log.read_all().await.unwrap().unwrap())
+    );
+}
+
+#[tokio::test]
+async fn test_file_event_log_of_other_configuration_is_rejected() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("event-log.ndjson");
+
+    FileEventLog::open(path.clone(), FINGERPRINT_1, 10)
+        .await
+        .unwrap()
+        .append(events(1))
+        .await
+        .unwrap();
+
+    assert!(FileEventLog::open(path, FINGERPRINT_2, 10).await.is_err());
+}
+
+// Helpers
+
+fn events(count: u64) -> Vec<BridgeEvent> {
+    (0..count)
+        .map(|to_block| BridgeEvent::BlocksIndexed {
+            chain_id: 1,
+            to_block,
+        })
+        .collect()
+}
+
+fn sequences(entries: &[EventLogEntry]) -> Vec<u64> {
+    entries.iter().map(|entry| entry.sequence).collect()
+}
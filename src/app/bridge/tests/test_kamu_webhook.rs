@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode, Uri};
+use kamu_molecule_bridge::config::Secret;
+use kamu_molecule_bridge::http_server::{KamuWebhookApi, kamu_webhook_router, redact_query_token};
+use pretty_assertions::assert_eq;
+use tower::ServiceExt as _;
+
+const TOKEN: &str = "webhook-token";
+
+/// Delivery of a `DATASET.REF.UPDATED` event as Kamu Node sends it
+const KAMU_PAYLOAD: &str = r#"{
+    "version": "1",
+    "datasetId": "did:odf:fed01data",
+    "ownerAccountId": "did:odf:fed01owner",
+    "blockRef": "head",
+    "oldHash": "f1620old",
+    "newHash": "f1620new"
+}"#;
+
+#[tokio::test]
+async fn test_kamu_delivery_requests_projects_loading() {
+    let kamu_webhook_api = kamu_webhook_api();
+
+    let status = deliver(
+        &kamu_webhook_api,
+        &format!("/system/webhooks/kamu?token={TOKEN}"),
+    )
+    .await;
+
+    assert_eq!(StatusCode::ACCEPTED, status);
+    tokio::time::timeout(
+        Duration::from_secs(1),
+        kamu_webhook_api.projects_loading_requested.notified(),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_kamu_delivery_with_wrong_token_is_rejected() {
+    let kamu_webhook_api = kamu_webhook_api();
+
+    for uri in [
+        "/system/webhooks/kamu?token=wrong-token",
+        "/system/webhooks/kamu",
+    ] {
+        assert_eq!(
+            StatusCode::UNAUTHORIZED,
+            deliver(&kamu_webhook_api, uri).await
+        );
+    }
+
+    assert!(
+        tokio::time::timeout(
+            Duration::from_millis(100),
+            kamu_webhook_api.projects_loading_requested.notified(),
+        )
+        .await
+        .is_err()
+    );
+}
+
+#[test]
+fn test_query_token_is_redacted() {
+    let uri = Uri::from_static("/system/webhooks/kamu?a=1&token=secret&b=2");
+
+    assert_eq!(
+        "/system/webhooks/kamu?a=1&token=<redacted>&b=2",
+        redact_query_token(&uri)
+    );
+}
+
+// Helpers
+
+fn kamu_webhook_api() -> KamuWebhookApi {
+    KamuWebhookApi {
+        token: Secret::new(TOKEN.to_string()),
+        projects_loading_requested: Arc::new(tokio::sync::Notify::new()),
+    }
+}
+
+async fn deliver(kamu_webhook_api: &KamuWebhookApi, uri: &str) -> StatusCode {
+    let request = Request::post(uri)
+        .header("content-type", "application/json")
+        .header("x-webhook-event-type", "DATASET.REF.UPDATED")
+        .header("x-webhook-delivery-attempt", "1")
+        .body(Body::from(KAMU_PAYLOAD))
+        .unwrap();
+
+    kamu_webhook_router(kamu_webhook_api.clone())
+        .oneshot(request)
+        .await
+        .unwrap()
+        .status()
+}
@@ -0,0 +1,54 @@
+use kamu_molecule_bridge::ocl_id_filter_file::OclIdFilterFile;
+use kamu_node_api_client::OclIdFilter;
+use molecule_ocl::entities::OclId;
+use pretty_assertions::assert_eq;
+
+const OCL_ID_1: &str = "0x0101000000000000000000a1117b215dcd666dd847cfa84721480d316440faa9";
+const OCL_ID_2: &str = "0x0101000000000000000000a754158f71186f2cd00d895eff64f926c4fcd69179";
+
+#[test]
+fn test_file_is_loaded() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("ocl-id-filter.yaml");
+
+    // NOTE: Upper case ids are normalized.
+    let upper_case_ocl_id = format!("0x{}", OCL_ID_1[2..].to_uppercase());
+    std::fs::write(
+        &path,
+        format!("ignore_ocl_ids:\n  - '{upper_case_ocl_id}'\n"),
+    )
+    .unwrap();
+    assert_eq!(
+        OclIdFilter {
+            ignore: [ocl_id(OCL_ID_1)].into(),
+            only: None,
+        },
+        OclIdFilterFile::load(&path).unwrap()
+    );
+
+    std::fs::write(&path, format!("only_ocl_ids:\n  - '{OCL_ID_2}'\n")).unwrap();
+    assert_eq!(
+        OclIdFilter {
+            ignore: Default::default(),
+            only: Some([ocl_id(OCL_ID_2)].into()),
+        },
+        OclIdFilterFile::load(&path).unwrap()
+    );
+}
+
+#[test]
+fn test_invalid_or_missing_file_is_rejected() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("ocl-id-filter.yaml");
+
+    std::fs::write(&path, "only_ocl_ids:\n  - 'not-an-ocl-id'\n").unwrap();
+    assert!(OclIdFilterFile::load(&path).is_err());
+
+    // NOTE: A missing file must not silently allow all projects.
+    std::fs::remove_file(&path).unwrap();
+    assert!(OclIdFilterFile::load(&path).is_err());
+}
+
+fn ocl_id(value: &str) -> OclId {
+    value.parse().unwrap()
+}
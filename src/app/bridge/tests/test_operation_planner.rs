@@ -0,0 +1,86 @@
+use kamu_molecule_bridge::operation_planner::{
+    AppliedRolesMap, collapse_operations, plan_operations, record_applied_operations,
+};
+use kamu_node_api_client::{
+    AccountDatasetRelationOperation, DatasetAccessRole, DatasetRoleOperation,
+};
+use pretty_assertions::assert_eq;
+
+const OWNER_A: &str = "did:pkh:eip155:1:0x1111111111111111111111111111111111111111";
+const OWNER_B: &str = "did:pkh:eip155:1:0x2222222222222222222222222222222222222222";
+
+#[test]
+fn test_set_unset_pairs_are_collapsed_into_the_latest_operation() {
+    let operations = vec![
+        AccountDatasetRelationOperation::maintainer_access(OWNER_A.into(), "dataset-1".into()),
+        AccountDatasetRelationOperation::reader_access(OWNER_B.into(), "dataset-1".into()),
+        AccountDatasetRelationOperation::revoke_access(OWNER_A.into(), "dataset-1".into()),
+        AccountDatasetRelationOperation::reader_access(OWNER_B.into(), "dataset-1".into()),
+    ];
+
+    assert_eq!(
+        vec![
+            ("dataset-1", OWNER_A, DatasetRoleOperation::Unset),
+            (
+                "dataset-1",
+                OWNER_B,
+                DatasetRoleOperation::Set(DatasetAccessRole::Reader)
+            ),
+        ],
+        to_tuples(&collapse_operations(&operations))
+    );
+}
+
+#[test]
+fn test_operations_equal_to_applied_ones_are_dropped() {
+    let mut applied_roles = AppliedRolesMap::new();
+    record_applied_operations(
+        &mut applied_roles,
+        &[
+            AccountDatasetRelationOperation::maintainer_access(
+                OWNER_A.to_uppercase(),
+                "dataset-1".into(),
+            ),
+            AccountDatasetRelationOperation::revoke_access(OWNER_B.into(), "dataset-1".into()),
+        ],
+    );
+
+    let operations = vec![
+        // No-ops
+        AccountDatasetRelationOperation::maintainer_access(OWNER_A.into(), "dataset-1".into()),
+        AccountDatasetRelationOperation::revoke_access(OWNER_B.into(), "dataset-1".into()),
+        // Changed role
+        AccountDatasetRelationOperation::reader_access(OWNER_B.into(), "dataset-1".into()),
+        // Never applied
+        AccountDatasetRelationOperation::revoke_access(OWNER_A.into(), "dataset-2".into()),
+    ];
+
+    assert_eq!(
+        vec![
+            (
+                "dataset-1",
+                OWNER_B,
+                DatasetRoleOperation::Set(DatasetAccessRole::Reader)
+            ),
+            ("dataset-2", OWNER_A, DatasetRoleOperation::Unset),
+        ],
+        to_tuples(&plan_operations(&applied_roles, &operations))
+    );
+}
+
+// Helpers
+
+fn to_tuples(
+    operations: &[AccountDatasetRelationOperation],
+) -> Vec<(&str, &str, DatasetRoleOperation)> {
+    operations
+        .iter()
+        .map(|operation| {
+            (
+                operation.dataset_id.as_str(),
+                operation.account_id.as_str(),
+                operation.operation,
+            )
+        })
+        .collect()
+}
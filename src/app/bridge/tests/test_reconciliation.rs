@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use kamu_molecule_bridge::reconciliation::reconcile_dataset_roles;
+use kamu_node_api_client::{
+    AccountDatasetRelationOperation, DatasetAccessRole, DatasetAccountRolesMap,
+    DatasetRoleOperation,
+};
+use pretty_assertions::assert_eq;
+
+const OWNER_A: &str = "did:pkh:eip155:1:0x1111111111111111111111111111111111111111";
+const OWNER_B: &str = "did:pkh:eip155:1:0x2222222222222222222222222222222222222222";
+const PROJECT_ACCOUNT: &str = "did:odf:fed01project";
+
+struct ReconcileCase {
+    desired: Vec<(&'static str, &'static str, DatasetAccessRole)>,
+    desired_empty_datasets: Vec<&'static str>,
+    actual: Vec<(&'static str, &'static str, DatasetAccessRole)>,
+    expected: Vec<(&'static str, &'static str, DatasetRoleOperation)>,
+}
+
+#[rstest::rstest]
+#[case::in_sync(ReconcileCase {
+    desired: vec![("dataset-1", OWNER_A, DatasetAccessRole::Maintainer)],
+    desired_empty_datasets: vec![],
+    actual: vec![("dataset-1", OWNER_A, DatasetAccessRole::Maintainer)],
+    expected: vec![],
+})]
+#[case::missing_role(ReconcileCase {
+    desired: vec![
+        ("dataset-1", OWNER_A, DatasetAccessRole::Maintainer),
+        ("dataset-2", OWNER_A, DatasetAccessRole::Maintainer),
+    ],
+    desired_empty_datasets: vec![],
+    actual: vec![("dataset-1", OWNER_A, DatasetAccessRole::Maintainer)],
+    expected: vec![(
+        "dataset-2",
+        OWNER_A,
+        DatasetRoleOperation::Set(DatasetAccessRole::Maintainer),
+    )],
+})]
+#[case::different_role(ReconcileCase {
+    desired: vec![("dataset-1", OWNER_A, DatasetAccessRole::Maintainer)],
+    desired_empty_datasets: vec![],
+    actual: vec![("dataset-1", OWNER_A, DatasetAccessRole::Reader)],
+    expected: vec![(
+        "dataset-1",
+        OWNER_A,
+        DatasetRoleOperation::Set(DatasetAccessRole::Maintainer),
+    )],
+})]
+#[case::stray_role(ReconcileCase {
+    desired: vec![("dataset-1", OWNER_A, DatasetAccessRole::Maintainer)],
+    desired_empty_datasets: vec!["dataset-2"],
+    actual: vec![
+        ("dataset-1", OWNER_A, DatasetAccessRole::Maintainer),
+        ("dataset-1", OWNER_B, DatasetAccessRole::Editor),
+        ("dataset-2", OWNER_A, DatasetAccessRole::Reader),
+    ],
+    expected: vec![
+        ("dataset-1", OWNER_B, DatasetRoleOperation::Unset),
+        ("dataset-2", OWNER_A, DatasetRoleOperation::Unset),
+    ],
+})]
+#[case::unmanaged_accounts_are_kept(ReconcileCase {
+    desired: vec![("dataset-1", OWNER_A, DatasetAccessRole::Maintainer)],
+    desired_empty_datasets: vec![],
+    actual: vec![
+        ("dataset-1", OWNER_A, DatasetAccessRole::Maintainer),
+        ("dataset-1", PROJECT_ACCOUNT, DatasetAccessRole::Maintainer),
+    ],
+    expected: vec![],
+})]
+#[case::unknown_datasets_are_ignored(ReconcileCase {
+    desired: vec![],
+    desired_empty_datasets: vec![],
+    actual: vec![("dataset-1", OWNER_A, DatasetAccessRole::Maintainer)],
+    expected: vec![],
+})]
+fn test_reconcile_dataset_roles(#[case] case: ReconcileCase) {
+    let mut desired = roles_map(case.desired);
+    for dataset_id in case.desired_empty_datasets {
+        desired.entry(dataset_id.to_string()).or_default();
+    }
+    let actual = roles_map(case.actual);
+
+    let operations = reconcile_dataset_roles(&desired, &actual);
+
+    assert_eq!(
+        case.expected
+            .into_iter()
+            .map(|(dataset_id, account_id, operation)| {
+                (dataset_id.to_string(), account_id.to_string(), operation)
+            })
+            .collect::<Vec<_>>(),
+        flatten(operations)
+    );
+}
+
+#[test]
+fn test_reconcile_dataset_roles_ignores_account_id_case() {
+    const CHECKSUMMED_OWNER: &str = "did:pkh:eip155:1:0xAbCdEf0000000000000000000000000000000000";
+
+    let desired = roles_map(vec![(
+        "dataset-1",
+        CHECKSUMMED_OWNER,
+        DatasetAccessRole::Maintainer,
+    )]);
+    let actual = roles_map(vec![(
+        "dataset-1",
+        &CHECKSUMMED_OWNER.to_lowercase(),
+        DatasetAccessRole::Maintainer,
+    )]);
+
+    let operations = reconcile_dataset_roles(&desired, &actual);
+
+    assert_eq!(
+        Vec::<(String, String, DatasetRoleOperation)>::new(),
+        flatten(operations)
+    );
+}
+
+// Helpers
+
+fn roles_map(roles: Vec<(&str, &str, DatasetAccessRole)>) -> DatasetAccountRolesMap {
+    let mut map: DatasetAccountRolesMap = HashMap::new();
+
+    for (dataset_id, account_id, role) in roles {
+        map.entry(dataset_id.to_string())
+            .or_default()
+            .insert(account_id.to_string(), role);
+    }
+
+    map
+}
+
+fn flatten(
+    operations: Vec<AccountDatasetRelationOperation>,
+) -> Vec<(String, String, DatasetRoleOperation)> {
+    operations
+        .into_iter()
+        .map(|operation| {
+            (
+                operation.dataset_id,
+                operation.account_id,
+                operation.operation,
+            )
+        })
+        .collect()
+}
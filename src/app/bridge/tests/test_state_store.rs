@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use alloy::primitives::B256;
+use kamu_molecule_bridge::state::AppState;
+use kamu_molecule_bridge::state_store::{AppStateStore, FileAppStateStore, ReadOnlyAppStateStore};
+use pretty_assertions::assert_eq;
+
+const FINGERPRINT_1: B256 = B256::repeat_byte(0x01);
+const FINGERPRINT_2: B256 = B256::repeat_byte(0x02);
+
+#[tokio::test]
+async fn test_file_state_store_round_trip() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("state.json");
+    let state_store = FileAppStateStore::new(path, FINGERPRINT_1);
+
+    assert!(state_store.load().await.unwrap().is_none());
+
+    state_store.save(&app_state()).await.unwrap();
+
+    let state = state_store.load().await.unwrap().unwrap();
+    assert_eq!(Some(42), state.molecule_projects_dataset_offset);
+    assert_eq!(7, state.audit_log_next_pushed_record_id);
+    assert_eq!(3, state.access_changes_next_pushed_record_id);
+}
+
+#[tokio::test]
+async fn test_file_state_store_ignores_fingerprint_mismatch() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("state.json");
+
+    FileAppStateStore::new(path.clone(), FINGERPRINT_1)
+        .save(&app_state())
+        .await
+        .unwrap();
+
+    let state_store = FileAppStateStore::new(path, FINGERPRINT_2);
+    assert!(state_store.load().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_file_state_store_ignores_corrupted_state() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("state.json");
+    let state_store = FileAppStateStore::new(path.clone(), FINGERPRINT_1);
+
+    state_store.save(&app_state()).await.unwrap();
+
+    // Still a valid state, but not the one the checksum was computed for
+    let content = std::fs::read_to_string(&path).unwrap();
+    let corrupted_content = content.replace(
+        "\"audit_log_next_pushed_record_id\":7",
+        "\"audit_log_next_pushed_record_id\":8",
+    );
+    assert_ne!(content, corrupted_content);
+    std::fs::write(&path, corrupted_content).unwrap();
+
+    assert!(state_store.load().await.unwrap().is_none());
+
+    // Not a snapshot at all
+    std::fs::write(&path, "garbage").unwrap();
+
+    assert!(state_store.load().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_read_only_state_store_is_not_saved() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("state.json");
+    let file_state_store = Arc::new(FileAppStateStore::new(path, FINGERPRINT_1));
+    file_state_store.save(&app_state()).await.unwrap();
+
+    let state_store = ReadOnlyAppStateStore::new(file_state_store);
+    let mut state = state_store.load().await.unwrap().unwrap();
+    state.audit_log_next_pushed_record_id = 8;
+    state_store.save(&state).await.unwrap();
+
+    let state = state_store.load().await.unwrap().unwrap();
+    assert_eq!(7, state.audit_log_next_pushed_record_id);
+}
+
+// Helpers
+
+fn app_state() -> AppState {
+    AppState {
+        molecule_projects_dataset_offset: Some(42),
+        audit_log_next_pushed_record_id: 7,
+        access_changes_next_pushed_record_id: 3,
+        ..Default::default()
+    }
+}
@@ -1,9 +1,11 @@
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 use alloy::primitives::Address;
-use eyre::bail;
+use eyre::{ContextCompat as _, bail};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct DidPhk {
     caip2: &'static str,
     address: Address,
@@ -32,3 +34,29 @@ impl Display for DidPhk {
         write!(f, "did:pkh:{}:{}", self.caip2, self.address)
     }
 }
+
+impl FromStr for DidPhk {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (chain_id, address) = s
+            .strip_prefix("did:pkh:eip155:")
+            .and_then(|caip10| caip10.split_once(':'))
+            .with_context(|| format!("Unexpected DID: {s}"))?;
+
+        Self::new_from_chain_id(chain_id.parse()?, address.parse()?)
+    }
+}
+
+impl Serialize for DidPhk {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DidPhk {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
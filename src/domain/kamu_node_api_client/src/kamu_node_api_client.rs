@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use async_trait::async_trait;
 use eyre::bail;
@@ -6,18 +6,25 @@ use molecule_ocl::entities::OclId;
 use serde::{Deserialize, Serialize};
 
 use crate::did_phk::DidPhk;
+use crate::ocl_id_filter::OclIdFilter;
 
-#[cfg_attr(
-    any(feature = "testing", test),
-    mockall::automock,
-    allow(clippy::ref_option_ref)
-)]
+#[cfg_attr(any(feature = "testing", test), mockall::automock)]
 #[async_trait]
 pub trait KamuNodeApiClient {
+    /// Returns the head of the `molecule/projects` dataset, `None` if it was
+    /// not found
+    async fn get_molecule_projects_head(&self) -> eyre::Result<Option<DatasetHead>>;
+
+    /// Returns the heads of the datasets.
+    /// Datasets that were not found are omitted.
+    async fn get_dataset_heads(&self, dataset_ids: Vec<DatasetID>)
+    -> eyre::Result<DatasetHeadsMap>;
+
+    /// Returns the latest entries of the projects allowed by the filter
     async fn get_molecule_project_entries<'a>(
         &self,
         offset: u64,
-        maybe_ignore_ocl_ids: Option<&'a HashSet<String>>,
+        ocl_id_filter: &'a OclIdFilter,
     ) -> eyre::Result<Vec<MoleculeProjectEntry>>;
 
     async fn get_versioned_files_entries_by_data_rooms(
@@ -32,20 +39,45 @@ pub trait KamuNodeApiClient {
 
     async fn create_wallet_accounts(&self, did_pkhs: Vec<DidPhk>) -> eyre::Result<()>;
 
+    /// Returns the message of the Kamu Node response
     async fn apply_account_dataset_relations(
         &self,
         operations: Vec<AccountDatasetRelationOperation>,
-    ) -> eyre::Result<()>;
+    ) -> eyre::Result<String>;
 
     async fn resolve_datasets(
         &self,
         dataset_ids: Vec<DatasetID>,
     ) -> eyre::Result<DatasetResolution>;
+
+    /// Returns the roles currently set for the datasets.
+    /// Datasets that were not found are omitted.
+    async fn get_dataset_account_roles(
+        &self,
+        dataset_ids: Vec<DatasetID>,
+    ) -> eyre::Result<DatasetAccountRolesMap>;
+
+    /// Sets the same visibility for all datasets.
+    /// Datasets that were not found are skipped.
+    async fn set_datasets_visibility(
+        &self,
+        dataset_ids: Vec<DatasetID>,
+        visibility: DatasetVisibility,
+    ) -> eyre::Result<()>;
+
+    /// Pushes records (newline-delimited JSON) into a root dataset via its
+    /// push source
+    async fn ingest_records(&self, dataset_alias: &str, records: String) -> eyre::Result<()>;
 }
 
 pub type DatasetID = String;
 pub type AccountID = String;
 
+/// Hash of the head metadata block: it moves on every change of the dataset,
+/// so comparing heads is a cheap way to detect changes
+pub type DatasetHead = String;
+pub type DatasetHeadsMap = HashMap<DatasetID, DatasetHead>;
+
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum OperationType {
@@ -70,7 +102,7 @@ impl TryFrom<u8> for OperationType {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoleculeProjectEntry {
     pub offset: u64,
     pub op: OperationType,
@@ -99,7 +131,7 @@ pub struct VersionedFilesEntries {
 
 pub type ChangedVersionedFiles = HashMap<DatasetID, VersionedFileEntry>;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionedFileEntry {
     pub offset: u64,
     pub path: String,
@@ -109,7 +141,7 @@ pub type MoleculeAccessLevelEntryMap =
     HashMap</* versioned_file_dataset_id */ DatasetID, MoleculeAccessLevel>;
 
 // https://discord.com/channels/@me/1364902681159794688/1394272024746135644
-#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum MoleculeAccessLevel {
     #[serde(alias = "PUBLIC")]
@@ -121,6 +153,15 @@ pub enum MoleculeAccessLevel {
     // NOTE: plural variants only occur for molecule.dev / testnet
     #[serde(alias = "HOLDER", alias = "holders", alias = "HOLDERS")]
     Holder,
+    /// Value not supported yet, kept as is
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl MoleculeAccessLevel {
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
 }
 
 #[derive(Debug)]
@@ -129,7 +170,7 @@ pub struct DataRoomDatasetIdWithOffset {
     pub offset: u64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountDatasetRelationOperation {
     pub account_id: AccountID,
     pub operation: DatasetRoleOperation,
@@ -153,6 +194,18 @@ impl AccountDatasetRelationOperation {
         }
     }
 
+    pub fn role_access(
+        account_id: AccountID,
+        dataset_id: DatasetID,
+        role: DatasetAccessRole,
+    ) -> Self {
+        Self {
+            account_id,
+            operation: DatasetRoleOperation::Set(role),
+            dataset_id,
+        }
+    }
+
     pub fn revoke_access(account_id: AccountID, dataset_id: DatasetID) -> Self {
         Self {
             account_id,
@@ -162,18 +215,28 @@ impl AccountDatasetRelationOperation {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DatasetRoleOperation {
     Set(DatasetAccessRole),
     Unset,
 }
 
-#[derive(Debug, Copy, Clone, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DatasetAccessRole {
     Reader,
+    Editor,
     Maintainer,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DatasetVisibility {
+    Private,
+    /// Readable by everyone, including anonymous users
+    Public,
+}
+
+pub type DatasetAccountRolesMap = HashMap<DatasetID, HashMap<AccountID, DatasetAccessRole>>;
+
 #[derive(Debug)]
 pub struct DatasetResolution {
     pub resolved_dataset_ids: Vec<DatasetID>,
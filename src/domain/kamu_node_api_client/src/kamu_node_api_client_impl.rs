@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 
@@ -11,7 +11,11 @@ use serde::{Deserialize, Serialize};
 use crate::did_phk::DidPhk;
 use crate::*;
 
-const MAX_SQL_QUERY_LIMIT: usize = 10_000;
+/// Number of rows requested at once, larger results are paginated
+const SQL_QUERY_PAGE_SIZE: NonZeroUsize = NonZeroUsize::new(10_000).unwrap();
+
+/// Number of datasets whose heads are requested at once
+const DATASET_HEADS_BATCH_SIZE: usize = 100;
 
 pub struct KamuNodeApiClientImpl {
     gql_api_endpoint: String,
@@ -21,6 +25,7 @@ pub struct KamuNodeApiClientImpl {
 
     metric_gql_requests_num_total: prometheus::IntCounter,
     metric_gql_errors_num_total: prometheus::IntCounter,
+    metric_unknown_molecule_access_levels_num_total: prometheus::IntCounterVec,
 
     dry_run: bool,
 }
@@ -32,6 +37,7 @@ impl KamuNodeApiClientImpl {
         molecule_projects_dataset_alias: String,
         metric_gql_requests_num_total: prometheus::IntCounter,
         metric_gql_errors_num_total: prometheus::IntCounter,
+        metric_unknown_molecule_access_levels_num_total: prometheus::IntCounterVec,
         dry_run: bool,
     ) -> Self {
         let http_client = {
@@ -51,18 +57,33 @@ impl KamuNodeApiClientImpl {
             molecule_projects_dataset_alias,
             metric_gql_requests_num_total,
             metric_gql_errors_num_total,
+            metric_unknown_molecule_access_levels_num_total,
             dry_run,
         }
     }
 
-    async fn sql_query<T: for<'de> Deserialize<'de>>(&self, sql: String) -> eyre::Result<T> {
+    /// Returns all rows of the query, requested page by page (see
+    /// [`fetch_all_pages`]). The query must be ordered by a unique key.
+    async fn sql_query<T: for<'de> Deserialize<'de>>(&self, sql: String) -> eyre::Result<Vec<T>> {
+        fetch_all_pages(SQL_QUERY_PAGE_SIZE, |skip, limit| {
+            self.sql_query_page(&sql, skip, limit)
+        })
+        .await
+    }
+
+    async fn sql_query_page<T: for<'de> Deserialize<'de>>(
+        &self,
+        sql: &str,
+        skip: usize,
+        limit: usize,
+    ) -> eyre::Result<SqlQueryPage<T>> {
         use sql_query::SqlQueryDataQuery;
 
         let response = self
             .gql_api_call::<SqlQuery>(sql_query::Variables {
-                sql,
-                // TODO: pagination if limit reached
-                limit: i64::try_from(MAX_SQL_QUERY_LIMIT).unwrap(),
+                sql: sql.to_string(),
+                skip: i64::try_from(skip)?,
+                limit: i64::try_from(limit)?,
             })
             .await?;
         let raw_query_result = match response.data.query {
@@ -71,9 +92,12 @@ impl KamuNodeApiClientImpl {
                 bail!("Query failed with error: {e:#?}")
             }
         };
-        let query_result: T = serde_json::from_str(&raw_query_result.data.content)?;
+        let rows: Vec<T> = serde_json::from_str(&raw_query_result.data.content)?;
 
-        Ok(query_result)
+        Ok(SqlQueryPage {
+            rows,
+            num_records: usize::try_from(raw_query_result.data.num_records)?,
+        })
     }
 
     async fn gql_api_call<Q: GraphQLQuery>(
@@ -113,129 +137,140 @@ impl KamuNodeApiClientImpl {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(dataset_id = dataset_id))]
+    async fn query_dataset_account_roles(
+        &self,
+        dataset_id: &DatasetID,
+    ) -> eyre::Result<Option<HashMap<AccountID, DatasetAccessRole>>> {
+        use dataset_account_roles as codegen;
+
+        const ACCOUNT_ROLES_PER_PAGE: i64 = 100;
+
+        let mut roles = HashMap::new();
+        let mut page = 0;
+
+        loop {
+            let response = self
+                .gql_api_call::<DatasetAccountRoles>(codegen::Variables {
+                    dataset_id: dataset_id.clone(),
+                    page,
+                    per_page: ACCOUNT_ROLES_PER_PAGE,
+                })
+                .await?;
+
+            let Some(dataset) = response.datasets.by_id else {
+                tracing::warn!("Dataset not found, skipping its roles");
+                return Ok(None);
+            };
+            let account_roles = dataset.collaboration.account_roles;
+
+            for node in account_roles.nodes {
+                let role = match node.role {
+                    codegen::DatasetAccessRole::READER => DatasetAccessRole::Reader,
+                    codegen::DatasetAccessRole::EDITOR => DatasetAccessRole::Editor,
+                    codegen::DatasetAccessRole::MAINTAINER => DatasetAccessRole::Maintainer,
+                    codegen::DatasetAccessRole::Other(unknown_role) => {
+                        bail!("Unknown dataset access role: {unknown_role}")
+                    }
+                };
+                roles.insert(node.account.id, role);
+            }
+
+            if !account_roles.page_info.has_next_page {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(Some(roles))
+    }
+
     #[tracing::instrument(level = "debug", skip_all, fields(data_rooms_batch_size = data_rooms.len()))]
     async fn query_versioned_file_batch(
         &self,
-        data_rooms: &[DataRoomDatasetIdWithOffset],
+        data_rooms: &[(SqlDatasetRef, u64)],
     ) -> eyre::Result<Vec<VersionedFileEntryDto>> {
-        let data_room_queries = data_rooms
-            .iter()
-            .map(|data_room| {
-                let data_room_dataset_id = &data_room.dataset_id;
-                let offset = data_room.offset;
-
-                indoc::formatdoc!(
-                    r#"
-                    SELECT '{data_room_dataset_id}' AS data_room_dataset_id,
-                           "offset",
-                           op,
-                           path,
-                           ref                      AS versioned_file_dataset_id
-                    FROM '{data_room_dataset_id}'
-                    WHERE offset >= {offset}
-                    "#
-                )
-            })
-            .collect::<Vec<_>>();
-
-        let sql = indoc::formatdoc!(
-            r#"
-            SELECT data_room_dataset_id,
-                   "offset",
-                   op,
-                   path,
-                   versioned_file_dataset_id
-            FROM ({subquery})
-            ORDER BY data_room_dataset_id, offset
-            "#,
-            subquery = data_room_queries.join("UNION ALL\n")
-        );
-
-        self.sql_query::<Vec<VersionedFileEntryDto>>(sql).await
+        self.sql_query::<VersionedFileEntryDto>(versioned_files_query(data_rooms))
+            .await
     }
 
     #[tracing::instrument(
         level = "debug",
         skip_all,
-        fields(versioned_file_dataset_ids_batch_size = versioned_file_dataset_ids.len())
+        fields(versioned_files_batch_size = versioned_files.len())
     )]
     async fn query_molecule_access_level_batch(
         &self,
-        versioned_file_dataset_ids: &[String],
+        versioned_files: &[SqlDatasetRef],
     ) -> eyre::Result<Vec<VersionedFileMoleculeAccessLevelDto>> {
-        let molecule_access_level_queries = versioned_file_dataset_ids
-            .iter()
-            .map(|versioned_file_dataset_id| {
-                indoc::formatdoc!(
-                    r#"
-                    (SELECT '{versioned_file_dataset_id}' AS versioned_file_dataset_id,
-                            molecule_access_level
-                     FROM '{versioned_file_dataset_id}'
-                     ORDER BY "offset" DESC
-                     LIMIT 1)
-                    "#
-                )
-            })
-            .collect::<Vec<_>>();
-
-        let sql = indoc::formatdoc!(
-            r#"
-            SELECT versioned_file_dataset_id,
-                   molecule_access_level
-            FROM ({subquery})
-            "#,
-            subquery = molecule_access_level_queries.join("UNION ALL\n")
-        );
-
-        self.sql_query::<Vec<VersionedFileMoleculeAccessLevelDto>>(sql)
-            .await
+        self.sql_query::<VersionedFileMoleculeAccessLevelDto>(molecule_access_levels_query(
+            versioned_files,
+        ))
+        .await
     }
 }
 
 #[async_trait]
 impl KamuNodeApiClient for KamuNodeApiClientImpl {
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn get_molecule_projects_head(&self) -> eyre::Result<Option<DatasetHead>> {
+        let response = self
+            .gql_api_call::<DatasetHeadByRef>(dataset_head_by_ref::Variables {
+                dataset_ref: self.molecule_projects_dataset_alias.clone(),
+            })
+            .await?;
+
+        Ok(response.datasets.by_ref.map(|dataset| dataset.head))
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(datasets_count = dataset_ids.len()))]
+    async fn get_dataset_heads(
+        &self,
+        dataset_ids: Vec<DatasetID>,
+    ) -> eyre::Result<DatasetHeadsMap> {
+        let mut dataset_heads = DatasetHeadsMap::with_capacity(dataset_ids.len());
+
+        for dataset_ids_batch in dataset_ids.chunks(DATASET_HEADS_BATCH_SIZE) {
+            let response = self
+                .gql_api_call::<DatasetHeads>(dataset_heads::Variables {
+                    dataset_ids: dataset_ids_batch.to_vec(),
+                })
+                .await?;
+
+            dataset_heads.extend(
+                response
+                    .datasets
+                    .by_ids
+                    .into_iter()
+                    .map(|dataset| (dataset.id, dataset.head)),
+            );
+        }
+
+        Ok(dataset_heads)
+    }
+
     #[tracing::instrument(level = "debug", skip_all, fields(offset = offset))]
     async fn get_molecule_project_entries<'a>(
         &self,
         offset: u64,
-        maybe_ignore_ocl_ids: Option<&'a HashSet<String>>,
+        ocl_id_filter: &'a OclIdFilter,
     ) -> eyre::Result<Vec<MoleculeProjectEntry>> {
-        let molecule_projects = &self.molecule_projects_dataset_alias;
-
-        // NOTE: We don't exclude retracted (-R) records. They are needed to correctly revoke permissions.
-        let sql = indoc::formatdoc!(
-            r#"
-            SELECT offset,
-                   op,
-                   ocl_id,
-                   symbol,
-                   odf_account_id AS project_account_id,
-                   odf_data_room_dataset_id AS data_room_dataset_id,
-                   odf_announcements_dataset_id AS announcements_dataset_id
-            FROM (SELECT *,
-                         row_number() over (
-                                         partition BY ocl_id
-                                         ORDER BY `offset` DESC
-                                     ) AS __rank
-                  FROM '{molecule_projects}')
-            WHERE __rank IN (1, 2) -- NOTE: include the last retracted records
-              AND offset >= {offset}
-            ORDER BY `offset`
-            "#
-        );
-
-        let mut dtos = self.sql_query::<Vec<MoleculeProjectEntryDto>>(sql).await?;
-
-        if let Some(ignore_ocl_ids) = maybe_ignore_ocl_ids {
-            dtos.retain(|p| !ignore_ocl_ids.contains(&p.ocl_id));
-        }
+        let molecule_projects = SqlDatasetRef::new(&self.molecule_projects_dataset_alias)?;
+        let sql = molecule_project_entries_query(&molecule_projects, offset, ocl_id_filter);
 
-        let project_entries = dtos
+        let dtos = self.sql_query::<MoleculeProjectEntryDto>(sql).await?;
+
+        let mut project_entries = dtos
             .into_iter()
             .map(TryInto::try_into)
             // Vec<Result<T, E>> --> Result<Vec<T>, E>
             .collect::<Result<Vec<MoleculeProjectEntry>, _>>()?;
 
+        // NOTE: The filter is applied by the query, but OCL ids stored in a different
+        //       format (e.g. without `0x`) slip through `NOT IN`, so they are checked
+        //       again once parsed.
+        project_entries.retain(|project_entry| ocl_id_filter.is_allowed(&project_entry.ocl_id));
+
         Ok(project_entries)
     }
 
@@ -272,9 +307,19 @@ impl KamuNodeApiClient for KamuNodeApiClientImpl {
             resolution.resolved_dataset_ids
         };
 
+        // NOTE: Dataset IDs come from the projects dataset, so invalid ones are skipped
+        //       instead of being used in SQL.
         let data_rooms = data_rooms
             .into_iter()
             .filter(|data_room| resolved_data_room_dataset_ids.contains(&data_room.dataset_id))
+            .filter_map(|data_room| {
+                let data_room_ref = SqlDatasetRef::new(&data_room.dataset_id)
+                    .inspect_err(
+                        |e| tracing::warn!(error = %e, "Skip data room that can't be queried"),
+                    )
+                    .ok()?;
+                Some((data_room_ref, data_room.offset))
+            })
             .collect::<Vec<_>>();
 
         if data_rooms.is_empty() {
@@ -366,7 +411,19 @@ impl KamuNodeApiClient for KamuNodeApiClientImpl {
                 );
             }
 
-            resolution.resolved_dataset_ids
+            // NOTE: Dataset IDs come from data rooms, so invalid ones are skipped
+            //       instead of being used in SQL.
+            resolution
+                .resolved_dataset_ids
+                .iter()
+                .filter_map(|dataset_id| {
+                    SqlDatasetRef::new(dataset_id)
+                        .inspect_err(|e| {
+                            tracing::warn!(error = %e, "Skip versioned file that can't be queried");
+                        })
+                        .ok()
+                })
+                .collect::<Vec<_>>()
         };
 
         if resolved_versioned_file_dataset_ids.is_empty() {
@@ -396,10 +453,37 @@ impl KamuNodeApiClient for KamuNodeApiClientImpl {
                 .try_collect()
                 .await?;
 
+        // NOTE: Values are parsed per file, so that a malformed one only affects its file.
         let map = batch_results
             .into_iter()
             .flatten()
-            .map(|dto| (dto.versioned_file_dataset_id, dto.molecule_access_level))
+            .filter_map(|dto| {
+                let molecule_access_level =
+                    match serde_json::from_value::<MoleculeAccessLevel>(dto.molecule_access_level) {
+                        Ok(molecule_access_level) => molecule_access_level,
+                        Err(e) => {
+                            tracing::warn!(
+                                versioned_file_dataset_id = dto.versioned_file_dataset_id,
+                                error = %e,
+                                "Skip versioned file with malformed molecule_access_level",
+                            );
+                            return None;
+                        }
+                    };
+
+                if let MoleculeAccessLevel::Unknown(value) = &molecule_access_level {
+                    tracing::warn!(
+                        versioned_file_dataset_id = dto.versioned_file_dataset_id,
+                        molecule_access_level = value,
+                        "Unknown molecule_access_level, the fallback of the access policy is applied",
+                    );
+                    self.metric_unknown_molecule_access_levels_num_total
+                        .with_label_values(&[value.as_str()])
+                        .inc();
+                }
+
+                Some((dto.versioned_file_dataset_id, molecule_access_level))
+            })
             .collect();
 
         Ok(map)
@@ -423,19 +507,23 @@ impl KamuNodeApiClient for KamuNodeApiClientImpl {
     async fn apply_account_dataset_relations(
         &self,
         operations: Vec<AccountDatasetRelationOperation>,
-    ) -> eyre::Result<()> {
+    ) -> eyre::Result<String> {
         if self.dry_run {
-            return Ok(());
+            return Ok("Dry run: operations are not applied".to_string());
         }
 
         let operations = operations.into_iter().map(Into::into).collect();
 
-        self.gql_api_call::<ApplyAccountDatasetRelations>(
-            apply_account_dataset_relations::Variables { operations },
-        )
-        .await?;
+        let response = self
+            .gql_api_call::<ApplyAccountDatasetRelations>(
+                apply_account_dataset_relations::Variables { operations },
+            )
+            .await?;
 
-        Ok(())
+        Ok(response
+            .collaboration
+            .apply_account_dataset_relations
+            .message)
     }
 
     #[tracing::instrument(level = "debug", skip_all, fields(datasets_count = dataset_ids.len()))]
@@ -467,6 +555,97 @@ impl KamuNodeApiClient for KamuNodeApiClientImpl {
             not_found_dataset_ids,
         })
     }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(datasets_count = dataset_ids.len()))]
+    async fn get_dataset_account_roles(
+        &self,
+        dataset_ids: Vec<DatasetID>,
+    ) -> eyre::Result<DatasetAccountRolesMap> {
+        use futures::stream::{StreamExt, TryStreamExt};
+
+        const MAX_CONCURRENT_DATASET_REQUESTS: usize = 4;
+
+        let dataset_roles: Vec<Option<(DatasetID, HashMap<AccountID, DatasetAccessRole>)>> =
+            futures::stream::iter(dataset_ids)
+                .map(|dataset_id| async move {
+                    let maybe_roles = self.query_dataset_account_roles(&dataset_id).await?;
+                    Ok::<_, eyre::Error>(maybe_roles.map(|roles| (dataset_id, roles)))
+                })
+                .buffer_unordered(MAX_CONCURRENT_DATASET_REQUESTS)
+                .try_collect()
+                .await?;
+
+        Ok(dataset_roles.into_iter().flatten().collect())
+    }
+
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(datasets_count = dataset_ids.len(), visibility = ?visibility)
+    )]
+    async fn set_datasets_visibility(
+        &self,
+        dataset_ids: Vec<DatasetID>,
+        visibility: DatasetVisibility,
+    ) -> eyre::Result<()> {
+        use futures::stream::{StreamExt, TryStreamExt};
+
+        const MAX_CONCURRENT_DATASET_REQUESTS: usize = 4;
+
+        if self.dry_run {
+            return Ok(());
+        }
+
+        futures::stream::iter(dataset_ids)
+            .map(|dataset_id| async move {
+                let response = self
+                    .gql_api_call::<SetDatasetVisibility>(set_dataset_visibility::Variables {
+                        dataset_id: dataset_id.clone(),
+                        visibility: visibility.into(),
+                    })
+                    .await?;
+
+                if response.datasets.by_id.is_none() {
+                    tracing::warn!(dataset_id, "Dataset not found, skipping its visibility");
+                }
+
+                Ok::<_, eyre::Error>(())
+            })
+            .buffer_unordered(MAX_CONCURRENT_DATASET_REQUESTS)
+            .try_collect::<()>()
+            .await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(dataset_alias = dataset_alias))]
+    async fn ingest_records(&self, dataset_alias: &str, records: String) -> eyre::Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+
+        // NOTE: The REST API is served next to the GraphQL one.
+        let api_base_url = self
+            .gql_api_endpoint
+            .trim_end_matches('/')
+            .trim_end_matches("/graphql");
+        let ingest_url = format!("{api_base_url}/{dataset_alias}/ingest");
+
+        let response = self
+            .http_client
+            .post(&ingest_url)
+            .bearer_auth(&self.token)
+            .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+            .body(records)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await?;
+            bail!("Unexpected status code: {status}, body: {body}");
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(GraphQLQuery)]
@@ -527,7 +706,8 @@ struct VersionedFileEntryDto {
 #[derive(Debug, Deserialize, Serialize)]
 struct VersionedFileMoleculeAccessLevelDto {
     versioned_file_dataset_id: String,
-    molecule_access_level: MoleculeAccessLevel,
+    /// Parsed separately (see [`MoleculeAccessLevel`])
+    molecule_access_level: serde_json::Value,
 }
 
 #[derive(GraphQLQuery)]
@@ -551,6 +731,7 @@ impl From<AccountDatasetRelationOperation>
                     codegen::DatasetRoleOperation::Set(codegen::DatasetRoleSetOperation {
                         role: match role {
                             DatasetAccessRole::Reader => codegen::DatasetAccessRole::READER,
+                            DatasetAccessRole::Editor => codegen::DatasetAccessRole::EDITOR,
                             DatasetAccessRole::Maintainer => codegen::DatasetAccessRole::MAINTAINER,
                         },
                     })
@@ -573,3 +754,57 @@ impl From<AccountDatasetRelationOperation>
     response_derives = "Debug"
 )]
 struct AvailabilityOfDatasets;
+
+// NOTE: GQL scalars require additional declarations
+type Multihash = String;
+type DatasetRef = String;
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.graphql",
+    query_path = "gql/dataset_heads.graphql",
+    response_derives = "Debug"
+)]
+struct DatasetHeads;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.graphql",
+    query_path = "gql/dataset_head_by_ref.graphql",
+    response_derives = "Debug"
+)]
+struct DatasetHeadByRef;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.graphql",
+    query_path = "gql/dataset_account_roles.graphql",
+    response_derives = "Debug"
+)]
+struct DatasetAccountRoles;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.graphql",
+    query_path = "gql/set_dataset_visibility.graphql",
+    response_derives = "Debug"
+)]
+struct SetDatasetVisibility;
+
+impl From<DatasetVisibility> for set_dataset_visibility::DatasetVisibilityInput {
+    fn from(v: DatasetVisibility) -> Self {
+        use set_dataset_visibility as codegen;
+
+        match v {
+            DatasetVisibility::Private => Self {
+                private: Some(codegen::PrivateDatasetVisibilityInput { dummy: None }),
+                public: None,
+            },
+            DatasetVisibility::Public => Self {
+                private: None,
+                public: Some(codegen::PublicDatasetVisibilityInput {
+                    anonymous_available: true,
+                }),
+            },
+        }
+    }
+}
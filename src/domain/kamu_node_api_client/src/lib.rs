@@ -1,7 +1,13 @@
 mod did_phk;
 mod kamu_node_api_client;
 mod kamu_node_api_client_impl;
+mod ocl_id_filter;
+mod pagination;
+mod sql;
 
 pub use did_phk::*;
 pub use kamu_node_api_client::*;
 pub use kamu_node_api_client_impl::*;
+pub use ocl_id_filter::*;
+pub use pagination::*;
+pub use sql::*;
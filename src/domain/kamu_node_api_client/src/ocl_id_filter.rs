@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+
+use molecule_ocl::entities::OclId;
+use serde::{Deserialize, Serialize};
+
+/// Projects (by OCL id) that are loaded from `molecule/projects`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OclIdFilter {
+    /// Projects that are never loaded
+    #[serde(default)]
+    pub ignore: HashSet<OclId>,
+    /// If set, only these projects are loaded. `ignore` takes precedence.
+    #[serde(default)]
+    pub only: Option<HashSet<OclId>>,
+}
+
+impl OclIdFilter {
+    pub fn is_allowed(&self, ocl_id: &OclId) -> bool {
+        !self.ignore.contains(ocl_id)
+            && self
+                .only
+                .as_ref()
+                .is_none_or(|only_ocl_ids| only_ocl_ids.contains(ocl_id))
+    }
+
+    /// Returns the filter for projects that are allowed by this filter, but
+    /// were not allowed by the previous one. `None` if there are no such
+    /// projects.
+    pub fn newly_allowed(&self, previous: &OclIdFilter) -> Option<OclIdFilter> {
+        if self == previous {
+            return None;
+        }
+
+        // NOTE: Newly allowed projects can only be listed if either the allowlist
+        //       is set now or nothing but the ignored projects was allowed before.
+        let maybe_candidates = match (&self.only, &previous.only) {
+            (Some(only_ocl_ids), _) => Some(only_ocl_ids),
+            (None, None) => Some(&previous.ignore),
+            (None, Some(_)) => None,
+        };
+
+        let Some(candidates) = maybe_candidates else {
+            // Everything except for the projects allowed before.
+            let mut ignore = self.ignore.clone();
+            ignore.extend(
+                previous
+                    .only
+                    .iter()
+                    .flatten()
+                    .filter(|ocl_id| previous.is_allowed(ocl_id)),
+            );
+            return Some(OclIdFilter { ignore, only: None });
+        };
+
+        let newly_allowed_ocl_ids = candidates
+            .iter()
+            .filter(|ocl_id| self.is_allowed(ocl_id) && !previous.is_allowed(ocl_id))
+            .copied()
+            .collect::<HashSet<_>>();
+
+        if newly_allowed_ocl_ids.is_empty() {
+            return None;
+        }
+
+        Some(OclIdFilter {
+            ignore: HashSet::new(),
+            only: Some(newly_allowed_ocl_ids),
+        })
+    }
+}
@@ -0,0 +1,62 @@
+use std::num::NonZeroUsize;
+
+use eyre::bail;
+
+/// Page of SQL query results
+#[derive(Debug)]
+pub struct SqlQueryPage<T> {
+    pub rows: Vec<T>,
+    /// Number of records reported by Kamu Node for the page
+    pub num_records: usize,
+}
+
+/// Fetches pages (`skip`, `limit`) until one is not full, so that results are
+/// never silently truncated.
+///
+/// Expects the query to have a deterministic order (`ORDER BY` a unique key),
+/// otherwise records may be skipped or repeated between pages.
+pub async fn fetch_all_pages<T, F, Fut>(
+    page_size: NonZeroUsize,
+    mut fetch_page: F,
+) -> eyre::Result<Vec<T>>
+where
+    F: FnMut(/* skip */ usize, /* limit */ usize) -> Fut,
+    Fut: Future<Output = eyre::Result<SqlQueryPage<T>>>,
+{
+    let page_size = page_size.get();
+    let mut rows = Vec::new();
+
+    loop {
+        let skip = rows.len();
+        let SqlQueryPage {
+            rows: page_rows,
+            num_records,
+        } = fetch_page(skip, page_size).await?;
+
+        if page_rows.len() != num_records {
+            bail!(
+                "Page at {skip} has {} rows, but {num_records} records were reported",
+                page_rows.len()
+            );
+        }
+        if page_rows.len() > page_size {
+            bail!(
+                "Page at {skip} has {} rows, more than the limit of {page_size}",
+                page_rows.len()
+            );
+        }
+
+        let is_last_page = page_rows.len() < page_size;
+
+        rows.extend(page_rows);
+
+        if is_last_page {
+            return Ok(rows);
+        }
+
+        tracing::debug!(
+            fetched_rows = rows.len(),
+            "Page is full, fetching the next one"
+        );
+    }
+}
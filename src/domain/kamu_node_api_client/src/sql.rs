@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use eyre::bail;
+use molecule_ocl::entities::OclId;
+
+use crate::ocl_id_filter::OclIdFilter;
+
+/// Dataset IDs (`did:odf:...`) are much shorter, aliases are limited as well
+const MAX_DATASET_REF_LEN: usize = 256;
+
+/// Dataset reference (ID or alias) that is safe to use in SQL queries.
+///
+/// Dataset IDs are partially controlled by project owners (e.g. via the
+/// `molecule/projects` dataset), so they are validated before being used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlDatasetRef(String);
+
+impl SqlDatasetRef {
+    pub fn new(dataset_ref: &str) -> eyre::Result<Self> {
+        let is_valid = !dataset_ref.is_empty()
+            && dataset_ref.len() <= MAX_DATASET_REF_LEN
+            && dataset_ref
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | ':' | '/'));
+
+        if !is_valid {
+            bail!("Invalid dataset reference: {dataset_ref:?}");
+        }
+
+        Ok(Self(dataset_ref.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Table to select from
+    pub fn table(&self) -> SqlTable<'_> {
+        SqlTable(self)
+    }
+
+    /// The reference itself as a value
+    pub fn literal(&self) -> SqlStringLiteral<'_> {
+        SqlStringLiteral(&self.0)
+    }
+}
+
+/// Dataset table, quoted the way the Kamu dialect expects it
+pub struct SqlTable<'a>(&'a SqlDatasetRef);
+
+impl fmt::Display for SqlTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // NOTE: The reference can't contain quotes (see `SqlDatasetRef::new()`).
+        write!(f, "'{}'", self.0.0)
+    }
+}
+
+/// String value with quotes escaped
+pub struct SqlStringLiteral<'a>(pub &'a str);
+
+impl fmt::Display for SqlStringLiteral<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}'", self.0.replace('\'', "''"))
+    }
+}
+
+/// Returns entries of the data rooms starting from their offsets
+pub fn versioned_files_query(data_rooms: &[(SqlDatasetRef, u64)]) -> String {
+    let data_room_queries = data_rooms
+        .iter()
+        .map(|(data_room, offset)| {
+            indoc::formatdoc!(
+                r#"
+                SELECT {data_room_dataset_id} AS data_room_dataset_id,
+                       "offset",
+                       op,
+                       path,
+                       ref                      AS versioned_file_dataset_id
+                FROM {data_room_table}
+                WHERE offset >= {offset}
+                "#,
+                data_room_dataset_id = data_room.literal(),
+                data_room_table = data_room.table(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    indoc::formatdoc!(
+        r#"
+        SELECT data_room_dataset_id,
+               "offset",
+               op,
+               path,
+               versioned_file_dataset_id
+        FROM ({subquery})
+        ORDER BY data_room_dataset_id, offset
+        "#,
+        subquery = data_room_queries.join("UNION ALL\n")
+    )
+}
+
+/// Returns the latest `molecule_access_level` of each versioned file
+pub fn molecule_access_levels_query(versioned_files: &[SqlDatasetRef]) -> String {
+    let molecule_access_level_queries = versioned_files
+        .iter()
+        .map(|versioned_file| {
+            indoc::formatdoc!(
+                r#"
+                (SELECT {versioned_file_dataset_id} AS versioned_file_dataset_id,
+                        molecule_access_level
+                 FROM {versioned_file_table}
+                 ORDER BY "offset" DESC
+                 LIMIT 1)
+                "#,
+                versioned_file_dataset_id = versioned_file.literal(),
+                versioned_file_table = versioned_file.table(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    indoc::formatdoc!(
+        r#"
+        SELECT versioned_file_dataset_id,
+               molecule_access_level
+        FROM ({subquery})
+        ORDER BY versioned_file_dataset_id
+        "#,
+        subquery = molecule_access_level_queries.join("UNION ALL\n")
+    )
+}
+
+/// Returns the latest entries of the allowed projects starting from the offset
+pub fn molecule_project_entries_query(
+    molecule_projects: &SqlDatasetRef,
+    offset: u64,
+    ocl_id_filter: &OclIdFilter,
+) -> String {
+    let mut conditions = vec![
+        "__rank IN (1, 2) -- NOTE: include the last retracted records".to_string(),
+        format!("offset >= {offset}"),
+    ];
+    conditions.extend(ocl_id_filter_conditions(ocl_id_filter));
+
+    // NOTE: We don't exclude retracted (-R) records. They are needed to correctly revoke permissions.
+    indoc::formatdoc!(
+        r#"
+        SELECT offset,
+               op,
+               ocl_id,
+               symbol,
+               odf_account_id AS project_account_id,
+               odf_data_room_dataset_id AS data_room_dataset_id,
+               odf_announcements_dataset_id AS announcements_dataset_id
+        FROM (SELECT *,
+                     row_number() over (
+                                     partition BY ocl_id
+                                     ORDER BY `offset` DESC
+                                 ) AS __rank
+              FROM {molecule_projects_table})
+        WHERE {conditions}
+        ORDER BY `offset`
+        "#,
+        molecule_projects_table = molecule_projects.table(),
+        conditions = conditions.join("\n  AND "),
+    )
+}
+
+fn ocl_id_filter_conditions(ocl_id_filter: &OclIdFilter) -> Vec<String> {
+    let mut conditions = Vec::new();
+
+    if !ocl_id_filter.ignore.is_empty() {
+        conditions.push(format!(
+            "lower(ocl_id) NOT IN ({})",
+            ocl_id_list(&ocl_id_filter.ignore)
+        ));
+    }
+
+    match &ocl_id_filter.only {
+        // NOTE: `IN ()` is not valid SQL.
+        Some(only_ocl_ids) if only_ocl_ids.is_empty() => conditions.push("FALSE".to_string()),
+        Some(only_ocl_ids) => {
+            conditions.push(format!("lower(ocl_id) IN ({})", ocl_id_list(only_ocl_ids)));
+        }
+        None => {}
+    }
+
+    conditions
+}
+
+/// OCL ids are stored as hex strings that may differ in case, so they are
+/// compared in lower case (the way [`OclId`] is displayed).
+fn ocl_id_list(ocl_ids: &HashSet<OclId>) -> String {
+    let mut ocl_ids = ocl_ids
+        .iter()
+        .map(|ocl_id| ocl_id.to_string().to_lowercase())
+        .collect::<Vec<_>>();
+    // NOTE: Stable order regardless of the set iteration order
+    ocl_ids.sort();
+
+    ocl_ids
+        .iter()
+        .map(|ocl_id| SqlStringLiteral(ocl_id).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -23,3 +23,24 @@ fn test_new_from_chain_id() {
         );
     }
 }
+
+#[test]
+fn test_from_str() {
+    let did_phk: DidPhk = "did:pkh:eip155:8453:0xabCdeF1010101010101010101010101010101010"
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        DidPhk::new_from_chain_id(8453, address!("0xabcdef1010101010101010101010101010101010"))
+            .unwrap(),
+        did_phk
+    );
+
+    assert!("did:pkh:eip155:8453".parse::<DidPhk>().is_err());
+    assert!(
+        "did:pkh:eip155:10:0xabCdeF1010101010101010101010101010101010"
+            .parse::<DidPhk>()
+            .is_err()
+    );
+    assert!("did:odf:fed01".parse::<DidPhk>().is_err());
+}
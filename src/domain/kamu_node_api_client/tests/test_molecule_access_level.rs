@@ -0,0 +1,34 @@
+use kamu_node_api_client::MoleculeAccessLevel;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn test_known_values() {
+    for (value, expected) in [
+        ("public", MoleculeAccessLevel::Public),
+        ("ADMIN", MoleculeAccessLevel::Admin),
+        ("admin_2", MoleculeAccessLevel::Admin2),
+        ("holders", MoleculeAccessLevel::Holder),
+    ] {
+        let actual: MoleculeAccessLevel = serde_json::from_value(value.into()).unwrap();
+
+        assert_eq!(expected, actual, "value: {value}");
+        assert!(!actual.is_unknown());
+    }
+}
+
+#[test]
+fn test_unknown_value_is_kept() {
+    let actual: MoleculeAccessLevel = serde_json::from_value("partners".into()).unwrap();
+
+    assert_eq!(MoleculeAccessLevel::Unknown("partners".to_string()), actual);
+    assert!(actual.is_unknown());
+    assert_eq!(
+        serde_json::Value::from("partners"),
+        serde_json::to_value(&actual).unwrap()
+    );
+}
+
+#[test]
+fn test_malformed_value_is_rejected() {
+    assert!(serde_json::from_value::<MoleculeAccessLevel>(serde_json::json!(42)).is_err());
+}
@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+use kamu_node_api_client::OclIdFilter;
+use molecule_ocl::entities::OclId;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn test_ignore_takes_precedence_over_allowlist() {
+    let ocl_id_filter = OclIdFilter {
+        ignore: ocl_ids(&[1]),
+        only: Some(ocl_ids(&[1, 2])),
+    };
+
+    assert!(!ocl_id_filter.is_allowed(&ocl_id(1)));
+    assert!(ocl_id_filter.is_allowed(&ocl_id(2)));
+    assert!(!ocl_id_filter.is_allowed(&ocl_id(3)));
+}
+
+#[test]
+fn test_newly_allowed_after_unignoring() {
+    let previous = OclIdFilter {
+        ignore: ocl_ids(&[1, 2]),
+        only: None,
+    };
+    let current = OclIdFilter {
+        ignore: ocl_ids(&[2]),
+        only: None,
+    };
+
+    assert_eq!(
+        Some(OclIdFilter {
+            ignore: HashSet::new(),
+            only: Some(ocl_ids(&[1])),
+        }),
+        current.newly_allowed(&previous)
+    );
+    assert_eq!(None, previous.newly_allowed(&current));
+    assert_eq!(None, current.newly_allowed(&current));
+}
+
+#[test]
+fn test_newly_allowed_after_extending_allowlist() {
+    let previous = OclIdFilter {
+        ignore: HashSet::new(),
+        only: Some(ocl_ids(&[1])),
+    };
+    let current = OclIdFilter {
+        ignore: HashSet::new(),
+        only: Some(ocl_ids(&[1, 2])),
+    };
+
+    assert_eq!(
+        Some(OclIdFilter {
+            ignore: HashSet::new(),
+            only: Some(ocl_ids(&[2])),
+        }),
+        current.newly_allowed(&previous)
+    );
+    assert_eq!(None, previous.newly_allowed(&current));
+}
+
+#[test]
+fn test_newly_allowed_after_dropping_allowlist() {
+    let previous = OclIdFilter {
+        ignore: ocl_ids(&[2]),
+        only: Some(ocl_ids(&[1, 2])),
+    };
+    let current = OclIdFilter {
+        ignore: ocl_ids(&[3]),
+        only: None,
+    };
+
+    let newly_allowed = current.newly_allowed(&previous).unwrap();
+
+    assert!(!newly_allowed.is_allowed(&ocl_id(1)));
+    assert!(newly_allowed.is_allowed(&ocl_id(2)));
+    assert!(!newly_allowed.is_allowed(&ocl_id(3)));
+    assert!(newly_allowed.is_allowed(&ocl_id(4)));
+}
+
+fn ocl_id(n: u8) -> OclId {
+    OclId::from(alloy::primitives::B256::with_last_byte(n))
+}
+
+fn ocl_ids(ns: &[u8]) -> HashSet<OclId> {
+    ns.iter().copied().map(ocl_id).collect()
+}
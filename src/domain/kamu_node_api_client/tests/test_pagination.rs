@@ -0,0 +1,73 @@
+use std::num::NonZeroUsize;
+
+use kamu_node_api_client::{SqlQueryPage, fetch_all_pages};
+use pretty_assertions::assert_eq;
+
+const PAGE_SIZE: NonZeroUsize = NonZeroUsize::new(3).unwrap();
+
+#[tokio::test]
+async fn test_pages_are_fetched_until_one_is_not_full() {
+    let all_rows = (0..7).collect::<Vec<u64>>();
+    let mut requests = Vec::new();
+
+    let rows = fetch_all_pages(PAGE_SIZE, |skip, limit| {
+        requests.push((skip, limit));
+        let page_rows = all_rows
+            .iter()
+            .copied()
+            .skip(skip)
+            .take(limit)
+            .collect::<Vec<_>>();
+        async move {
+            Ok(SqlQueryPage {
+                num_records: page_rows.len(),
+                rows: page_rows,
+            })
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(all_rows, rows);
+    assert_eq!(vec![(0, 3), (3, 3), (6, 3)], requests);
+}
+
+#[tokio::test]
+async fn test_full_last_page_is_followed_by_an_empty_one() {
+    let all_rows = (0..6).collect::<Vec<u64>>();
+    let mut requests = Vec::new();
+
+    let rows = fetch_all_pages(PAGE_SIZE, |skip, limit| {
+        requests.push(skip);
+        let page_rows = all_rows
+            .iter()
+            .copied()
+            .skip(skip)
+            .take(limit)
+            .collect::<Vec<_>>();
+        async move {
+            Ok(SqlQueryPage {
+                num_records: page_rows.len(),
+                rows: page_rows,
+            })
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(all_rows, rows);
+    assert_eq!(vec![0, 3, 6], requests);
+}
+
+#[tokio::test]
+async fn test_page_with_missing_rows_is_rejected() {
+    let result = fetch_all_pages(PAGE_SIZE, |_, _| async {
+        Ok(SqlQueryPage {
+            rows: vec![1_u64, 2],
+            num_records: 3,
+        })
+    })
+    .await;
+
+    assert!(result.is_err());
+}
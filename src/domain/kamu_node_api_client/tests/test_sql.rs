@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+
+use kamu_node_api_client::{
+    OclIdFilter, SqlDatasetRef, SqlStringLiteral, molecule_access_levels_query,
+    molecule_project_entries_query, versioned_files_query,
+};
+use molecule_ocl::entities::OclId;
+use pretty_assertions::assert_eq;
+
+const DATA_ROOM_DATASET_ID: &str =
+    "did:odf:fed01c8788dc7825dc95dfaa6c67f989b758d3ebcb1efcb9f47ef914911ea7e2c2dac";
+const OCL_ID_1: &str = "0x0101000000000000000000A1117B215DCD666DD847CFA84721480D316440FAA9";
+const OCL_ID_2: &str = "0x0101000000000000000000a754158f71186f2cd00d895eff64f926c4fcd69179";
+
+#[test]
+fn test_valid_dataset_refs() {
+    for dataset_ref in [
+        DATA_ROOM_DATASET_ID,
+        "molecule/projects",
+        "kamu/molecule.projects-v2_test",
+    ] {
+        let sql_dataset_ref = SqlDatasetRef::new(dataset_ref).unwrap();
+
+        assert_eq!(dataset_ref, sql_dataset_ref.as_str());
+        assert_eq!(
+            format!("'{dataset_ref}'"),
+            sql_dataset_ref.table().to_string()
+        );
+    }
+}
+
+#[test]
+fn test_hostile_dataset_refs_are_rejected() {
+    for dataset_ref in [
+        "",
+        "did:odf:fed01' UNION SELECT * FROM 'molecule/projects",
+        "did:odf:fed01'--",
+        "did:odf:fed01\"",
+        "did:odf:fed01; DROP TABLE x",
+        "did:odf:fed01\nWHERE 1 = 1",
+        "did:odf:fed01`",
+        "did:odf:fed01()",
+        "did:odf:fеd01", // NOTE: Cyrillic "е".
+        &"a".repeat(257),
+    ] {
+        assert!(
+            SqlDatasetRef::new(dataset_ref).is_err(),
+            "dataset_ref: {dataset_ref:?}"
+        );
+    }
+}
+
+#[test]
+fn test_string_literal_is_escaped() {
+    assert_eq!(
+        "'it''s'' OR ''1''=''1'",
+        SqlStringLiteral("it's' OR '1'='1").to_string()
+    );
+}
+
+#[test]
+fn test_queries_only_contain_validated_refs() {
+    let data_room = SqlDatasetRef::new(DATA_ROOM_DATASET_ID).unwrap();
+    let molecule_projects = SqlDatasetRef::new("molecule/projects").unwrap();
+
+    let versioned_files_sql = versioned_files_query(&[(data_room.clone(), 7)]);
+    assert!(versioned_files_sql.contains(&format!(
+        "SELECT '{DATA_ROOM_DATASET_ID}' AS data_room_dataset_id"
+    )));
+    assert!(versioned_files_sql.contains(&format!("FROM '{DATA_ROOM_DATASET_ID}'")));
+    assert!(versioned_files_sql.contains("WHERE offset >= 7"));
+
+    let molecule_access_levels_sql = molecule_access_levels_query(&[data_room]);
+    assert!(molecule_access_levels_sql.contains(&format!("FROM '{DATA_ROOM_DATASET_ID}'")));
+    assert!(molecule_access_levels_sql.contains("ORDER BY versioned_file_dataset_id"));
+
+    let molecule_project_entries_sql =
+        molecule_project_entries_query(&molecule_projects, 3, &OclIdFilter::default());
+    assert!(molecule_project_entries_sql.contains("FROM 'molecule/projects')"));
+    assert!(molecule_project_entries_sql.contains("AND offset >= 3"));
+    assert!(!molecule_project_entries_sql.contains("lower(ocl_id)"));
+}
+
+#[test]
+fn test_ocl_id_filter_is_applied_in_lower_case() {
+    let molecule_projects = SqlDatasetRef::new("molecule/projects").unwrap();
+    let ocl_id_filter = OclIdFilter {
+        ignore: ocl_ids(&[OCL_ID_1]),
+        only: Some(ocl_ids(&[OCL_ID_2, OCL_ID_1])),
+    };
+
+    let sql = molecule_project_entries_query(&molecule_projects, 0, &ocl_id_filter);
+
+    let ocl_id_1 = OCL_ID_1.to_lowercase();
+    assert!(sql.contains(&format!("AND lower(ocl_id) NOT IN ('{ocl_id_1}')")));
+    assert!(sql.contains(&format!(
+        "AND lower(ocl_id) IN ('{ocl_id_1}', '{OCL_ID_2}')"
+    )));
+}
+
+#[test]
+fn test_empty_ocl_id_allowlist_matches_nothing() {
+    let molecule_projects = SqlDatasetRef::new("molecule/projects").unwrap();
+    let ocl_id_filter = OclIdFilter {
+        ignore: HashSet::new(),
+        only: Some(HashSet::new()),
+    };
+
+    let sql = molecule_project_entries_query(&molecule_projects, 0, &ocl_id_filter);
+
+    assert!(sql.contains("AND FALSE"));
+    assert!(!sql.contains("IN ()"));
+}
+
+fn ocl_ids(ocl_ids: &[&str]) -> HashSet<OclId> {
+    ocl_ids
+        .iter()
+        .map(|ocl_id| ocl_id.parse().unwrap())
+        .collect()
+}
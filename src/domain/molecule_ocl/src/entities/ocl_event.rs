@@ -11,6 +11,21 @@ pub struct OclTransferEvent {
     pub to: Address,
 }
 
+#[derive(Debug)]
+pub struct OclIdentityCreatedEvent {
+    pub ocl_id: OclId,
+    pub account: Address,
+}
+
+impl From<LabNFT::OclIdentityCreated> for OclIdentityCreatedEvent {
+    fn from(value: LabNFT::OclIdentityCreated) -> Self {
+        OclIdentityCreatedEvent {
+            ocl_id: value.oclId.into(),
+            account: value.account,
+        }
+    }
+}
+
 impl From<LabNFT::OclTransfer> for OclTransferEvent {
     fn from(value: LabNFT::OclTransfer) -> Self {
         OclTransferEvent {
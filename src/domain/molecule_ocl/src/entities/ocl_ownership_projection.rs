@@ -5,6 +5,9 @@ use alloy::primitives::Address;
 pub struct OclOwnershipProjection {
     pub current: Option<Address>,
     pub previous: Vec<Address>,
+    /// ERC-6551 token-bound account of the OCL (see `OclIdentityCreated`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub identity_account: Option<Address>,
 }
 
 impl OclOwnershipProjection {
@@ -12,6 +15,7 @@ impl OclOwnershipProjection {
         Self {
             current: Some(initial_owner),
             previous: vec![],
+            identity_account: None,
         }
     }
 
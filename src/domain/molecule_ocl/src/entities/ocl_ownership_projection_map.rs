@@ -3,7 +3,8 @@ use std::collections::HashMap;
 use alloy::primitives::Address;
 
 use crate::entities::{
-    OclId, OclOwnershipChange, OclOwnershipDiffMap, OclOwnershipProjection, OclTransferEvent,
+    OclId, OclIdentityCreatedEvent, OclOwnershipChange, OclOwnershipDiffMap,
+    OclOwnershipProjection, OclTransferEvent,
 };
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -34,15 +35,18 @@ impl OclOwnershipProjectionMap {
             match self.entries.entry(ocl_id) {
                 Entry::Occupied(mut e) => {
                     let projection = e.get_mut();
-                    let Some(former_owner) = projection.apply_transfer(last_owner) else {
+                    if projection.current == Some(last_owner) {
                         // No changes
                         continue;
-                    };
+                    }
+
+                    // NOTE: There is no current owner if only the identity is known so far.
+                    let former_owner = projection.apply_transfer(last_owner);
 
                     diff.insert(
                         ocl_id,
                         OclOwnershipChange {
-                            former_owner: Some(former_owner),
+                            former_owner,
                             current_owner: last_owner,
                         },
                     );
@@ -64,6 +68,51 @@ impl OclOwnershipProjectionMap {
 
         diff
     }
+
+    /// Returns OCLs whose identity account was not known before
+    pub fn apply_identity_events(&mut self, events: Vec<OclIdentityCreatedEvent>) -> Vec<OclId> {
+        let mut changed_ocl_ids = Vec::new();
+
+        for OclIdentityCreatedEvent { ocl_id, account } in events {
+            let projection = self.entries.entry(ocl_id).or_default();
+
+            if projection.identity_account != Some(account) {
+                projection.identity_account = Some(account);
+                changed_ocl_ids.push(ocl_id);
+            }
+        }
+
+        changed_ocl_ids
+    }
+
+    /// Keeps an OCL without an owner, the dropped owner becomes a previous one
+    /// (e.g. when the OCL was transferred only in orphaned blocks)
+    pub fn drop_owner(
+        &mut self,
+        ocl_id: OclId,
+        dropped_owner: Address,
+        identity_account: Option<Address>,
+    ) {
+        let projection = self.entries.entry(ocl_id).or_default();
+
+        projection.current = None;
+        if !projection.previous.contains(&dropped_owner) {
+            projection.previous.push(dropped_owner);
+        }
+        if projection.identity_account.is_none() {
+            projection.identity_account = identity_account;
+        }
+    }
+
+    /// Finds the OCL that the token-bound account belongs to
+    pub fn find_by_identity_account(
+        &self,
+        account: Address,
+    ) -> Option<(&OclId, &OclOwnershipProjection)> {
+        self.entries
+            .iter()
+            .find(|(_, projection)| projection.identity_account == Some(account))
+    }
 }
 
 impl std::ops::Deref for OclOwnershipProjectionMap {
@@ -21,6 +21,7 @@ struct ApplyTransferStep {
         expected_projection: OclOwnershipProjection {
             current: Some(ADDR_A),
             previous: vec![],
+            identity_account: None,
         },
         expected_previous_owner: None,
     },
@@ -29,6 +30,7 @@ struct ApplyTransferStep {
         expected_projection: OclOwnershipProjection {
             current: Some(ADDR_A),
             previous: vec![],
+            identity_account: None,
         },
         expected_previous_owner: None,
     },
@@ -39,6 +41,7 @@ struct ApplyTransferStep {
         expected_projection: OclOwnershipProjection {
             current: Some(ADDR_A),
             previous: vec![],
+            identity_account: None,
         },
         expected_previous_owner: None,
     },
@@ -47,6 +50,7 @@ struct ApplyTransferStep {
         expected_projection: OclOwnershipProjection {
             current: Some(ADDR_B),
             previous: vec![ADDR_A],
+            identity_account: None,
         },
         expected_previous_owner: Some(ADDR_A),
     },
@@ -55,6 +59,7 @@ struct ApplyTransferStep {
         expected_projection: OclOwnershipProjection {
             current: Some(ADDR_C),
             previous: vec![ADDR_A, ADDR_B],
+            identity_account: None,
         },
         expected_previous_owner: Some(ADDR_B),
     },
@@ -63,6 +68,7 @@ struct ApplyTransferStep {
         expected_projection: OclOwnershipProjection {
             current: Some(ADDR_B),
             previous: vec![ADDR_A, ADDR_C],
+            identity_account: None,
         },
         expected_previous_owner: Some(ADDR_C),
     },
@@ -2,8 +2,8 @@ use std::str::FromStr;
 
 use alloy::primitives::{Address, address};
 use molecule_ocl::entities::{
-    OclId, OclOwnershipChange, OclOwnershipDiffMap, OclOwnershipProjection,
-    OclOwnershipProjectionMap, OclTransferEvent,
+    OclId, OclIdentityCreatedEvent, OclOwnershipChange, OclOwnershipDiffMap,
+    OclOwnershipProjection, OclOwnershipProjectionMap, OclTransferEvent,
 };
 use pretty_assertions::assert_eq;
 
@@ -21,7 +21,7 @@ const ADDR_C: Address = address!("0x3333333333333333333333333333333333333333");
     vec![OclTransferEvent { ocl_id: ocl_id_1(), from: Address::ZERO, to: ADDR_A }],
     expected_projections([(
         ocl_id_1(),
-        OclOwnershipProjection { current: Some(ADDR_A), previous: vec![] },
+        OclOwnershipProjection { current: Some(ADDR_A), previous: vec![], identity_account: None },
     )]),
     expected_diff([(
         ocl_id_1(),
@@ -36,7 +36,7 @@ const ADDR_C: Address = address!("0x3333333333333333333333333333333333333333");
     ],
     expected_projections([(
         ocl_id_1(),
-        OclOwnershipProjection { current: Some(ADDR_C), previous: vec![] },
+        OclOwnershipProjection { current: Some(ADDR_C), previous: vec![], identity_account: None },
     )]),
     expected_diff([(
         ocl_id_1(),
@@ -51,7 +51,7 @@ const ADDR_C: Address = address!("0x3333333333333333333333333333333333333333");
     ],
     expected_projections([(
         ocl_id_1(),
-        OclOwnershipProjection { current: Some(ADDR_A), previous: vec![] },
+        OclOwnershipProjection { current: Some(ADDR_A), previous: vec![], identity_account: None },
     )]),
     expected_diff([(
         ocl_id_1(),
@@ -64,8 +64,14 @@ const ADDR_C: Address = address!("0x3333333333333333333333333333333333333333");
         OclTransferEvent { ocl_id: ocl_id_2(), from: Address::ZERO, to: ADDR_B },
     ],
     expected_projections([
-        (ocl_id_1(), OclOwnershipProjection { current: Some(ADDR_A), previous: vec![] }),
-        (ocl_id_2(), OclOwnershipProjection { current: Some(ADDR_B), previous: vec![] }),
+        (
+            ocl_id_1(),
+            OclOwnershipProjection { current: Some(ADDR_A), previous: vec![], identity_account: None },
+        ),
+        (
+            ocl_id_2(),
+            OclOwnershipProjection { current: Some(ADDR_B), previous: vec![], identity_account: None },
+        ),
     ]),
     expected_diff([
         (ocl_id_1(), OclOwnershipChange { former_owner: None, current_owner: ADDR_A }),
@@ -99,6 +105,7 @@ fn test_process_events_one_by_one() {
                 OclOwnershipProjection {
                     current: Some(ADDR_A),
                     previous: vec![],
+                    identity_account: None,
                 }
             )]),
             projections
@@ -127,6 +134,7 @@ fn test_process_events_one_by_one() {
                 OclOwnershipProjection {
                     current: Some(ADDR_B),
                     previous: vec![ADDR_A],
+                    identity_account: None,
                 }
             )]),
             projections
@@ -155,6 +163,7 @@ fn test_process_events_one_by_one() {
                 OclOwnershipProjection {
                     current: Some(ADDR_C),
                     previous: vec![ADDR_A, ADDR_B],
+                    identity_account: None,
                 }
             )]),
             projections
@@ -183,6 +192,7 @@ fn test_process_events_one_by_one() {
                 OclOwnershipProjection {
                     current: Some(ADDR_A),
                     previous: vec![ADDR_B, ADDR_C],
+                    identity_account: None,
                 }
             )]),
             projections
@@ -200,6 +210,53 @@ fn test_process_events_one_by_one() {
     }
 }
 
+#[test]
+fn test_identity_created_before_first_transfer() {
+    const IDENTITY_ACCOUNT: Address = address!("0x4444444444444444444444444444444444444444");
+
+    let mut projections = OclOwnershipProjectionMap::default();
+
+    let changed_ocl_ids = projections.apply_identity_events(vec![OclIdentityCreatedEvent {
+        ocl_id: ocl_id_1(),
+        account: IDENTITY_ACCOUNT,
+    }]);
+    assert_eq!(vec![ocl_id_1()], changed_ocl_ids);
+
+    let diff = projections.apply_events(vec![OclTransferEvent {
+        ocl_id: ocl_id_1(),
+        from: Address::ZERO,
+        to: ADDR_A,
+    }]);
+
+    assert_eq!(
+        expected_projections([(
+            ocl_id_1(),
+            OclOwnershipProjection {
+                current: Some(ADDR_A),
+                previous: vec![],
+                identity_account: Some(IDENTITY_ACCOUNT),
+            }
+        )]),
+        projections
+    );
+    assert_eq!(
+        expected_diff([(
+            ocl_id_1(),
+            OclOwnershipChange {
+                former_owner: None,
+                current_owner: ADDR_A,
+            }
+        )]),
+        diff
+    );
+    assert_eq!(
+        Some(ocl_id_1()),
+        projections
+            .find_by_identity_account(IDENTITY_ACCOUNT)
+            .map(|(ocl_id, _)| *ocl_id)
+    );
+}
+
 // Helpers
 
 fn ocl_id_1() -> OclId {
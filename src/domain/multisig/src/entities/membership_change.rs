@@ -0,0 +1,10 @@
+use alloy::primitives::Address;
+
+/// Change of the signers of a multisig (or of the number of their
+/// confirmations required) signalled by a contract event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MembershipChange {
+    Added { owner: Address },
+    Removed { owner: Address },
+    ThresholdChanged { threshold: u64 },
+}
@@ -0,0 +1,5 @@
+mod membership_change;
+mod multisig_owners;
+
+pub use membership_change::*;
+pub use multisig_owners::*;
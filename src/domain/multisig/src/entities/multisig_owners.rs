@@ -0,0 +1,13 @@
+use std::collections::HashSet;
+
+use alloy::primitives::Address;
+
+/// Owners of a multisig as of a block
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MultisigOwners {
+    pub current_owners: HashSet<Address>,
+    /// Accounts that were owners before, but are not anymore
+    pub former_owners: HashSet<Address>,
+    /// Number of owner confirmations required for a transaction
+    pub threshold: u64,
+}
@@ -1,3 +1,7 @@
+mod multisig_provider;
+mod multisig_provider_registry;
 mod multisig_resolver;
 
+pub use multisig_provider::*;
+pub use multisig_provider_registry::*;
 pub use multisig_resolver::*;
@@ -0,0 +1,22 @@
+use std::collections::HashSet;
+
+use alloy::primitives::{B256, Log};
+
+use crate::entities::MembershipChange;
+use crate::services::MultisigResolver;
+
+/// Kind of smart account (e.g. Safe) whose signers are considered owners of
+/// the OCLs it owns.
+///
+/// The resolver detects the kind: it returns `None` for contracts of other
+/// kinds.
+pub trait MultisigProvider: MultisigResolver {
+    /// Name for logs, e.g. `safe`
+    fn kind(&self) -> &'static str;
+
+    /// Signatures of the events that signal membership changes
+    fn membership_event_signatures(&self) -> HashSet<B256>;
+
+    /// Parses a log of one of the membership events
+    fn parse_membership_event(&self, log: &Log) -> eyre::Result<MembershipChange>;
+}
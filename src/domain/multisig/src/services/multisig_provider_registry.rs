@@ -0,0 +1,108 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use alloy::primitives::{Address, B256, Log};
+use eyre::ContextCompat as _;
+
+use crate::entities::{MembershipChange, MultisigOwners};
+use crate::services::{MultisigProvider, MultisigResolver};
+
+/// Supported multisig kinds. An address is resolved by the first provider
+/// that detects its kind.
+pub struct MultisigProviderRegistry {
+    providers: Vec<Arc<dyn MultisigProvider>>,
+    /// If several providers declare the same signature, the first one is used
+    providers_by_event_signature: HashMap<B256, Arc<dyn MultisigProvider>>,
+}
+
+impl MultisigProviderRegistry {
+    pub fn new(providers: Vec<Arc<dyn MultisigProvider>>) -> Self {
+        let mut providers_by_event_signature = HashMap::new();
+        for provider in &providers {
+            for event_signature in provider.membership_event_signatures() {
+                providers_by_event_signature
+                    .entry(event_signature)
+                    .or_insert_with(|| provider.clone());
+            }
+        }
+
+        Self {
+            providers,
+            providers_by_event_signature,
+        }
+    }
+
+    /// Signatures of the membership events of all providers
+    pub fn membership_event_signatures(&self) -> HashSet<B256> {
+        self.providers_by_event_signature.keys().copied().collect()
+    }
+
+    /// Parses a log of one of the membership events
+    pub fn parse_membership_event(&self, log: &Log) -> eyre::Result<MembershipChange> {
+        let event_signature_hash = log.topics().first().context("Missing log topics")?;
+
+        let provider = self
+            .providers_by_event_signature
+            .get(event_signature_hash)
+            .with_context(|| {
+                format!("Unknown multisig event signature hash: {event_signature_hash}")
+            })?;
+
+        provider.parse_membership_event(log)
+    }
+}
+
+#[async_trait::async_trait]
+impl MultisigResolver for MultisigProviderRegistry {
+    async fn get_multisig_owners(
+        &self,
+        address: Address,
+        to_block: u64,
+    ) -> eyre::Result<Option<MultisigOwners>> {
+        for provider in &self.providers {
+            if let Some(multisig_owners) = provider.get_multisig_owners(address, to_block).await? {
+                tracing::debug!(%address, kind = provider.kind(), "Multisig detected");
+                return Ok(Some(multisig_owners));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn get_multisigs_owners(
+        &self,
+        addresses: Vec<Address>,
+        to_block: u64,
+    ) -> eyre::Result<HashMap<Address, Option<MultisigOwners>>> {
+        let mut multisigs_owners = HashMap::with_capacity(addresses.len());
+        let mut unresolved_addresses = addresses;
+
+        for provider in &self.providers {
+            if unresolved_addresses.is_empty() {
+                break;
+            }
+
+            let mut resolved = provider
+                .get_multisigs_owners(unresolved_addresses.clone(), to_block)
+                .await?;
+
+            unresolved_addresses.retain(|address| {
+                let Some(multisig_owners) = resolved.remove(address).flatten() else {
+                    return true;
+                };
+
+                multisigs_owners.insert(*address, Some(multisig_owners));
+                false
+            });
+        }
+
+        // NOTE: Not a multisig of any supported kind
+        multisigs_owners.extend(
+            unresolved_addresses
+                .into_iter()
+                .map(|address| (address, None)),
+        );
+
+        Ok(multisigs_owners)
+    }
+}
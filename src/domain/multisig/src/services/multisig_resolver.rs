@@ -1,10 +1,33 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 use alloy::primitives::Address;
 
+use crate::entities::MultisigOwners;
+
 #[cfg_attr(any(feature = "testing", test), mockall::automock)]
 #[async_trait::async_trait]
-pub trait MultisigResolver {
-    async fn get_multisig_owners(&self, address: Address)
-    -> eyre::Result<Option<HashSet<Address>>>;
+pub trait MultisigResolver: Send + Sync {
+    /// Returns owners of the multisig as of the block, or `None` if the
+    /// address is not a multisig
+    async fn get_multisig_owners(
+        &self,
+        address: Address,
+        to_block: u64,
+    ) -> eyre::Result<Option<MultisigOwners>>;
+
+    /// Same as [`MultisigResolver::get_multisig_owners`] for several
+    /// addresses. By default, the addresses are resolved one by one.
+    async fn get_multisigs_owners(
+        &self,
+        addresses: Vec<Address>,
+        to_block: u64,
+    ) -> eyre::Result<HashMap<Address, Option<MultisigOwners>>> {
+        let mut multisigs_owners = HashMap::with_capacity(addresses.len());
+        for address in addresses {
+            let maybe_multisig_owners = self.get_multisig_owners(address, to_block).await?;
+            multisigs_owners.insert(address, maybe_multisig_owners);
+        }
+
+        Ok(multisigs_owners)
+    }
 }
@@ -0,0 +1,127 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use alloy::primitives::{Address, B256, Log, LogData, address, b256};
+use multisig::entities::{MembershipChange, MultisigOwners};
+use multisig::services::{MultisigProvider, MultisigProviderRegistry, MultisigResolver};
+
+const MULTISIG_A: Address = address!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+const MULTISIG_B: Address = address!("0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+const WALLET: Address = address!("0xcccccccccccccccccccccccccccccccccccccccc");
+const OWNER: Address = address!("0x1111111111111111111111111111111111111111");
+
+const EVENT_A: B256 = b256!("0x00000000000000000000000000000000000000000000000000000000000000aa");
+const EVENT_B: B256 = b256!("0x00000000000000000000000000000000000000000000000000000000000000bb");
+
+#[tokio::test]
+async fn test_address_is_resolved_by_the_provider_of_its_kind() {
+    let registry = registry();
+
+    assert_eq!(
+        Some(owners(2)),
+        registry.get_multisig_owners(MULTISIG_B, 100).await.unwrap()
+    );
+    assert_eq!(
+        None,
+        registry.get_multisig_owners(WALLET, 100).await.unwrap()
+    );
+
+    assert_eq!(
+        HashMap::from([
+            (MULTISIG_A, Some(owners(1))),
+            (MULTISIG_B, Some(owners(2))),
+            (WALLET, None),
+        ]),
+        registry
+            .get_multisigs_owners(vec![MULTISIG_A, MULTISIG_B, WALLET], 100)
+            .await
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_membership_events_are_parsed_by_the_declaring_provider() {
+    let registry = registry();
+
+    assert_eq!(
+        HashSet::from([EVENT_A, EVENT_B]),
+        registry.membership_event_signatures()
+    );
+    assert_eq!(
+        MembershipChange::Added { owner: OWNER },
+        registry.parse_membership_event(&log(EVENT_A)).unwrap()
+    );
+    assert_eq!(
+        MembershipChange::Removed { owner: OWNER },
+        registry.parse_membership_event(&log(EVENT_B)).unwrap()
+    );
+    assert!(registry.parse_membership_event(&log(B256::ZERO)).is_err());
+}
+
+// Helpers
+
+/// Provider of a single multisig that signals membership changes with one
+/// event
+struct TestProvider {
+    multisig: Address,
+    owners: MultisigOwners,
+    event_signature: B256,
+    change: MembershipChange,
+}
+
+#[async_trait::async_trait]
+impl MultisigResolver for TestProvider {
+    async fn get_multisig_owners(
+        &self,
+        address: Address,
+        _to_block: u64,
+    ) -> eyre::Result<Option<MultisigOwners>> {
+        Ok((address == self.multisig).then(|| self.owners.clone()))
+    }
+}
+
+impl MultisigProvider for TestProvider {
+    fn kind(&self) -> &'static str {
+        "test"
+    }
+
+    fn membership_event_signatures(&self) -> HashSet<B256> {
+        HashSet::from([self.event_signature])
+    }
+
+    fn parse_membership_event(&self, _log: &Log) -> eyre::Result<MembershipChange> {
+        Ok(self.change)
+    }
+}
+
+fn registry() -> MultisigProviderRegistry {
+    MultisigProviderRegistry::new(vec![
+        Arc::new(TestProvider {
+            multisig: MULTISIG_A,
+            owners: owners(1),
+            event_signature: EVENT_A,
+            change: MembershipChange::Added { owner: OWNER },
+        }),
+        Arc::new(TestProvider {
+            multisig: MULTISIG_B,
+            owners: owners(2),
+            event_signature: EVENT_B,
+            change: MembershipChange::Removed { owner: OWNER },
+        }),
+    ])
+}
+
+fn owners(threshold: u64) -> MultisigOwners {
+    MultisigOwners {
+        current_owners: HashSet::from([OWNER]),
+        former_owners: HashSet::new(),
+        threshold,
+    }
+}
+
+fn log(event_signature: B256) -> Log {
+    Log {
+        address: MULTISIG_A,
+        data: LogData::new_unchecked(vec![event_signature], Default::default()),
+    }
+}
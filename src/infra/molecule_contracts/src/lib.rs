@@ -21,8 +21,16 @@ pub mod safe {
         sol!(Safe, "abis/Safe_1.5.0.json");
     }
 
-    pub use safe_utils::parse_safe_removed_owner_event;
+    pub use safe_utils::{parse_safe_added_owner_event, parse_safe_removed_owner_event};
 }
 
 // Actual version
 pub use safe::v1_5_0::Safe;
+
+// NOTE: Only the part needed to track holders of IP-tokens
+sol!(
+    #[sol(all_derives = true)]
+    interface IERC20 {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+);
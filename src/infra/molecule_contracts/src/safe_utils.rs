@@ -2,6 +2,24 @@ use alloy::primitives::{Address, Log};
 
 use crate::{Safe, safe};
 
+pub fn parse_safe_added_owner_event(log: &Log) -> eyre::Result<Address> {
+    use alloy::sol_types::SolEvent;
+
+    // NOTE: We can use the actual event signature hashes because
+    //       the indexed mark doesn't participate in hash calculation.
+
+    // First, try to parse the actual event signature (indexed "owner" field), ...
+    let added_owner = if let Ok(event) = Safe::AddedOwner::decode_log(log) {
+        event.owner
+    } else {
+        // Try to parse an old version event (w/o indexed mark) -- may be relevant for older Safe Wallet versions
+        let event = safe::v1_3_0::Safe::AddedOwner::decode_log(log)?;
+        event.owner
+    };
+
+    Ok(added_owner)
+}
+
 pub fn parse_safe_removed_owner_event(log: &Log) -> eyre::Result<Address> {
     use alloy::sol_types::SolEvent;
 
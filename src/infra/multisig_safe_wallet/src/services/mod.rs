@@ -1,3 +1,11 @@
+mod safe_contract_call_resolver;
+mod safe_multisig_provider;
+mod safe_on_chain_resolver;
+mod safe_owner_logs;
 mod safe_wallet_api_service;
 
+pub use safe_contract_call_resolver::*;
+pub use safe_multisig_provider::*;
+pub use safe_on_chain_resolver::*;
+pub use safe_owner_logs::*;
 pub use safe_wallet_api_service::*;
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use alloy::eips::BlockId;
+use alloy::primitives::Address;
+use alloy::providers::{CallItem, DynProvider, Provider};
+use alloy::sol_types::SolCall;
+use async_trait::async_trait;
+use molecule_contracts::Safe;
+use multisig::entities::MultisigOwners;
+use multisig::services::MultisigResolver;
+
+use crate::services::get_removed_safe_owners;
+
+/// Resolves Safe owners by calling `getOwners()` and `getThreshold()` on the
+/// contracts as of the indexed block, so that the owners are consistent with
+/// the indexed state. Calls for several Safes are batched via Multicall3.
+///
+/// Former owners are restored from `RemovedOwner` logs.
+pub struct SafeContractCallResolver {
+    rpc_client: DynProvider,
+}
+
+impl SafeContractCallResolver {
+    pub fn new(rpc_client: DynProvider) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Returns the addresses that have code at the block
+    async fn filter_contracts(
+        &self,
+        addresses: Vec<Address>,
+        block_id: BlockId,
+    ) -> eyre::Result<Vec<Address>> {
+        let codes = futures::future::try_join_all(addresses.iter().map(|address| async move {
+            self.rpc_client
+                .get_code_at(*address)
+                .block_id(block_id)
+                .await
+        }))
+        .await?;
+
+        Ok(addresses
+            .into_iter()
+            .zip(codes)
+            .filter_map(|(address, code)| (!code.is_empty()).then_some(address))
+            .collect())
+    }
+
+    /// Returns current owners and threshold of the contracts that are Safes
+    async fn call_safes(
+        &self,
+        addresses: &[Address],
+        block_id: BlockId,
+    ) -> eyre::Result<HashMap<Address, MultisigOwners>> {
+        // NOTE: Keep the request size reasonable for RPC nodes
+        const MAX_CALLS_PER_MULTICALL: usize = 100;
+
+        let mut safes = HashMap::new();
+
+        for addresses_chunk in addresses.chunks(MAX_CALLS_PER_MULTICALL) {
+            let mut owners_multicall = self
+                .rpc_client
+                .multicall()
+                .dynamic::<Safe::getOwnersCall>()
+                .block(block_id);
+            let mut threshold_multicall = self
+                .rpc_client
+                .multicall()
+                .dynamic::<Safe::getThresholdCall>()
+                .block(block_id);
+
+            for address in addresses_chunk {
+                // NOTE: Calls to contracts that are not Safes fail, which is allowed.
+                owners_multicall = owners_multicall.add_dynamic(
+                    CallItem::<Safe::getOwnersCall>::new(
+                        *address,
+                        Safe::getOwnersCall {}.abi_encode().into(),
+                    )
+                    .allow_failure(true),
+                );
+                threshold_multicall = threshold_multicall.add_dynamic(
+                    CallItem::<Safe::getThresholdCall>::new(
+                        *address,
+                        Safe::getThresholdCall {}.abi_encode().into(),
+                    )
+                    .allow_failure(true),
+                );
+            }
+
+            let owners_results = owners_multicall.aggregate3().await?;
+            let threshold_results = threshold_multicall.aggregate3().await?;
+
+            for ((address, owners_result), threshold_result) in addresses_chunk
+                .iter()
+                .zip(owners_results)
+                .zip(threshold_results)
+            {
+                let (Ok(owners), Ok(threshold)) = (owners_result, threshold_result) else {
+                    tracing::debug!(%address, "Not a Safe multisig: calls failed");
+                    continue;
+                };
+
+                safes.insert(
+                    *address,
+                    MultisigOwners {
+                        current_owners: owners.into_iter().collect(),
+                        former_owners: Default::default(),
+                        threshold: u64::try_from(threshold)?,
+                    },
+                );
+            }
+        }
+
+        Ok(safes)
+    }
+}
+
+#[async_trait]
+impl MultisigResolver for SafeContractCallResolver {
+    #[tracing::instrument(level = "debug", skip_all, fields(address = %address, to_block = %to_block))]
+    async fn get_multisig_owners(
+        &self,
+        address: Address,
+        to_block: u64,
+    ) -> eyre::Result<Option<MultisigOwners>> {
+        let mut multisigs_owners = self.get_multisigs_owners(vec![address], to_block).await?;
+
+        Ok(multisigs_owners.remove(&address).flatten())
+    }
+
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(addresses_count = addresses.len(), to_block = %to_block)
+    )]
+    async fn get_multisigs_owners(
+        &self,
+        addresses: Vec<Address>,
+        to_block: u64,
+    ) -> eyre::Result<HashMap<Address, Option<MultisigOwners>>> {
+        let block_id = BlockId::number(to_block);
+
+        // Cheap calls (blockchain)
+        let contracts = self.filter_contracts(addresses.clone(), block_id).await?;
+        let mut safes = self.call_safes(&contracts, block_id).await?;
+
+        let mut removed_owners_map = if safes.is_empty() {
+            HashMap::new()
+        } else {
+            get_removed_safe_owners(&self.rpc_client, safes.keys().copied().collect(), to_block)
+                .await?
+        };
+        for (address, multisig_owners) in &mut safes {
+            multisig_owners.former_owners = removed_owners_map
+                .remove(address)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|removed_owner| !multisig_owners.current_owners.contains(removed_owner))
+                .collect();
+        }
+
+        Ok(addresses
+            .into_iter()
+            .map(|address| (address, safes.remove(&address)))
+            .collect())
+    }
+}
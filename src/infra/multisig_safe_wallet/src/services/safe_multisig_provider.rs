@@ -0,0 +1,79 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use alloy::primitives::{Address, B256, Log};
+use async_trait::async_trait;
+use eyre::bail;
+use molecule_contracts::Safe;
+use molecule_contracts::prelude::*;
+use molecule_contracts::safe::{parse_safe_added_owner_event, parse_safe_removed_owner_event};
+use multisig::entities::{MembershipChange, MultisigOwners};
+use multisig::services::{MultisigProvider, MultisigResolver};
+
+/// Safe multisig wallet. Owners are resolved by one of the Safe resolvers
+/// (see [`crate::services::SafeOnChainResolver`]).
+pub struct SafeMultisigProvider {
+    resolver: Arc<dyn MultisigResolver>,
+}
+
+impl SafeMultisigProvider {
+    pub fn new(resolver: Arc<dyn MultisigResolver>) -> Self {
+        Self { resolver }
+    }
+}
+
+#[async_trait]
+impl MultisigResolver for SafeMultisigProvider {
+    async fn get_multisig_owners(
+        &self,
+        address: Address,
+        to_block: u64,
+    ) -> eyre::Result<Option<MultisigOwners>> {
+        self.resolver.get_multisig_owners(address, to_block).await
+    }
+
+    async fn get_multisigs_owners(
+        &self,
+        addresses: Vec<Address>,
+        to_block: u64,
+    ) -> eyre::Result<HashMap<Address, Option<MultisigOwners>>> {
+        self.resolver
+            .get_multisigs_owners(addresses, to_block)
+            .await
+    }
+}
+
+impl MultisigProvider for SafeMultisigProvider {
+    fn kind(&self) -> &'static str {
+        "safe"
+    }
+
+    fn membership_event_signatures(&self) -> HashSet<B256> {
+        HashSet::from_iter([
+            Safe::AddedOwner::SIGNATURE_HASH,
+            Safe::RemovedOwner::SIGNATURE_HASH,
+            Safe::ChangedThreshold::SIGNATURE_HASH,
+        ])
+    }
+
+    fn parse_membership_event(&self, log: &Log) -> eyre::Result<MembershipChange> {
+        match log.topics().first() {
+            Some(&Safe::AddedOwner::SIGNATURE_HASH) => Ok(MembershipChange::Added {
+                owner: parse_safe_added_owner_event(log)?,
+            }),
+            Some(&Safe::RemovedOwner::SIGNATURE_HASH) => Ok(MembershipChange::Removed {
+                owner: parse_safe_removed_owner_event(log)?,
+            }),
+            Some(&Safe::ChangedThreshold::SIGNATURE_HASH) => {
+                let event = Safe::ChangedThreshold::decode_log(log)?.data;
+
+                Ok(MembershipChange::ThresholdChanged {
+                    threshold: u64::try_from(event.threshold)?,
+                })
+            }
+            unknown_event_signature_hash => {
+                bail!("Unknown Safe event signature hash: {unknown_event_signature_hash:?}")
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use alloy::eips::BlockId;
+use alloy::primitives::Address;
+use alloy::providers::{DynProvider, Provider};
+use alloy_ext::prelude::*;
+use async_trait::async_trait;
+use eyre::{ContextCompat as _, bail};
+use molecule_contracts::Safe;
+use molecule_contracts::prelude::*;
+use molecule_contracts::safe::{parse_safe_added_owner_event, parse_safe_removed_owner_event};
+use multisig::entities::MultisigOwners;
+use multisig::services::MultisigResolver;
+
+/// Resolves Safe owners from the contract logs only: `SafeSetup`,
+/// `AddedOwner`, `RemovedOwner` and `ChangedThreshold` events are replayed
+/// from the creation of the Safe.
+///
+/// Safes before v1.3.0 don't emit `SafeSetup`, so their initial owners are
+/// unknown from the logs. Such Safes are resolved by the legacy resolver
+/// (e.g. [`crate::services::SafeWalletApiService`]), if set.
+pub struct SafeOnChainResolver {
+    rpc_client: DynProvider,
+    maybe_legacy_resolver: Option<Arc<dyn MultisigResolver>>,
+}
+
+impl SafeOnChainResolver {
+    pub fn new(
+        rpc_client: DynProvider,
+        maybe_legacy_resolver: Option<Arc<dyn MultisigResolver>>,
+    ) -> Self {
+        Self {
+            rpc_client,
+            maybe_legacy_resolver,
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(address = %address))]
+    async fn is_contract(&self, address: Address, to_block: u64) -> eyre::Result<bool> {
+        // NOTE: Pinned to the indexed block, so that a Safe deployed later is not
+        //       resolved from logs that don't include its setup.
+        let code = self
+            .rpc_client
+            .get_code_at(address)
+            .block_id(BlockId::number(to_block))
+            .await?;
+        Ok(!code.is_empty())
+    }
+
+    async fn get_safe_owner_events(
+        &self,
+        address: Address,
+        to_block: u64,
+    ) -> eyre::Result<Vec<SafeOwnerEvent>> {
+        let mut events = Vec::new();
+
+        self.rpc_client
+            .get_logs_ext(
+                vec![address],
+                HashSet::from_iter([
+                    Safe::SafeSetup::SIGNATURE_HASH,
+                    Safe::AddedOwner::SIGNATURE_HASH,
+                    Safe::RemovedOwner::SIGNATURE_HASH,
+                    Safe::ChangedThreshold::SIGNATURE_HASH,
+                ]),
+                0, // From the beginning
+                to_block,
+                &mut |logs_chunk| {
+                    for log in logs_chunk.logs {
+                        let block_number = log.block_number.context("Missing log block number")?;
+                        let log_index = log.log_index.context("Missing log index")?;
+
+                        let event = match log.event_signature_hash() {
+                            Safe::SafeSetup::SIGNATURE_HASH => {
+                                let event = Safe::SafeSetup::decode_log(&log.inner)?.data;
+
+                                SafeOwnerEvent::SafeSetup {
+                                    owners: event.owners,
+                                    threshold: u64::try_from(event.threshold)?,
+                                }
+                            }
+                            Safe::AddedOwner::SIGNATURE_HASH => SafeOwnerEvent::AddedOwner {
+                                owner: parse_safe_added_owner_event(&log.inner)?,
+                            },
+                            Safe::RemovedOwner::SIGNATURE_HASH => SafeOwnerEvent::RemovedOwner {
+                                owner: parse_safe_removed_owner_event(&log.inner)?,
+                            },
+                            Safe::ChangedThreshold::SIGNATURE_HASH => {
+                                let event = Safe::ChangedThreshold::decode_log(&log.inner)?.data;
+
+                                SafeOwnerEvent::ChangedThreshold {
+                                    threshold: u64::try_from(event.threshold)?,
+                                }
+                            }
+                            unknown_event_signature_hash => {
+                                bail!("Unknown Safe event signature hash: {unknown_event_signature_hash}")
+                            }
+                        };
+
+                        events.push((block_number, log_index, event));
+                    }
+
+                    Ok(())
+                },
+            )
+            .await?;
+
+        // NOTE: Keep the order in which events happened on the chain
+        events.sort_by_key(|(block_number, log_index, _)| (*block_number, *log_index));
+
+        Ok(events.into_iter().map(|(_, _, event)| event).collect())
+    }
+}
+
+#[async_trait]
+impl MultisigResolver for SafeOnChainResolver {
+    #[tracing::instrument(level = "debug", skip_all, fields(address = %address, to_block = %to_block))]
+    async fn get_multisig_owners(
+        &self,
+        address: Address,
+        to_block: u64,
+    ) -> eyre::Result<Option<MultisigOwners>> {
+        // Cheap call (blockchain)
+        if !self.is_contract(address, to_block).await? {
+            return Ok(None);
+        }
+
+        let events = self.get_safe_owner_events(address, to_block).await?;
+        if let Some(multisig_owners) = replay_safe_owner_events(events) {
+            return Ok(Some(multisig_owners));
+        }
+
+        // Either a Safe before v1.3.0 or not a Safe at all
+        let Some(legacy_resolver) = &self.maybe_legacy_resolver else {
+            tracing::warn!(
+                "No SafeSetup event found and there is no legacy resolver, \
+                 considering the contract not a multisig"
+            );
+            return Ok(None);
+        };
+
+        legacy_resolver.get_multisig_owners(address, to_block).await
+    }
+}
+
+/// Event of a Safe that affects its owners
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SafeOwnerEvent {
+    SafeSetup {
+        owners: Vec<Address>,
+        threshold: u64,
+    },
+    AddedOwner {
+        owner: Address,
+    },
+    RemovedOwner {
+        owner: Address,
+    },
+    ChangedThreshold {
+        threshold: u64,
+    },
+}
+
+/// Replays events of a Safe in the order they happened on the chain.
+///
+/// Returns `None` if the events don't start with `SafeSetup`, so the initial
+/// owners are unknown.
+pub fn replay_safe_owner_events(
+    events: impl IntoIterator<Item = SafeOwnerEvent>,
+) -> Option<MultisigOwners> {
+    let mut events = events.into_iter();
+
+    let Some(SafeOwnerEvent::SafeSetup { owners, threshold }) = events.next() else {
+        return None;
+    };
+
+    let mut multisig_owners = MultisigOwners {
+        current_owners: owners.into_iter().collect(),
+        former_owners: HashSet::new(),
+        threshold,
+    };
+
+    for event in events {
+        match event {
+            SafeOwnerEvent::SafeSetup { .. } => {
+                // NOTE: A Safe can only be set up once.
+                tracing::warn!("Skip repeated SafeSetup event");
+            }
+            SafeOwnerEvent::AddedOwner { owner } => {
+                multisig_owners.current_owners.insert(owner);
+                multisig_owners.former_owners.remove(&owner);
+            }
+            SafeOwnerEvent::RemovedOwner { owner } => {
+                multisig_owners.current_owners.remove(&owner);
+                multisig_owners.former_owners.insert(owner);
+            }
+            SafeOwnerEvent::ChangedThreshold { threshold } => {
+                multisig_owners.threshold = threshold;
+            }
+        }
+    }
+
+    Some(multisig_owners)
+}
@@ -0,0 +1,51 @@
+use std::collections::{HashMap, HashSet};
+
+use alloy::primitives::Address;
+use alloy::providers::DynProvider;
+use alloy_ext::prelude::*;
+use eyre::bail;
+use molecule_contracts::Safe;
+use molecule_contracts::prelude::*;
+use molecule_contracts::safe::parse_safe_removed_owner_event;
+
+/// Returns accounts removed from the owners of the Safes up to the block
+/// (inclusive), including the ones that were added back later
+pub async fn get_removed_safe_owners(
+    rpc_client: &DynProvider,
+    addresses: Vec<Address>,
+    to_block: u64,
+) -> eyre::Result<HashMap<Address, HashSet<Address>>> {
+    let mut removed_owners_map = HashMap::<Address, HashSet<Address>>::new();
+
+    rpc_client
+        .get_logs_ext(
+            addresses,
+            HashSet::from_iter([Safe::RemovedOwner::SIGNATURE_HASH]),
+            0, // From the beginning
+            to_block,
+            &mut |logs_chunk| {
+                for log in logs_chunk.logs {
+                    match log.event_signature_hash() {
+                        Safe::RemovedOwner::SIGNATURE_HASH => {
+                            let removed_owner = parse_safe_removed_owner_event(&log.inner)?;
+
+                            removed_owners_map
+                                .entry(log.address())
+                                .or_default()
+                                .insert(removed_owner);
+                        }
+                        unknown_event_signature_hash => {
+                            bail!(
+                                "Unknown Safe event signature hash: {unknown_event_signature_hash}"
+                            )
+                        }
+                    }
+                }
+
+                Ok(())
+            },
+        )
+        .await?;
+
+    Ok(removed_owners_map)
+}
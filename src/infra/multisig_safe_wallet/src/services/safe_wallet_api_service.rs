@@ -4,10 +4,17 @@ use alloy::primitives::Address;
 use alloy::providers::{DynProvider, Provider};
 use async_trait::async_trait;
 use eyre::bail;
+use multisig::entities::MultisigOwners;
 use multisig::services::MultisigResolver;
 use serde::{Deserialize, Serialize};
 
-/// Safe Wallet Service for interacting with Safe Transaction Service API
+use crate::services::get_removed_safe_owners;
+
+/// Safe Wallet Service for interacting with Safe Transaction Service API.
+///
+/// The API only returns the current owners, so former owners are restored
+/// from `RemovedOwner` logs. Needed for Safes before v1.3.0 that don't emit
+/// `SafeSetup` (see [`crate::services::SafeOnChainResolver`]).
 #[derive(Clone)]
 pub struct SafeWalletApiService {
     api_base_url: &'static str,
@@ -62,11 +69,12 @@ impl SafeWalletApiService {
 
 #[async_trait]
 impl MultisigResolver for SafeWalletApiService {
-    #[tracing::instrument(level = "debug", skip_all, fields(address = %address))]
+    #[tracing::instrument(level = "debug", skip_all, fields(address = %address, to_block = %to_block))]
     async fn get_multisig_owners(
         &self,
         address: Address,
-    ) -> eyre::Result<Option<HashSet<Address>>> {
+        to_block: u64,
+    ) -> eyre::Result<Option<MultisigOwners>> {
         // Cheap call (blockchain)
         if !self.is_contract(address).await? {
             return Ok(None);
@@ -95,12 +103,33 @@ impl MultisigResolver for SafeWalletApiService {
         struct SafeInfoResponseLike {
             pub address: Address,
             pub owners: Vec<Address>,
+            pub threshold: u64,
         }
 
         let response: SafeInfoResponseLike = response.json().await?;
         assert_eq!(address, response.address);
 
-        let owners = response.owners.into_iter().collect::<HashSet<_>>();
-        Ok(Some(owners))
+        // Safe Wallet before v1.3.0 did not have the SafeSetup event that would allow using logs
+        // only to restore the full ownership history (https://github.com/safe-global/safe-smart-account/issues/233).
+        // Therefore, we use the current owners list from the API and the for former owners from the RemovedOwner event.
+
+        let current_owners = response.owners.into_iter().collect::<HashSet<_>>();
+
+        let mut removed_owners_map =
+            get_removed_safe_owners(&self.rpc_client, vec![address], to_block).await?;
+        let former_owners = removed_owners_map
+            .remove(&address)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|removed_owner| !current_owners.contains(removed_owner))
+            .collect();
+
+        let multisig_owners = MultisigOwners {
+            current_owners,
+            former_owners,
+            threshold: response.threshold,
+        };
+
+        Ok(Some(multisig_owners))
     }
 }
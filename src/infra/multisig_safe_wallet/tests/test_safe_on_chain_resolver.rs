@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+
+use alloy::primitives::{Address, address};
+use multisig::entities::MultisigOwners;
+use multisig_safe_wallet::services::{SafeOwnerEvent, replay_safe_owner_events};
+
+const OWNER_A: Address = address!("0x1111111111111111111111111111111111111111");
+const OWNER_B: Address = address!("0x2222222222222222222222222222222222222222");
+const OWNER_C: Address = address!("0x3333333333333333333333333333333333333333");
+
+#[test]
+fn test_replay_owner_changes() {
+    let events = vec![
+        SafeOwnerEvent::SafeSetup {
+            owners: vec![OWNER_A, OWNER_B],
+            threshold: 2,
+        },
+        SafeOwnerEvent::AddedOwner { owner: OWNER_C },
+        SafeOwnerEvent::ChangedThreshold { threshold: 3 },
+        SafeOwnerEvent::RemovedOwner { owner: OWNER_A },
+        SafeOwnerEvent::RemovedOwner { owner: OWNER_B },
+        SafeOwnerEvent::ChangedThreshold { threshold: 1 },
+        // Former owner is added back
+        SafeOwnerEvent::AddedOwner { owner: OWNER_B },
+    ];
+
+    assert_eq!(
+        Some(MultisigOwners {
+            current_owners: HashSet::from([OWNER_B, OWNER_C]),
+            former_owners: HashSet::from([OWNER_A]),
+            threshold: 1,
+        }),
+        replay_safe_owner_events(events)
+    );
+}
+
+#[test]
+fn test_replay_without_safe_setup() {
+    // NOTE: Safes before v1.3.0 don't emit SafeSetup, so the initial owners are unknown.
+    assert_eq!(
+        None,
+        replay_safe_owner_events(vec![SafeOwnerEvent::AddedOwner { owner: OWNER_A }])
+    );
+    assert_eq!(None, replay_safe_owner_events(Vec::new()));
+}
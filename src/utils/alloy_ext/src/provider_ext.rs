@@ -8,6 +8,13 @@ use alloy::transports::{RpcError, TransportErrorKind};
 use async_trait::async_trait;
 use eyre::{ContextCompat, bail};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHashes {
+    pub number: u64,
+    pub hash: B256,
+    pub parent_hash: B256,
+}
+
 pub struct LogsChunk {
     pub from_block: u64,
     pub to_block: u64,
@@ -28,6 +35,13 @@ pub trait ProviderExt {
         F: FnMut(LogsChunk) -> eyre::Result<()> + Send + Sync;
 
     async fn latest_finalized_block_number(&self) -> eyre::Result<u64>;
+
+    async fn latest_block_number_by_tag(&self, tag: BlockNumberOrTag) -> eyre::Result<u64>;
+
+    async fn block_hashes(&self, block_number: u64) -> eyre::Result<BlockHashes>;
+
+    /// Returns `None` if the node has not seen the block yet
+    async fn maybe_block_hashes(&self, block_number: u64) -> eyre::Result<Option<BlockHashes>>;
 }
 
 #[async_trait]
@@ -72,13 +86,36 @@ impl ProviderExt for DynProvider {
     }
 
     async fn latest_finalized_block_number(&self) -> eyre::Result<u64> {
+        self.latest_block_number_by_tag(BlockNumberOrTag::Finalized)
+            .await
+    }
+
+    async fn latest_block_number_by_tag(&self, tag: BlockNumberOrTag) -> eyre::Result<u64> {
         let block = self
-            .get_block_by_number(BlockNumberOrTag::Finalized)
+            .get_block_by_number(tag)
             .await?
-            .context("Latest finalized block is missed")?;
+            .with_context(|| format!("Latest {tag} block is missed"))?;
 
         Ok(block.header.number)
     }
+
+    async fn block_hashes(&self, block_number: u64) -> eyre::Result<BlockHashes> {
+        self.maybe_block_hashes(block_number)
+            .await?
+            .with_context(|| format!("Block {block_number} is missed"))
+    }
+
+    async fn maybe_block_hashes(&self, block_number: u64) -> eyre::Result<Option<BlockHashes>> {
+        let maybe_block = self
+            .get_block_by_number(BlockNumberOrTag::Number(block_number))
+            .await?;
+
+        Ok(maybe_block.map(|block| BlockHashes {
+            number: block.header.number,
+            hash: block.header.hash,
+            parent_hash: block.header.parent_hash,
+        }))
+    }
 }
 
 #[tracing::instrument(